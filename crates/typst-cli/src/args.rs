@@ -83,6 +83,9 @@ pub enum Command {
     /// Processes an input file to extract provided metadata
     Query(QueryCommand),
 
+    /// Checks an input file for style problems
+    Lint(LintCommand),
+
     /// Lists all discovered fonts in system and custom font paths
     Fonts(FontsCommand),
 
@@ -98,12 +101,18 @@ pub struct CompileCommand {
     #[clap(flatten)]
     pub common: SharedArgs,
 
-    /// Path to output file (PDF, PNG or SVG). Use `-` to write output to stdout.
+    /// Path to output file (PDF, PNG, JPEG, WebP, AVIF, TIFF, SVG, PS, EPS, JSON, TXT,
+    /// or MD). Use `-` to write output to stdout.
+    ///
+    /// JSON output contains a dump of the laid-out pages, including the positions of
+    /// all text, shapes, images, links, and element locations. TXT and MD output
+    /// contain the document's text in reading order as plain text or Markdown.
     ///
-    /// For output formats emitting one file per page (PNG & SVG), a page number template
-    /// must be present if the source document renders to multiple pages. Use `{p}` for page
-    /// numbers, `{0p}` for zero padded page numbers and `{t}` for page count. For example,
-    /// `page-{0p}-of-{t}.png` creates `page-01-of-10.png`, `page-02-of-10.png` and so on.
+    /// For output formats emitting one file per page (all but PDF, TIFF, PS, JSON,
+    /// TXT, and MD), a page number template must be present if the source document
+    /// renders to multiple pages. Use `{p}` for page numbers, `{0p}` for zero padded
+    /// page numbers and `{t}` for page count. For example, `page-{0p}-of-{t}.png`
+    /// creates `page-01-of-10.png`, `page-02-of-10.png` and so on.
     #[clap(
         required_if_eq("input", "-"),
         value_parser = make_output_value_parser(),
@@ -138,10 +147,54 @@ pub struct CompileCommand {
     #[arg(long = "open", value_name = "VIEWER")]
     pub open: Option<Option<String>>,
 
-    /// The PPI (pixels per inch) to use for PNG export
+    /// The PPI (pixels per inch) to use for raster image export. TIFF files
+    /// also record it as their resolution
     #[arg(long = "ppi", default_value_t = 144.0)]
     pub ppi: f32,
 
+    /// The quality (1-100) to use for JPEG, WebP, and AVIF export. Defaults
+    /// to 90 for JPEG and AVIF. WebP images are lossless unless this is given.
+    /// PNG images are always lossless and reject this option
+    #[arg(
+        long = "quality",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub quality: Option<u8>,
+
+    /// The background of raster images. By default, the pages' fills are
+    /// used. JPEG images always have a white background
+    #[arg(long = "background")]
+    pub background: Option<RasterBackground>,
+
+    /// Disables anti-aliasing in raster images, so that every pixel is either
+    /// fully drawn or not at all. Useful for e-ink displays and printers
+    #[arg(long = "no-anti-alias")]
+    pub no_anti_alias: bool,
+
+    /// Rounds font sizes and baselines of text in raster images to whole
+    /// pixels, which makes small text crisper
+    #[arg(long = "snap-text")]
+    pub snap_text: bool,
+
+    /// Snaps glyphs in raster images to the pixel grid
+    #[arg(long = "snap-to-pixels")]
+    pub snap_to_pixels: bool,
+
+    /// The gamma with which text in raster images is blended. Values above 1
+    /// make text appear bolder
+    #[arg(long = "gamma", default_value_t = 1.0, value_parser = parse_gamma)]
+    pub gamma: f32,
+
+    /// Reduces TIFF images to black and white, either by thresholding or by
+    /// dithering
+    #[arg(long = "monochrome")]
+    pub monochrome: Option<Monochrome>,
+
+    /// The compression of TIFF images. Defaults to CCITT Group 4 for
+    /// monochrome images and to LZW otherwise
+    #[arg(long = "tiff-compression")]
+    pub tiff_compression: Option<TiffCompression>,
+
     /// Produces performance timings of the compilation process (experimental)
     ///
     /// The resulting JSON file can be loaded into a tracing tool such as
@@ -154,6 +207,45 @@ pub struct CompileCommand {
     /// conformance with.
     #[arg(long = "pdf-standard", value_delimiter = ',')]
     pub pdf_standard: Vec<PdfStandard>,
+
+    /// Writes a linearized PDF that viewers can start displaying before it
+    /// has been fully downloaded ("fast web view")
+    #[arg(long = "pdf-linearize")]
+    pub pdf_linearize: bool,
+
+    /// A CMYK ICC profile of the printing condition the PDF is intended for.
+    /// The file name (without extension) is used as the condition's
+    /// identifier
+    #[arg(long = "pdf-output-intent", value_name = "ICC_PROFILE")]
+    pub pdf_output_intent: Option<PathBuf>,
+
+    /// How text is represented in SVG export
+    #[arg(long = "svg-text", default_value = "paths")]
+    pub svg_text: SvgText,
+
+    /// Embeds subsets of the used fonts into SVGs, so that text is displayed
+    /// correctly when exported as `<text>` elements
+    #[arg(long = "svg-embed-fonts")]
+    pub svg_embed_fonts: bool,
+
+    /// Writes images as separate files next to the SVG instead of embedding
+    /// them
+    #[arg(long = "svg-external-images")]
+    pub svg_external_images: bool,
+
+    /// A prefix for all ids in SVGs, so that several of them can be inlined
+    /// into the same HTML page
+    #[arg(
+        long = "svg-id-prefix",
+        value_name = "PREFIX",
+        default_value = "",
+        value_parser = parse_id_prefix
+    )]
+    pub svg_id_prefix: String,
+
+    /// Omits the page background from SVGs
+    #[arg(long = "svg-no-background")]
+    pub svg_no_background: bool,
 }
 
 /// A PDF standard that Typst can enforce conformance with.
@@ -168,6 +260,44 @@ pub enum PdfStandard {
     A_2b,
 }
 
+/// The background of raster images.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum RasterBackground {
+    /// Transparent pixels are put onto white.
+    White,
+    /// The page fill is omitted.
+    Transparent,
+}
+
+/// How images are reduced to black and white.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum Monochrome {
+    /// Pixels darker than medium gray become black, all others white.
+    Threshold,
+    /// Shades of gray are approximated by patterns of black and white pixels.
+    Dither,
+}
+
+/// The compression of TIFF images.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum TiffCompression {
+    /// Lossless LZW compression.
+    Lzw,
+    /// CCITT Group 4 fax compression. Only for monochrome images.
+    G4,
+}
+
+/// How text is represented in SVG export.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum SvgText {
+    /// Glyphs are drawn as paths.
+    Paths,
+    /// Glyphs are drawn as paths with a layer of invisible, selectable text.
+    Invisible,
+    /// Text is written as real, selectable text.
+    Real,
+}
+
 /// Initializes a new project from a template
 #[derive(Debug, Clone, Parser)]
 pub struct InitCommand {
@@ -216,6 +346,26 @@ pub struct QueryCommand {
     pub pretty: bool,
 }
 
+/// Checks an input file for style problems
+///
+/// Exits with a non-zero status if any problems are found.
+#[derive(Debug, Clone, Parser)]
+pub struct LintCommand {
+    /// Shared arguments
+    #[clap(flatten)]
+    pub common: SharedArgs,
+
+    /// Enables lints, separated by commas
+    ///
+    /// All lints but unreferenced-label are enabled by default.
+    #[clap(long, value_name = "LINTS", value_delimiter = ',')]
+    pub enable: Vec<String>,
+
+    /// Disables lints, separated by commas
+    #[clap(long, value_name = "LINTS", value_delimiter = ',')]
+    pub disable: Vec<String>,
+}
+
 // Output file format for query command
 #[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
 pub enum SerializationFormat {
@@ -223,7 +373,7 @@ pub enum SerializationFormat {
     Yaml,
 }
 
-/// Common arguments of compile, watch, and query.
+/// Common arguments of compile, watch, query, and lint.
 #[derive(Debug, Clone, Args)]
 pub struct SharedArgs {
     /// Path to input Typst file. Use `-` to read input from stdin
@@ -310,6 +460,23 @@ fn parse_source_date_epoch(raw: &str) -> Result<DateTime<Utc>, String> {
         .ok_or_else(|| "timestamp out of range".to_string())
 }
 
+/// Parses a positive gamma value.
+fn parse_gamma(raw: &str) -> Result<f32, String> {
+    let gamma: f32 = raw.parse().map_err(|err| format!("invalid gamma ({err})"))?;
+    if !(gamma.is_finite() && gamma > 0.0) {
+        return Err("gamma must be positive".into());
+    }
+    Ok(gamma)
+}
+
+/// Parses a prefix for the ids in SVGs.
+fn parse_id_prefix(raw: &str) -> Result<String, String> {
+    if !typst_svg::is_valid_id_prefix(raw) {
+        return Err("id prefix must be a valid XML name (without colons)".into());
+    }
+    Ok(raw.into())
+}
+
 /// An input that is either stdin or a real path.
 #[derive(Debug, Clone)]
 pub enum Input {
@@ -509,7 +676,20 @@ pub struct UpdateCommand {
 pub enum OutputFormat {
     Pdf,
     Png,
+    #[value(alias = "jpeg")]
+    Jpg,
+    Webp,
+    Avif,
+    #[value(alias = "tif")]
+    Tiff,
     Svg,
+    Ps,
+    Eps,
+    Json,
+    #[value(alias = "txt")]
+    Text,
+    #[value(alias = "md")]
+    Markdown,
 }
 
 impl Display for OutputFormat {
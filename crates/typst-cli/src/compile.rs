@@ -5,23 +5,33 @@ use std::path::{Path, PathBuf};
 use chrono::{Datelike, Timelike};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term;
-use ecow::{eco_format, EcoString};
+use ecow::{eco_format, EcoString, EcoVec};
+#[cfg(feature = "avif")]
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, ImageEncoder};
 use parking_lot::RwLock;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use typst::diag::{
     bail, At, Severity, SourceDiagnostic, SourceResult, StrResult, Warned,
 };
-use typst::foundations::{Datetime, Smart};
+use typst::foundations::{Bytes, Datetime, Smart};
 use typst::layout::{Frame, Page, PageRanges};
 use typst::model::Document;
 use typst::syntax::{FileId, Source, Span};
 use typst::WorldExt;
-use typst_pdf::{PdfOptions, PdfStandards};
+use typst_pdf::{CmykOutputIntent, PdfCache, PdfOptions, PdfStandards};
+use typst_render::RenderOptions;
+use typst_svg::{SvgImages, SvgOptions};
+use typst_text::TextFormat;
 
 use crate::args::{
-    CompileCommand, DiagnosticFormat, Input, Output, OutputFormat, PageRangeArgument,
-    PdfStandard,
+    CompileCommand, DiagnosticFormat, Input, Monochrome, Output, OutputFormat,
+    PageRangeArgument, PdfStandard, RasterBackground, SvgText, TiffCompression,
 };
+use crate::dump;
+use crate::tiff::{self, TiffPage, TiffPixels};
 use crate::timings::Timer;
 use crate::watch::Status;
 use crate::world::SystemWorld;
@@ -41,7 +51,16 @@ impl CompileCommand {
                 match self.output_format().unwrap_or(OutputFormat::Pdf) {
                     OutputFormat::Pdf => "pdf",
                     OutputFormat::Png => "png",
+                    OutputFormat::Jpg => "jpg",
+                    OutputFormat::Webp => "webp",
+                    OutputFormat::Avif => "avif",
+                    OutputFormat::Tiff => "tiff",
                     OutputFormat::Svg => "svg",
+                    OutputFormat::Ps => "ps",
+                    OutputFormat::Eps => "eps",
+                    OutputFormat::Json => "json",
+                    OutputFormat::Text => "txt",
+                    OutputFormat::Markdown => "md",
                 },
             ))
         })
@@ -57,7 +76,31 @@ impl CompileCommand {
             match output.extension() {
                 Some(ext) if ext.eq_ignore_ascii_case("pdf") => OutputFormat::Pdf,
                 Some(ext) if ext.eq_ignore_ascii_case("png") => OutputFormat::Png,
+                Some(ext)
+                    if ext.eq_ignore_ascii_case("jpg")
+                        || ext.eq_ignore_ascii_case("jpeg") =>
+                {
+                    OutputFormat::Jpg
+                }
+                Some(ext) if ext.eq_ignore_ascii_case("webp") => OutputFormat::Webp,
+                Some(ext) if ext.eq_ignore_ascii_case("avif") => OutputFormat::Avif,
+                Some(ext)
+                    if ext.eq_ignore_ascii_case("tif")
+                        || ext.eq_ignore_ascii_case("tiff") =>
+                {
+                    OutputFormat::Tiff
+                }
                 Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
+                Some(ext) if ext.eq_ignore_ascii_case("ps") => OutputFormat::Ps,
+                Some(ext) if ext.eq_ignore_ascii_case("eps") => OutputFormat::Eps,
+                Some(ext) if ext.eq_ignore_ascii_case("json") => OutputFormat::Json,
+                Some(ext) if ext.eq_ignore_ascii_case("txt") => OutputFormat::Text,
+                Some(ext)
+                    if ext.eq_ignore_ascii_case("md")
+                        || ext.eq_ignore_ascii_case("markdown") =>
+                {
+                    OutputFormat::Markdown
+                }
                 _ => bail!(
                     "could not infer output format for path {}.\n\
                      consider providing the format manually with `--format/-f`",
@@ -92,6 +135,48 @@ impl CompileCommand {
             .collect::<Vec<_>>();
         PdfStandards::new(&list)
     }
+
+    /// The CMYK output intent to write into the PDF.
+    pub fn pdf_output_intent(&self) -> StrResult<Option<CmykOutputIntent>> {
+        let Some(path) = &self.pdf_output_intent else { return Ok(None) };
+        let data = fs::read(path)
+            .map_err(|err| eco_format!("failed to read output intent profile ({err})"))?;
+        let condition = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into())
+            .unwrap_or_default();
+        CmykOutputIntent::new(Bytes::from(data), condition).map(Some)
+    }
+
+    /// The settings for SVG export.
+    pub fn svg_options(&self) -> SvgOptions {
+        SvgOptions {
+            text: match self.svg_text {
+                SvgText::Paths => typst_svg::SvgText::Paths,
+                SvgText::Invisible => typst_svg::SvgText::Invisible,
+                SvgText::Real => typst_svg::SvgText::Real,
+            },
+            embed_fonts: self.svg_embed_fonts,
+            images: if self.svg_external_images {
+                SvgImages::External(EcoString::new())
+            } else {
+                SvgImages::Embed
+            },
+            id_prefix: self.svg_id_prefix.as_str().into(),
+            background: !self.svg_no_background,
+        }
+    }
+
+    /// The settings for raster export.
+    pub fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            pixel_per_pt: self.ppi / 72.0,
+            anti_alias: !self.no_anti_alias,
+            snap_text: self.snap_text,
+            snap_to_pixels: self.snap_to_pixels,
+            gamma: self.gamma,
+        }
+    }
 }
 
 /// Execute a compilation command.
@@ -119,8 +204,9 @@ pub fn compile_once(
         Status::Compiling.print(command).unwrap();
     }
 
-    let Warned { output, warnings } = typst::compile(world);
-    let result = output.and_then(|document| export(world, &document, command, watching));
+    let Warned { output, mut warnings } = typst::compile(world);
+    let result = output
+        .and_then(|document| export(world, &document, command, watching, &mut warnings));
 
     match result {
         // Export the PDF / PNG.
@@ -169,27 +255,43 @@ pub fn compile_once(
 }
 
 /// Export into the target format.
+///
+/// Warnings produced by the export are added to the compilation's warnings.
 fn export(
     world: &mut SystemWorld,
     document: &Document,
     command: &CompileCommand,
     watching: bool,
+    warnings: &mut EcoVec<SourceDiagnostic>,
 ) -> SourceResult<()> {
-    match command.output_format().at(Span::detached())? {
-        OutputFormat::Png => {
-            export_image(world, document, command, watching, ImageExportFormat::Png)
-                .at(Span::detached())
+    let fmt = match command.output_format().at(Span::detached())? {
+        OutputFormat::Pdf => return export_pdf(world, document, command, watching),
+        OutputFormat::Tiff => return export_tiff(document, command).at(Span::detached()),
+        OutputFormat::Ps => return export_ps(document, command).at(Span::detached()),
+        OutputFormat::Json => return export_json(document, command).at(Span::detached()),
+        OutputFormat::Text => {
+            return export_text(world, document, command, TextFormat::Plain, warnings)
         }
-        OutputFormat::Svg => {
-            export_image(world, document, command, watching, ImageExportFormat::Svg)
-                .at(Span::detached())
+        OutputFormat::Markdown => {
+            return export_text(world, document, command, TextFormat::Markdown, warnings)
         }
-        OutputFormat::Pdf => export_pdf(document, command),
-    }
+        OutputFormat::Png => ImageExportFormat::Png,
+        OutputFormat::Jpg => ImageExportFormat::Jpg,
+        OutputFormat::Webp => ImageExportFormat::Webp,
+        OutputFormat::Avif => ImageExportFormat::Avif,
+        OutputFormat::Svg => ImageExportFormat::Svg,
+        OutputFormat::Eps => ImageExportFormat::Eps,
+    };
+    export_image(world, document, command, watching, fmt).at(Span::detached())
 }
 
 /// Export to a PDF.
-fn export_pdf(document: &Document, command: &CompileCommand) -> SourceResult<()> {
+fn export_pdf(
+    world: &SystemWorld,
+    document: &Document,
+    command: &CompileCommand,
+    watching: bool,
+) -> SourceResult<()> {
     let options = PdfOptions {
         ident: Smart::Auto,
         timestamp: convert_datetime(
@@ -197,6 +299,11 @@ fn export_pdf(document: &Document, command: &CompileCommand) -> SourceResult<()>
         ),
         page_ranges: command.exported_page_ranges(),
         standards: command.pdf_standards().at(Span::detached())?,
+        linearize: command.pdf_linearize,
+        // If we are not watching, there won't be a next export to reuse the
+        // pages in.
+        cache: watching.then(|| &world.export_cache().pdf),
+        output_intent: command.pdf_output_intent().at(Span::detached())?,
     };
     let buffer = typst_pdf::pdf(document, &options)?;
     command
@@ -207,6 +314,172 @@ fn export_pdf(document: &Document, command: &CompileCommand) -> SourceResult<()>
     Ok(())
 }
 
+/// Export to a PostScript file.
+fn export_ps(document: &Document, command: &CompileCommand) -> StrResult<()> {
+    let buffer = typst_ps::ps(document, command.exported_page_ranges().as_ref());
+    command
+        .output()
+        .write(&buffer)
+        .map_err(|err| eco_format!("failed to write PostScript file ({err})"))
+}
+
+/// Export a JSON dump of the laid-out pages.
+fn export_json(document: &Document, command: &CompileCommand) -> StrResult<()> {
+    let dump = dump::dump(document, command.exported_page_ranges().as_ref());
+    let buffer = serde_json::to_vec_pretty(&dump).map_err(|err| eco_format!("{err}"))?;
+    command
+        .output()
+        .write(&buffer)
+        .map_err(|err| eco_format!("failed to write JSON file ({err})"))
+}
+
+/// Export the text content as plain text or Markdown.
+fn export_text(
+    world: &SystemWorld,
+    document: &Document,
+    command: &CompileCommand,
+    format: TextFormat,
+    warnings: &mut EcoVec<SourceDiagnostic>,
+) -> SourceResult<()> {
+    let Warned { output, warnings: text_warnings } =
+        typst_text::text(world, document, format);
+
+    // The text export evaluates the document again, which repeats the
+    // compilation's warnings.
+    for warning in text_warnings {
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+
+    let buffer = output?;
+    let kind = match format {
+        TextFormat::Plain => "text",
+        TextFormat::Markdown => "Markdown",
+    };
+    command
+        .output()
+        .write(buffer.as_bytes())
+        .map_err(|err| eco_format!("failed to write {kind} file ({err})"))
+        .at(Span::detached())
+}
+
+/// Export to a multi-page TIFF.
+fn export_tiff(document: &Document, command: &CompileCommand) -> StrResult<()> {
+    if command.monochrome.is_some()
+        && command.background == Some(RasterBackground::Transparent)
+    {
+        bail!("monochrome images cannot have a transparent background");
+    }
+
+    let compression = match (command.tiff_compression, command.monochrome) {
+        (Some(TiffCompression::G4), None) => {
+            bail!("CCITT Group 4 compression requires `--monochrome`")
+        }
+        (Some(TiffCompression::G4), Some(_)) | (None, Some(_)) => {
+            tiff::TiffCompression::CcittG4
+        }
+        (Some(TiffCompression::Lzw), _) | (None, None) => tiff::TiffCompression::Lzw,
+    };
+
+    let exported_page_ranges = command.exported_page_ranges();
+    let pages: Vec<_> = document
+        .pages
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            exported_page_ranges.as_ref().map_or(true, |exported_page_ranges| {
+                exported_page_ranges.includes_page_index(*i)
+            })
+        })
+        .map(|(_, page)| page)
+        .collect();
+
+    let pages: Vec<_> =
+        pages.par_iter().map(|page| render_tiff_page(command, page)).collect();
+
+    let buf = tiff::encode(&pages, compression, command.ppi);
+    command
+        .output()
+        .write(&buf)
+        .map_err(|err| eco_format!("failed to write TIFF file ({err})"))
+}
+
+/// Render a page for a TIFF file.
+fn render_tiff_page(command: &CompileCommand, page: &Page) -> TiffPage {
+    let pixmap = render_raster(command, page, command.monochrome.is_some());
+    let (width, height) = (pixmap.width(), pixmap.height());
+    let pixels = match command.monochrome {
+        Some(mode) => TiffPixels::Bilevel(to_bilevel(&pixmap, mode)),
+        None if command.background == Some(RasterBackground::White) => TiffPixels::Rgb(
+            pixmap
+                .pixels()
+                .iter()
+                .flat_map(|pixel| [pixel.red(), pixel.green(), pixel.blue()])
+                .collect(),
+        ),
+        None => TiffPixels::Rgba(
+            pixmap
+                .pixels()
+                .iter()
+                .flat_map(|pixel| {
+                    let color = pixel.demultiply();
+                    [color.red(), color.green(), color.blue(), color.alpha()]
+                })
+                .collect(),
+        ),
+    };
+    TiffPage { width, height, pixels }
+}
+
+/// Reduce an opaque pixmap to one bit per pixel, where a set bit is black.
+fn to_bilevel(pixmap: &tiny_skia::Pixmap, mode: Monochrome) -> Vec<u8> {
+    let width = pixmap.width() as usize;
+    let height = pixmap.height() as usize;
+    let stride = width.div_ceil(8);
+
+    let mut luma: Vec<f32> = pixmap
+        .pixels()
+        .iter()
+        .map(|pixel| {
+            0.299 * f32::from(pixel.red())
+                + 0.587 * f32::from(pixel.green())
+                + 0.114 * f32::from(pixel.blue())
+        })
+        .collect();
+
+    let mut bits = vec![0; stride * height];
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let black = luma[i] < 128.0;
+            if black {
+                bits[y * stride + x / 8] |= 0x80 >> (x % 8);
+            }
+
+            // Distribute the error to the unvisited neighbours with
+            // Floyd-Steinberg dithering.
+            if mode == Monochrome::Dither {
+                let error = luma[i] - if black { 0.0 } else { 255.0 };
+                if x + 1 < width {
+                    luma[i + 1] += error * 7.0 / 16.0;
+                }
+                if y + 1 < height {
+                    if x > 0 {
+                        luma[i + width - 1] += error * 3.0 / 16.0;
+                    }
+                    luma[i + width] += error * 5.0 / 16.0;
+                    if x + 1 < width {
+                        luma[i + width + 1] += error / 16.0;
+                    }
+                }
+            }
+        }
+    }
+
+    bits
+}
+
 /// Convert [`chrono::DateTime`] to [`Datetime`]
 fn convert_datetime(date_time: chrono::DateTime<chrono::Utc>) -> Option<Datetime> {
     Datetime::from_ymd_hms(
@@ -223,7 +496,25 @@ fn convert_datetime(date_time: chrono::DateTime<chrono::Utc>) -> Option<Datetime
 #[derive(Clone, Copy)]
 enum ImageExportFormat {
     Png,
+    Jpg,
+    Webp,
+    Avif,
     Svg,
+    Eps,
+}
+
+impl ImageExportFormat {
+    /// The name of the format in messages.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Jpg => "JPEG",
+            Self::Webp => "WebP",
+            Self::Avif => "AVIF",
+            Self::Svg => "SVG",
+            Self::Eps => "EPS",
+        }
+    }
 }
 
 /// Export to one or multiple images.
@@ -266,6 +557,13 @@ fn export_image(
         bail!("cannot export multiple images {err}");
     }
 
+    if matches!(fmt, ImageExportFormat::Svg)
+        && command.svg_external_images
+        && matches!(output, Output::Stdout)
+    {
+        bail!("cannot write external images when exporting to stdout");
+    }
+
     let cache = world.export_cache();
 
     // The results are collected in a `Vec<()>` which does not allocate.
@@ -344,25 +642,160 @@ fn export_image_page(
     fmt: ImageExportFormat,
 ) -> StrResult<()> {
     match fmt {
-        ImageExportFormat::Png => {
-            let pixmap = typst_render::render(page, command.ppi / 72.0);
-            let buf = pixmap
-                .encode_png()
-                .map_err(|err| eco_format!("failed to encode PNG file ({err})"))?;
-            output
-                .write(&buf)
-                .map_err(|err| eco_format!("failed to write PNG file ({err})"))?;
+        ImageExportFormat::Png
+        | ImageExportFormat::Jpg
+        | ImageExportFormat::Webp
+        | ImageExportFormat::Avif => {
+            let buf = encode_raster(command, page, fmt)?;
+            output.write(&buf).map_err(|err| {
+                eco_format!("failed to write {} file ({err})", fmt.name())
+            })?;
         }
         ImageExportFormat::Svg => {
-            let svg = typst_svg::svg(page);
+            let options = command.svg_options();
+            let svg = typst_svg::svg(page, &options);
             output
                 .write(svg.as_bytes())
                 .map_err(|err| eco_format!("failed to write SVG file ({err})"))?;
+
+            // Exporting to stdout with external images was already rejected.
+            if let (SvgImages::External(_), Output::Path(path)) =
+                (&options.images, output)
+            {
+                let dir = path.parent().unwrap_or(Path::new(""));
+                for (name, image) in typst_svg::external_images(page) {
+                    fs::write(dir.join(name.as_str()), image.data()).map_err(|err| {
+                        eco_format!("failed to write image file ({err})")
+                    })?;
+                }
+            }
+        }
+        ImageExportFormat::Eps => {
+            let eps = typst_ps::eps(page);
+            output
+                .write(&eps)
+                .map_err(|err| eco_format!("failed to write EPS file ({err})"))?;
         }
     }
     Ok(())
 }
 
+/// Render a page and encode it in a raster format.
+fn encode_raster(
+    command: &CompileCommand,
+    page: &Page,
+    fmt: ImageExportFormat,
+) -> StrResult<Vec<u8>> {
+    let opaque = matches!(fmt, ImageExportFormat::Jpg);
+    if opaque && command.background == Some(RasterBackground::Transparent) {
+        bail!("JPEG images cannot have a transparent background");
+    }
+
+    if matches!(fmt, ImageExportFormat::Png) && command.quality.is_some() {
+        bail!("PNG images are always lossless and have no quality setting");
+    }
+    let quality = command.quality.unwrap_or(90);
+
+    let pixmap = render_raster(command, page, opaque);
+    let failed = |err| eco_format!("failed to encode {} file ({err})", fmt.name());
+    if let ImageExportFormat::Png = fmt {
+        return pixmap.encode_png().map_err(|err| failed(err.to_string()));
+    }
+
+    let (width, height) = (pixmap.width(), pixmap.height());
+    let rgba: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    let mut buf = vec![];
+    match fmt {
+        ImageExportFormat::Jpg => {
+            let rgb: Vec<u8> =
+                rgba.chunks_exact(4).flat_map(|pixel| &pixel[..3]).copied().collect();
+            JpegEncoder::new_with_quality(&mut buf, quality).write_image(
+                &rgb,
+                width,
+                height,
+                ExtendedColorType::Rgb8,
+            )
+        }
+        // WebP images are lossless unless a quality is given.
+        ImageExportFormat::Webp if command.quality.is_none() => {
+            WebPEncoder::new_lossless(&mut buf).write_image(
+                &rgba,
+                width,
+                height,
+                ExtendedColorType::Rgba8,
+            )
+        }
+        #[cfg(feature = "lossy-webp")]
+        ImageExportFormat::Webp => {
+            return webp::Encoder::from_rgba(&rgba, width, height)
+                .encode_simple(false, quality.into())
+                .map(|encoded| encoded.to_vec())
+                .map_err(|err| failed(format!("{err:?}")));
+        }
+        #[cfg(not(feature = "lossy-webp"))]
+        ImageExportFormat::Webp => {
+            bail!(
+                "lossy WebP export requires Typst to be built with the \
+                 `lossy-webp` feature"
+            )
+        }
+        #[cfg(feature = "avif")]
+        ImageExportFormat::Avif => {
+            let encoder = AvifEncoder::new_with_speed_quality(&mut buf, 6, quality);
+            encoder.write_image(&rgba, width, height, ExtendedColorType::Rgba8)
+        }
+        #[cfg(not(feature = "avif"))]
+        ImageExportFormat::Avif => {
+            bail!("AVIF export requires Typst to be built with the `avif` feature")
+        }
+        ImageExportFormat::Png | ImageExportFormat::Svg | ImageExportFormat::Eps => {
+            unreachable!()
+        }
+    }
+    .map_err(|err| failed(err.to_string()))?;
+
+    Ok(buf)
+}
+
+/// Render a page for raster export.
+///
+/// The page is put onto white if the image must be `opaque` or if the user
+/// requested a white background.
+fn render_raster(
+    command: &CompileCommand,
+    page: &Page,
+    opaque: bool,
+) -> tiny_skia::Pixmap {
+    let options = command.render_options();
+    let mut pixmap = if command.background == Some(RasterBackground::Transparent) {
+        let page = Page { fill: Smart::Custom(None), ..page.clone() };
+        typst_render::render(&page, &options)
+    } else {
+        typst_render::render(page, &options)
+    };
+
+    // Put premultiplied pixels onto white.
+    if opaque || command.background == Some(RasterBackground::White) {
+        for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+            let rest = 255 - pixel[3];
+            pixel[0] += rest;
+            pixel[1] += rest;
+            pixel[2] += rest;
+            pixel[3] = 255;
+        }
+    }
+
+    pixmap
+}
+
 impl Output {
     fn write(&self, buffer: &[u8]) -> StrResult<()> {
         match self {
@@ -383,12 +816,17 @@ impl Output {
 pub struct ExportCache {
     /// The hashes of last compilation's frames.
     pub cache: RwLock<Vec<u128>>,
+    /// The encoded pages of the last PDF export.
+    pub pdf: PdfCache,
 }
 
 impl ExportCache {
     /// Creates a new export cache.
     pub fn new() -> Self {
-        Self { cache: RwLock::new(Vec::with_capacity(32)) }
+        Self {
+            cache: RwLock::new(Vec::with_capacity(32)),
+            pdf: PdfCache::new(),
+        }
     }
 
     /// Returns true if the entry is cached and appends the new hash to the
@@ -624,3 +1062,60 @@ impl<'a> codespan_reporting::files::Files<'a> for SystemWorld {
         })
     }
 }
+
+#[cfg(all(test, feature = "lossy-webp"))]
+mod tests {
+    use clap::Parser;
+    use typst::foundations::{Content, Smart};
+    use typst::layout::{Abs, Frame, FrameItem, Page, Point, Size};
+    use typst::visualize::{Color, Geometry};
+
+    use super::*;
+    use crate::args::{CliArguments, Command};
+
+    /// Parse the arguments of a compile command to a WebP file.
+    fn command(args: &[&str]) -> CompileCommand {
+        let base = ["typst", "compile", "main.typ", "out.webp"];
+        let parsed = CliArguments::try_parse_from(base.iter().chain(args)).unwrap();
+        let Command::Compile(command) = parsed.command else { unreachable!() };
+        command
+    }
+
+    /// A page covered with small squares in pseudo-random colors, which
+    /// compresses poorly without loss.
+    fn noisy_page() -> Page {
+        let mut frame = Frame::hard(Size::splat(Abs::pt(60.0)));
+        let mut seed = 1_u32;
+        for y in 0..30 {
+            for x in 0..30 {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                let [r, g, b, _] = seed.to_be_bytes();
+                let shape = Geometry::Rect(Size::splat(Abs::pt(2.0)))
+                    .filled(Color::from_u8(r, g, b, 255));
+                let pos = Point::new(Abs::pt(2.0 * x as f64), Abs::pt(2.0 * y as f64));
+                frame.push(pos, FrameItem::Shape(shape, Span::detached()));
+            }
+        }
+
+        Page {
+            frame,
+            fill: Smart::Auto,
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+            bleed: Abs::zero(),
+            crop_marks: false,
+        }
+    }
+
+    #[test]
+    fn test_webp_quality() {
+        let page = noisy_page();
+        let lossless = encode_raster(&command(&[]), &page, ImageExportFormat::Webp);
+        let lossy =
+            encode_raster(&command(&["--quality", "50"]), &page, ImageExportFormat::Webp);
+        let (lossless, lossy) = (lossless.unwrap(), lossy.unwrap());
+        assert!(lossless.starts_with(b"RIFF") && lossy.starts_with(b"RIFF"));
+        assert!(lossy.len() < lossless.len());
+    }
+}
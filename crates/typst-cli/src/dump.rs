@@ -0,0 +1,477 @@
+//! Serialization of laid-out pages into JSON.
+//!
+//! The dump mirrors the frame tree of each page. Item positions are relative
+//! to the parent frame, while bounding boxes are given in page coordinates to
+//! make geometric checks straightforward. All lengths are in points.
+
+use ecow::{eco_format, EcoString};
+use serde::Serialize;
+use typst::introspection::{Introspector, Location, Tag};
+use typst::layout::{
+    Abs, Frame, FrameItem, GroupItem, Page, PageRanges, Point, Position, Size, Transform,
+};
+use typst::model::{Destination, Document};
+use typst::text::{FontVariant, TextItem};
+use typst::visualize::{
+    FixedStroke, Geometry, Image, ImageFormat, Paint, RasterFormat, Shape, VectorFormat,
+};
+
+/// Dump the pages of a document, optionally restricted to some page ranges.
+pub fn dump(document: &Document, page_ranges: Option<&PageRanges>) -> DocumentDump {
+    let pages = document
+        .pages
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            page_ranges.map_or(true, |ranges| ranges.includes_page_index(*i))
+        })
+        .map(|(i, page)| dump_page(&document.introspector, i, page))
+        .collect();
+
+    DocumentDump { title: document.info.title.clone(), pages }
+}
+
+/// The serialized document.
+#[derive(Serialize)]
+pub struct DocumentDump {
+    title: Option<EcoString>,
+    pages: Vec<PageDump>,
+}
+
+#[derive(Serialize)]
+struct PageDump {
+    /// The physical page index, starting at 1.
+    index: usize,
+    /// The logical page number, which is affected by the page counter.
+    number: usize,
+    width: f64,
+    height: f64,
+    fill: Option<EcoString>,
+    items: Vec<ItemDump>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum ItemDump {
+    Group {
+        pos: [f64; 2],
+        bbox: Rect,
+        /// The transform as `[sx, ky, kx, sy, tx, ty]`.
+        transform: [f64; 6],
+        clipped: bool,
+        label: Option<EcoString>,
+        items: Vec<ItemDump>,
+    },
+    Text {
+        pos: [f64; 2],
+        bbox: Rect,
+        text: EcoString,
+        font: FontDump,
+        size: f64,
+        fill: EcoString,
+        stroke: Option<StrokeDump>,
+        lang: EcoString,
+    },
+    Shape {
+        pos: [f64; 2],
+        bbox: Rect,
+        geometry: &'static str,
+        fill: Option<EcoString>,
+        stroke: Option<StrokeDump>,
+    },
+    Image {
+        pos: [f64; 2],
+        bbox: Rect,
+        format: &'static str,
+        width: f64,
+        height: f64,
+        alt: Option<EcoString>,
+    },
+    Link {
+        pos: [f64; 2],
+        bbox: Rect,
+        destination: DestinationDump,
+    },
+    Tag {
+        pos: [f64; 2],
+        /// Either `start` or `end`.
+        tag: &'static str,
+        /// The element's name, only known for start tags.
+        element: Option<&'static str>,
+        label: Option<EcoString>,
+        location: EcoString,
+    },
+}
+
+#[derive(Serialize)]
+struct FontDump {
+    family: String,
+    #[serde(flatten)]
+    variant: FontVariant,
+}
+
+#[derive(Serialize)]
+struct StrokeDump {
+    paint: EcoString,
+    thickness: f64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum DestinationDump {
+    Url { url: EcoString },
+    Position { page: usize, x: f64, y: f64 },
+}
+
+/// An axis-aligned rectangle in page coordinates.
+#[derive(Serialize)]
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+fn dump_page(introspector: &Introspector, i: usize, page: &Page) -> PageDump {
+    let size = page.frame.size();
+    PageDump {
+        index: i + 1,
+        number: page.number,
+        width: size.x.to_pt(),
+        height: size.y.to_pt(),
+        fill: page.fill_or_white().as_ref().map(paint),
+        items: dump_frame(introspector, &page.frame, Transform::identity()),
+    }
+}
+
+/// Dump the items of a frame whose origin has the given transform relative to
+/// the page.
+fn dump_frame(
+    introspector: &Introspector,
+    frame: &Frame,
+    ts: Transform,
+) -> Vec<ItemDump> {
+    frame
+        .items()
+        .filter_map(|(pos, item)| {
+            let ts = ts.pre_concat(Transform::translate(pos.x, pos.y));
+            let pos = [pos.x.to_pt(), pos.y.to_pt()];
+            Some(match item {
+                FrameItem::Group(group) => dump_group(introspector, pos, group, ts),
+                FrameItem::Text(text) => dump_text(pos, text, ts),
+                FrameItem::Shape(shape, _) => dump_shape(pos, shape, ts),
+                FrameItem::Image(image, size, _) => dump_image(pos, image, *size, ts),
+                FrameItem::Link(dest, size) => ItemDump::Link {
+                    pos,
+                    bbox: bbox(ts, Point::zero(), *size),
+                    destination: destination(introspector, dest),
+                },
+                FrameItem::Tag(tag) => dump_tag(pos, tag)?,
+            })
+        })
+        .collect()
+}
+
+fn dump_group(
+    introspector: &Introspector,
+    pos: [f64; 2],
+    group: &GroupItem,
+    ts: Transform,
+) -> ItemDump {
+    let ts = ts.pre_concat(group.transform);
+    let Transform { sx, ky, kx, sy, tx, ty } = group.transform;
+    ItemDump::Group {
+        pos,
+        bbox: bbox(ts, Point::zero(), group.frame.size()),
+        transform: [sx.get(), ky.get(), kx.get(), sy.get(), tx.to_pt(), ty.to_pt()],
+        clipped: group.clip_path.is_some(),
+        label: group.label.map(|label| label.as_str().into()),
+        items: dump_frame(introspector, &group.frame, ts),
+    }
+}
+
+fn dump_text(pos: [f64; 2], text: &TextItem, ts: Transform) -> ItemDump {
+    // The box spans from the font's ascender to its descender.
+    let metrics = text.font.metrics();
+    let top = -metrics.ascender.at(text.size);
+    let bottom = -metrics.descender.at(text.size);
+    let info = text.font.info();
+    ItemDump::Text {
+        pos,
+        bbox: bbox(ts, Point::with_y(top), Size::new(text.width(), bottom - top)),
+        text: text.text.clone(),
+        font: FontDump { family: info.family.clone(), variant: info.variant },
+        size: text.size.to_pt(),
+        fill: paint(&text.fill),
+        stroke: text.stroke.as_ref().map(stroke),
+        lang: text.lang.as_str().into(),
+    }
+}
+
+fn dump_shape(pos: [f64; 2], shape: &Shape, ts: Transform) -> ItemDump {
+    // Lines and paths may extend into negative coordinates.
+    let (origin, size) = match &shape.geometry {
+        Geometry::Line(target) => {
+            let min = Point::new(target.x.min(Abs::zero()), target.y.min(Abs::zero()));
+            (min, Size::new(target.x.abs(), target.y.abs()))
+        }
+        Geometry::Rect(size) => (Point::zero(), *size),
+        Geometry::Path(path) => (Point::zero(), path.bbox_size()),
+    };
+
+    ItemDump::Shape {
+        pos,
+        bbox: bbox(ts, origin, size),
+        geometry: match shape.geometry {
+            Geometry::Line(_) => "line",
+            Geometry::Rect(_) => "rect",
+            Geometry::Path(_) => "path",
+        },
+        fill: shape.fill.as_ref().map(paint),
+        stroke: shape.stroke.as_ref().map(stroke),
+    }
+}
+
+fn dump_image(pos: [f64; 2], image: &Image, size: Size, ts: Transform) -> ItemDump {
+    ItemDump::Image {
+        pos,
+        bbox: bbox(ts, Point::zero(), size),
+        format: match image.format() {
+            ImageFormat::Raster(RasterFormat::Png) => "png",
+            ImageFormat::Raster(RasterFormat::Jpg) => "jpg",
+            ImageFormat::Raster(RasterFormat::Gif) => "gif",
+            ImageFormat::Vector(VectorFormat::Svg) => "svg",
+        },
+        width: image.width(),
+        height: image.height(),
+        alt: image.alt().map(Into::into),
+    }
+}
+
+/// Dump a tag. Start tags of elements that were never located, which only
+/// occur in frames that weren't produced by regular layout, are skipped.
+fn dump_tag(pos: [f64; 2], tag: &Tag) -> Option<ItemDump> {
+    Some(match tag {
+        Tag::Start(elem) => ItemDump::Tag {
+            pos,
+            tag: "start",
+            element: Some(elem.elem().name()),
+            label: elem.label().map(|label| label.as_str().into()),
+            location: location(elem.location()?),
+        },
+        Tag::End(loc, _) => ItemDump::Tag {
+            pos,
+            tag: "end",
+            element: None,
+            label: None,
+            location: location(*loc),
+        },
+    })
+}
+
+/// Resolve a link destination.
+fn destination(introspector: &Introspector, dest: &Destination) -> DestinationDump {
+    let position = |Position { page, point }: Position| DestinationDump::Position {
+        page: page.get(),
+        x: point.x.to_pt(),
+        y: point.y.to_pt(),
+    };
+
+    match dest {
+        Destination::Url(url) => DestinationDump::Url { url: url.as_str().into() },
+        Destination::Position(pos) => position(*pos),
+        Destination::Location(loc) => position(introspector.position(*loc)),
+    }
+}
+
+/// A stable textual representation of a location.
+fn location(loc: Location) -> EcoString {
+    eco_format!("{:032x}", loc.hash())
+}
+
+/// A short description of a paint: Solid colors are given as hex strings.
+fn paint(paint: &Paint) -> EcoString {
+    match paint {
+        Paint::Solid(color) => color.to_hex(),
+        Paint::Gradient(_) => "gradient".into(),
+        Paint::Pattern(_) => "pattern".into(),
+    }
+}
+
+fn stroke(stroke: &FixedStroke) -> StrokeDump {
+    StrokeDump {
+        paint: paint(&stroke.paint),
+        thickness: stroke.thickness.to_pt(),
+    }
+}
+
+/// The axis-aligned bounding box of a transformed rectangle.
+fn bbox(ts: Transform, origin: Point, size: Size) -> Rect {
+    let corners = [
+        origin,
+        origin + Point::with_x(size.x),
+        origin + Point::with_y(size.y),
+        origin + size.to_point(),
+    ]
+    .map(|corner| corner.transform(ts));
+
+    let min_x = corners.iter().map(|p| p.x).min().unwrap();
+    let min_y = corners.iter().map(|p| p.y).min().unwrap();
+    let max_x = corners.iter().map(|p| p.x).max().unwrap();
+    let max_y = corners.iter().map(|p| p.y).max().unwrap();
+
+    Rect {
+        x: min_x.to_pt(),
+        y: min_y.to_pt(),
+        width: (max_x - min_x).to_pt(),
+        height: (max_y - min_y).to_pt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use serde_json::json;
+    use typst::foundations::{Content, Smart};
+    use typst::layout::Ratio;
+    use typst::model::{DocumentInfo, Url};
+    use typst::syntax::Span;
+    use typst::visualize::Color;
+
+    use super::*;
+
+    fn point(x: f64, y: f64) -> Point {
+        Point::new(Abs::pt(x), Abs::pt(y))
+    }
+
+    fn size(x: f64, y: f64) -> Size {
+        Size::new(Abs::pt(x), Abs::pt(y))
+    }
+
+    /// A start tag, optionally with a location.
+    fn start(loc: Option<Location>) -> FrameItem {
+        let mut content = Content::empty();
+        if let Some(loc) = loc {
+            content.set_location(loc);
+        }
+        FrameItem::Tag(Tag::Start(content))
+    }
+
+    fn document() -> Document {
+        let rect = Geometry::Rect(size(30.0, 10.0)).filled(Color::BLACK);
+        let mut inner = Frame::soft(size(50.0, 20.0));
+        inner.push(point(2.0, 3.0), FrameItem::Shape(rect, Span::detached()));
+        let mut group = GroupItem::new(inner);
+        group.transform = Transform::scale(Ratio::new(2.0), Ratio::new(2.0));
+
+        let loc = Location::new(0xABC);
+        let url = Url::new("https://typst.app").unwrap();
+        let mut frame = Frame::hard(size(200.0, 100.0));
+        frame.push(Point::zero(), start(Some(loc)));
+        frame.push(point(5.0, 5.0), FrameItem::Group(group));
+        frame.push(
+            point(0.0, 50.0),
+            FrameItem::Link(Destination::Url(url), size(40.0, 10.0)),
+        );
+        frame.push(Point::zero(), start(None));
+        frame.push(Point::zero(), FrameItem::Tag(Tag::End(loc, 0)));
+
+        let page = Page {
+            frame,
+            fill: Smart::Auto,
+            numbering: None,
+            supplement: Content::empty(),
+            number: 3,
+            bleed: Abs::zero(),
+            crop_marks: false,
+        };
+
+        Document {
+            pages: vec![page],
+            info: DocumentInfo {
+                title: Some("Dump".into()),
+                ..DocumentInfo::default()
+            },
+            introspector: Introspector::default(),
+        }
+    }
+
+    #[test]
+    fn test_dump_structure() {
+        let value = serde_json::to_value(dump(&document(), None)).unwrap();
+        let location = "00000000000000000000000000000abc";
+        assert_eq!(
+            value,
+            json!({
+                "title": "Dump",
+                "pages": [{
+                    "index": 1,
+                    "number": 3,
+                    "width": 200.0,
+                    "height": 100.0,
+                    "fill": "#ffffff",
+                    "items": [
+                        {
+                            "kind": "tag",
+                            "pos": [0.0, 0.0],
+                            "tag": "start",
+                            "element": "sequence",
+                            "label": null,
+                            "location": location,
+                        },
+                        {
+                            "kind": "group",
+                            "pos": [5.0, 5.0],
+                            "bbox": { "x": 5.0, "y": 5.0, "width": 100.0, "height": 40.0 },
+                            "transform": [2.0, 0.0, 0.0, 2.0, 0.0, 0.0],
+                            "clipped": false,
+                            "label": null,
+                            "items": [{
+                                "kind": "shape",
+                                // Positions are relative to the parent, while
+                                // bounding boxes are in page coordinates.
+                                "pos": [2.0, 3.0],
+                                "bbox": {
+                                    "x": 9.0,
+                                    "y": 11.0,
+                                    "width": 60.0,
+                                    "height": 20.0,
+                                },
+                                "geometry": "rect",
+                                "fill": "#000000",
+                                "stroke": null,
+                            }],
+                        },
+                        {
+                            "kind": "link",
+                            "pos": [0.0, 50.0],
+                            "bbox": { "x": 0.0, "y": 50.0, "width": 40.0, "height": 10.0 },
+                            "destination": { "url": "https://typst.app" },
+                        },
+                        // The start tag without a location is skipped.
+                        {
+                            "kind": "tag",
+                            "pos": [0.0, 0.0],
+                            "tag": "end",
+                            "element": null,
+                            "label": null,
+                            "location": location,
+                        },
+                    ],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_dump_page_ranges() {
+        let mut document = document();
+        document.pages.push(document.pages[0].clone());
+        let ranges = PageRanges::new(vec![Some(NonZeroUsize::new(2).unwrap())..=None]);
+        let value = serde_json::to_value(dump(&document, Some(&ranges))).unwrap();
+        let pages = value["pages"].as_array().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0]["index"], 2);
+    }
+}
@@ -0,0 +1,67 @@
+use ecow::{eco_format, EcoVec};
+use typst::diag::{bail, HintedStrResult, Warned};
+use typst::World;
+use typst_ide::{Lint, LintConfig};
+
+use crate::args::LintCommand;
+use crate::compile::print_diagnostics;
+use crate::set_failed;
+use crate::world::SystemWorld;
+
+/// Execute a lint command.
+pub fn lint(command: &LintCommand) -> HintedStrResult<()> {
+    let enable = parse_lints(&command.enable)?;
+    let disable = parse_lints(&command.disable)?;
+    let mut world = SystemWorld::new(&command.common)?;
+
+    // Reset everything and ensure that the main file is present.
+    world.reset();
+    world.source(world.main()).map_err(|err| err.to_string())?;
+
+    let Warned { output, mut warnings } = typst::compile(&world);
+    let errors = match output {
+        Ok(document) => {
+            let mut config = LintConfig::default();
+            for lint in enable {
+                config.enable(lint);
+            }
+            for lint in disable {
+                config.disable(lint);
+            }
+
+            let lints = typst_ide::lint(&world, &document, &config);
+            if !lints.is_empty() {
+                set_failed();
+            }
+
+            warnings.extend(lints);
+            EcoVec::new()
+        }
+        Err(errors) => {
+            set_failed();
+            errors
+        }
+    };
+
+    print_diagnostics(&world, &errors, &warnings, command.common.diagnostic_format)
+        .map_err(|err| eco_format!("failed to print diagnostics ({err})"))?;
+
+    Ok(())
+}
+
+/// Find the lints with the given names.
+fn parse_lints(names: &[String]) -> HintedStrResult<Vec<Lint>> {
+    names
+        .iter()
+        .map(|name| match Lint::from_name(name) {
+            Some(lint) => Ok(lint),
+            None => {
+                let names: Vec<_> = Lint::ALL.iter().map(|lint| lint.name()).collect();
+                bail!(
+                    "unknown lint: {name}";
+                    hint: "available lints are {}", names.join(", ")
+                )
+            }
+        })
+        .collect()
+}
@@ -1,12 +1,15 @@
 mod args;
 mod compile;
 mod download;
+mod dump;
 mod fonts;
 mod greet;
 mod init;
+mod lint;
 mod package;
 mod query;
 mod terminal;
+mod tiff;
 mod timings;
 #[cfg(feature = "self-update")]
 mod update;
@@ -63,6 +66,7 @@ fn dispatch() -> HintedStrResult<()> {
         Command::Watch(command) => crate::watch::watch(timer, command.clone())?,
         Command::Init(command) => crate::init::init(command)?,
         Command::Query(command) => crate::query::query(command)?,
+        Command::Lint(command) => crate::lint::lint(command)?,
         Command::Fonts(command) => crate::fonts::fonts(command),
         Command::Update(command) => crate::update::update(command)?,
     }
@@ -0,0 +1,648 @@
+//! Encoding of multi-page TIFF files.
+//!
+//! Pages are stored in a single strip each and compressed either with LZW or,
+//! for black-and-white pages, with CCITT Group 4 as used by fax machines.
+
+use std::collections::HashMap;
+
+/// A page to be written into a TIFF file.
+pub struct TiffPage {
+    /// The width in pixels.
+    pub width: u32,
+    /// The height in pixels.
+    pub height: u32,
+    /// The pixel data.
+    pub pixels: TiffPixels,
+}
+
+/// The pixel data of a page.
+pub enum TiffPixels {
+    /// Three bytes per pixel.
+    Rgb(Vec<u8>),
+    /// Four bytes per pixel with non-premultiplied alpha.
+    Rgba(Vec<u8>),
+    /// One bit per pixel, where a set bit is black. Each row starts at a new
+    /// byte.
+    Bilevel(Vec<u8>),
+}
+
+/// How the pages of a TIFF file are compressed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TiffCompression {
+    /// Lempel-Ziv-Welch compression, which works for all pages.
+    Lzw,
+    /// CCITT Group 4 fax compression, which only works for bilevel pages.
+    CcittG4,
+}
+
+/// Encode pages into a multi-page TIFF file with the given resolution in
+/// pixels per inch.
+pub fn encode(pages: &[TiffPage], compression: TiffCompression, ppi: f32) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend(b"II*\0");
+
+    // The offset of the pointer to the next IFD, which is patched once the
+    // IFD has been written.
+    let mut link = buf.len();
+    buf.extend(0u32.to_le_bytes());
+
+    let resolution = [(ppi * 1000.0).round() as u32, 1000];
+    for (i, page) in pages.iter().enumerate() {
+        let (samples, photometric, data) = match &page.pixels {
+            TiffPixels::Rgb(data) => (3, PHOTOMETRIC_RGB, data),
+            TiffPixels::Rgba(data) => (4, PHOTOMETRIC_RGB, data),
+            TiffPixels::Bilevel(data) => (1, PHOTOMETRIC_WHITE_IS_ZERO, data),
+        };
+        let bits = if samples == 1 { 1 } else { 8 };
+
+        let (scheme, compressed) = match compression {
+            TiffCompression::CcittG4 if samples == 1 => {
+                (COMPRESSION_CCITT_G4, encode_g4(data, page.width, page.height))
+            }
+            _ => (COMPRESSION_LZW, encode_lzw(data)),
+        };
+
+        let strip = buf.len() as u32;
+        buf.extend(&compressed);
+        pad(&mut buf);
+
+        // Values that don't fit into an IFD entry are written before it.
+        let bits_offset = buf.len() as u32;
+        if samples > 2 {
+            (0..samples).for_each(|_| buf.extend(8u16.to_le_bytes()));
+        }
+        let resolution_offset = buf.len() as u32;
+        resolution.iter().for_each(|v| buf.extend(v.to_le_bytes()));
+
+        let mut entries = vec![
+            Entry::long(TAG_NEW_SUBFILE_TYPE, SUBFILE_PAGE),
+            Entry::long(TAG_IMAGE_WIDTH, page.width),
+            Entry::long(TAG_IMAGE_LENGTH, page.height),
+            if samples > 2 {
+                Entry::new(TAG_BITS_PER_SAMPLE, TYPE_SHORT, samples, bits_offset)
+            } else {
+                Entry::short(TAG_BITS_PER_SAMPLE, bits)
+            },
+            Entry::short(TAG_COMPRESSION, scheme),
+            Entry::short(TAG_PHOTOMETRIC, photometric),
+            Entry::long(TAG_STRIP_OFFSETS, strip),
+            Entry::short(TAG_SAMPLES_PER_PIXEL, samples as u16),
+            Entry::long(TAG_ROWS_PER_STRIP, page.height),
+            Entry::long(TAG_STRIP_BYTE_COUNTS, compressed.len() as u32),
+            Entry::new(TAG_X_RESOLUTION, TYPE_RATIONAL, 1, resolution_offset),
+            Entry::new(TAG_Y_RESOLUTION, TYPE_RATIONAL, 1, resolution_offset),
+            Entry::short(TAG_PLANAR_CONFIGURATION, 1),
+            Entry::short(TAG_RESOLUTION_UNIT, RESOLUTION_UNIT_INCH),
+            Entry::new(
+                TAG_PAGE_NUMBER,
+                TYPE_SHORT,
+                2,
+                u32::from(i as u16) | (u32::from(pages.len() as u16) << 16),
+            ),
+        ];
+        if samples == 4 {
+            entries.push(Entry::short(TAG_EXTRA_SAMPLES, EXTRA_SAMPLES_UNASSOCIATED));
+        }
+
+        let ifd = buf.len() as u32;
+        buf[link..link + 4].copy_from_slice(&ifd.to_le_bytes());
+        buf.extend((entries.len() as u16).to_le_bytes());
+        for entry in entries {
+            buf.extend(entry.tag.to_le_bytes());
+            buf.extend(entry.kind.to_le_bytes());
+            buf.extend(entry.count.to_le_bytes());
+            buf.extend(entry.value.to_le_bytes());
+        }
+        link = buf.len();
+        buf.extend(0u32.to_le_bytes());
+    }
+
+    buf
+}
+
+const TAG_NEW_SUBFILE_TYPE: u16 = 254;
+const TAG_IMAGE_WIDTH: u16 = 256;
+const TAG_IMAGE_LENGTH: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_COMPRESSION: u16 = 259;
+const TAG_PHOTOMETRIC: u16 = 262;
+const TAG_STRIP_OFFSETS: u16 = 273;
+const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+const TAG_ROWS_PER_STRIP: u16 = 278;
+const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+const TAG_X_RESOLUTION: u16 = 282;
+const TAG_Y_RESOLUTION: u16 = 283;
+const TAG_PLANAR_CONFIGURATION: u16 = 284;
+const TAG_RESOLUTION_UNIT: u16 = 296;
+const TAG_PAGE_NUMBER: u16 = 297;
+const TAG_EXTRA_SAMPLES: u16 = 338;
+
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_RATIONAL: u16 = 5;
+
+const SUBFILE_PAGE: u32 = 2;
+const COMPRESSION_CCITT_G4: u16 = 4;
+const COMPRESSION_LZW: u16 = 5;
+const PHOTOMETRIC_WHITE_IS_ZERO: u16 = 0;
+const PHOTOMETRIC_RGB: u16 = 2;
+const RESOLUTION_UNIT_INCH: u16 = 2;
+const EXTRA_SAMPLES_UNASSOCIATED: u16 = 2;
+
+/// An entry in an image file directory.
+struct Entry {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    /// The value if it fits into four bytes, otherwise its offset.
+    value: u32,
+}
+
+impl Entry {
+    fn new(tag: u16, kind: u16, count: u32, value: u32) -> Self {
+        Self { tag, kind, count, value }
+    }
+
+    fn short(tag: u16, value: u16) -> Self {
+        Self::new(tag, TYPE_SHORT, 1, value.into())
+    }
+
+    fn long(tag: u16, value: u32) -> Self {
+        Self::new(tag, TYPE_LONG, 1, value)
+    }
+}
+
+/// Pad the buffer to an even length, as offsets must be word-aligned.
+fn pad(buf: &mut Vec<u8>) {
+    if buf.len() % 2 == 1 {
+        buf.push(0);
+    }
+}
+
+/// Compress data with LZW as specified by TIFF 6.0.
+fn encode_lzw(data: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    const FIRST: u16 = 258;
+
+    let mut writer = BitWriter::default();
+    let mut table = HashMap::<(u16, u8), u16>::new();
+    let mut next = FIRST;
+    let mut width = 9;
+    writer.write(CLEAR, width);
+
+    let mut prefix = None;
+    for &byte in data {
+        let Some(current) = prefix else {
+            prefix = Some(u16::from(byte));
+            continue;
+        };
+
+        if let Some(&code) = table.get(&(current, byte)) {
+            prefix = Some(code);
+            continue;
+        }
+
+        writer.write(current, width);
+        table.insert((current, byte), next);
+        prefix = Some(byte.into());
+
+        if advance_lzw(&mut next, &mut width) {
+            writer.write(CLEAR, width);
+            table.clear();
+            next = FIRST;
+            width = 9;
+        }
+    }
+
+    // Like libtiff, the final code counts as a new entry, which may change the
+    // width of the end code.
+    if let Some(current) = prefix {
+        writer.write(current, width);
+        if advance_lzw(&mut next, &mut width) {
+            writer.write(CLEAR, width);
+            width = 9;
+        }
+    }
+
+    writer.write(END, width);
+    writer.finish()
+}
+
+/// Account for a new entry in the LZW table and grow the code width if the
+/// next code doesn't fit anymore. Returns whether the table is full and must
+/// be cleared.
+fn advance_lzw(next: &mut u16, width: &mut u8) -> bool {
+    *next += 1;
+    if *next == 4094 {
+        return true;
+    }
+    if *next > (1 << *width) - 1 {
+        *width += 1;
+    }
+    false
+}
+
+/// Compress bilevel data with CCITT Group 4 (ITU-T T.6).
+fn encode_g4(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let width = width as usize;
+    let stride = width.div_ceil(8);
+    let mut writer = BitWriter::default();
+
+    // The line above the first one is imagined to be white.
+    let white = vec![0; stride];
+    let mut reference = white.as_slice();
+    for line in data.chunks_exact(stride).take(height as usize) {
+        encode_g4_line(&mut writer, reference, line, width);
+        reference = line;
+    }
+
+    // The end of the block is marked by two end-of-line codes.
+    writer.write(1, 12);
+    writer.write(1, 12);
+    writer.finish()
+}
+
+/// Encode a single line relative to the line above it.
+///
+/// The names of the variables follow the standard: `a0` is the current
+/// position on the coding line, `a1` and `a2` are the next changing elements
+/// on the coding line, and `b1` and `b2` the ones on the reference line.
+fn encode_g4_line(writer: &mut BitWriter, reference: &[u8], line: &[u8], width: usize) {
+    // The imaginary position before the first pixel.
+    let mut a0 = None;
+    let mut black = false;
+
+    loop {
+        let start = a0.map_or(0, |a0| a0 + 1);
+        let a1 = next_change(line, start, width);
+        let mut b1 = next_change(reference, start, width);
+        if b1 < width && is_black(reference, b1) == black {
+            b1 = next_change(reference, b1 + 1, width);
+        }
+        let b2 = next_change(reference, b1 + 1, width);
+
+        let next = if b2 < a1 {
+            // Pass mode.
+            writer.write(0b0001, 4);
+            b2
+        } else if a1.abs_diff(b1) <= 3 {
+            // Vertical mode.
+            let (code, len) = match a1 as isize - b1 as isize {
+                -3 => (0b0000010, 7),
+                -2 => (0b000010, 6),
+                -1 => (0b010, 3),
+                0 => (0b1, 1),
+                1 => (0b011, 3),
+                2 => (0b000011, 6),
+                _ => (0b0000011, 7),
+            };
+            writer.write(code, len);
+            black = !black;
+            a1
+        } else {
+            // Horizontal mode.
+            let a2 = next_change(line, a1 + 1, width);
+            writer.write(0b001, 3);
+            write_run(writer, a1 - a0.unwrap_or(0), black);
+            write_run(writer, a2 - a1, !black);
+            a2
+        };
+
+        if next >= width {
+            break;
+        }
+        a0 = Some(next);
+    }
+}
+
+/// Find the first changing element at or after `start`, that is the first
+/// pixel with a different color than the one before it. The pixel before the
+/// first one is imagined to be white.
+fn next_change(line: &[u8], start: usize, width: usize) -> usize {
+    if start >= width {
+        return width;
+    }
+
+    let before = start > 0 && is_black(line, start - 1);
+    (start..width).find(|&x| is_black(line, x) != before).unwrap_or(width)
+}
+
+/// Whether the pixel at the given position is black.
+fn is_black(line: &[u8], x: usize) -> bool {
+    (line[x / 8] >> (7 - x % 8)) & 1 == 1
+}
+
+/// Write the code for a run of pixels of the same color.
+fn write_run(writer: &mut BitWriter, mut run: usize, black: bool) {
+    let (terminating, makeup) = if black {
+        (&BLACK_TERMINATING, &BLACK_MAKEUP)
+    } else {
+        (&WHITE_TERMINATING, &WHITE_MAKEUP)
+    };
+
+    let max = EXTENDED_MAKEUP.len() - 1;
+    while run >= 2560 {
+        let (code, len) = EXTENDED_MAKEUP[max];
+        writer.write(code, len);
+        run -= 2560;
+    }
+
+    if run >= 64 {
+        let i = run / 64 - 1;
+        let (code, len) = makeup
+            .get(i)
+            .copied()
+            .unwrap_or_else(|| EXTENDED_MAKEUP[i - makeup.len()]);
+        writer.write(code, len);
+        run %= 64;
+    }
+
+    let (code, len) = terminating[run];
+    writer.write(code, len);
+}
+
+/// Terminating codes for white runs of 0 to 63 pixels.
+#[rustfmt::skip]
+const WHITE_TERMINATING: [(u16, u8); 64] = [
+    (0b00110101, 8), (0b000111, 6), (0b0111, 4), (0b1000, 4), (0b1011, 4), (0b1100, 4),
+    (0b1110, 4), (0b1111, 4), (0b10011, 5), (0b10100, 5), (0b00111, 5), (0b01000, 5),
+    (0b001000, 6), (0b000011, 6), (0b110100, 6), (0b110101, 6), (0b101010, 6),
+    (0b101011, 6), (0b0100111, 7), (0b0001100, 7), (0b0001000, 7), (0b0010111, 7),
+    (0b0000011, 7), (0b0000100, 7), (0b0101000, 7), (0b0101011, 7), (0b0010011, 7),
+    (0b0100100, 7), (0b0011000, 7), (0b00000010, 8), (0b00000011, 8), (0b00011010, 8),
+    (0b00011011, 8), (0b00010010, 8), (0b00010011, 8), (0b00010100, 8), (0b00010101, 8),
+    (0b00010110, 8), (0b00010111, 8), (0b00101000, 8), (0b00101001, 8), (0b00101010, 8),
+    (0b00101011, 8), (0b00101100, 8), (0b00101101, 8), (0b00000100, 8), (0b00000101, 8),
+    (0b00001010, 8), (0b00001011, 8), (0b01010010, 8), (0b01010011, 8), (0b01010100, 8),
+    (0b01010101, 8), (0b00100100, 8), (0b00100101, 8), (0b01011000, 8), (0b01011001, 8),
+    (0b01011010, 8), (0b01011011, 8), (0b01001010, 8), (0b01001011, 8), (0b00110010, 8),
+    (0b00110011, 8), (0b00110100, 8),
+];
+
+/// Make-up codes for white runs of 64 to 1728 pixels in steps of 64.
+#[rustfmt::skip]
+const WHITE_MAKEUP: [(u16, u8); 27] = [
+    (0b11011, 5), (0b10010, 5), (0b010111, 6), (0b0110111, 7), (0b00110110, 8),
+    (0b00110111, 8), (0b01100100, 8), (0b01100101, 8), (0b01101000, 8), (0b01100111, 8),
+    (0b011001100, 9), (0b011001101, 9), (0b011010010, 9), (0b011010011, 9),
+    (0b011010100, 9), (0b011010101, 9), (0b011010110, 9), (0b011010111, 9),
+    (0b011011000, 9), (0b011011001, 9), (0b011011010, 9), (0b011011011, 9),
+    (0b010011000, 9), (0b010011001, 9), (0b010011010, 9), (0b011000, 6),
+    (0b010011011, 9),
+];
+
+/// Terminating codes for black runs of 0 to 63 pixels.
+#[rustfmt::skip]
+const BLACK_TERMINATING: [(u16, u8); 64] = [
+    (0b0000110111, 10), (0b010, 3), (0b11, 2), (0b10, 2), (0b011, 3), (0b0011, 4),
+    (0b0010, 4), (0b00011, 5), (0b000101, 6), (0b000100, 6), (0b0000100, 7),
+    (0b0000101, 7), (0b0000111, 7), (0b00000100, 8), (0b00000111, 8), (0b000011000, 9),
+    (0b0000010111, 10), (0b0000011000, 10), (0b0000001000, 10), (0b00001100111, 11),
+    (0b00001101000, 11), (0b00001101100, 11), (0b00000110111, 11), (0b00000101000, 11),
+    (0b00000010111, 11), (0b00000011000, 11), (0b000011001010, 12),
+    (0b000011001011, 12), (0b000011001100, 12), (0b000011001101, 12),
+    (0b000001101000, 12), (0b000001101001, 12), (0b000001101010, 12),
+    (0b000001101011, 12), (0b000011010010, 12), (0b000011010011, 12),
+    (0b000011010100, 12), (0b000011010101, 12), (0b000011010110, 12),
+    (0b000011010111, 12), (0b000001101100, 12), (0b000001101101, 12),
+    (0b000011011010, 12), (0b000011011011, 12), (0b000001010100, 12),
+    (0b000001010101, 12), (0b000001010110, 12), (0b000001010111, 12),
+    (0b000001100100, 12), (0b000001100101, 12), (0b000001010010, 12),
+    (0b000001010011, 12), (0b000000100100, 12), (0b000000110111, 12),
+    (0b000000111000, 12), (0b000000100111, 12), (0b000000101000, 12),
+    (0b000001011000, 12), (0b000001011001, 12), (0b000000101011, 12),
+    (0b000000101100, 12), (0b000001011010, 12), (0b000001100110, 12),
+    (0b000001100111, 12),
+];
+
+/// Make-up codes for black runs of 64 to 1728 pixels in steps of 64.
+#[rustfmt::skip]
+const BLACK_MAKEUP: [(u16, u8); 27] = [
+    (0b0000001111, 10), (0b000011001000, 12), (0b000011001001, 12),
+    (0b000001011011, 12), (0b000000110011, 12), (0b000000110100, 12),
+    (0b000000110101, 12), (0b0000001101100, 13), (0b0000001101101, 13),
+    (0b0000001001010, 13), (0b0000001001011, 13), (0b0000001001100, 13),
+    (0b0000001001101, 13), (0b0000001110010, 13), (0b0000001110011, 13),
+    (0b0000001110100, 13), (0b0000001110101, 13), (0b0000001110110, 13),
+    (0b0000001110111, 13), (0b0000001010010, 13), (0b0000001010011, 13),
+    (0b0000001010100, 13), (0b0000001010101, 13), (0b0000001011010, 13),
+    (0b0000001011011, 13), (0b0000001100100, 13), (0b0000001100101, 13),
+];
+
+/// Make-up codes shared by both colors for runs of 1792 to 2560 pixels in
+/// steps of 64.
+#[rustfmt::skip]
+const EXTENDED_MAKEUP: [(u16, u8); 13] = [
+    (0b00000001000, 11), (0b00000001100, 11), (0b00000001101, 11), (0b000000010010, 12),
+    (0b000000010011, 12), (0b000000010100, 12), (0b000000010101, 12),
+    (0b000000010110, 12), (0b000000010111, 12), (0b000000011100, 12),
+    (0b000000011101, 12), (0b000000011110, 12), (0b000000011111, 12),
+];
+
+/// Writes codes with the most significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    buf: Vec<u8>,
+    acc: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    /// Write the lowest `len` bits of `code`.
+    fn write(&mut self, code: u16, len: u8) {
+        self.acc = (self.acc << len) | u32::from(code);
+        self.bits += len;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.buf.push((self.acc >> self.bits) as u8);
+        }
+        self.acc &= (1 << self.bits) - 1;
+    }
+
+    /// Pad the last byte with zeros and return the bytes.
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.buf.push((self.acc << (8 - self.bits)) as u8);
+        }
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use tiff::decoder::{Decoder, DecodingResult};
+
+    use super::*;
+
+    /// Decode all pages of a TIFF file into their raw pixel data.
+    fn decode(buf: &[u8]) -> Vec<(u32, u32, Vec<u8>)> {
+        let mut decoder = Decoder::new(Cursor::new(buf)).unwrap();
+        let mut pages = vec![];
+        loop {
+            let (width, height) = decoder.dimensions().unwrap();
+            let DecodingResult::U8(data) = decoder.read_image().unwrap() else {
+                panic!("expected 8-bit samples");
+            };
+            pages.push((width, height, data));
+            if !decoder.more_images() {
+                break pages;
+            }
+            decoder.next_image().unwrap();
+        }
+    }
+
+    /// Encode a single page and check that it decodes to the same pixels.
+    fn roundtrip(page: TiffPage, compression: TiffCompression) {
+        let buf = encode(std::slice::from_ref(&page), compression, 72.0);
+        let (width, height, data) = decode(&buf).remove(0);
+        assert_eq!((width, height), (page.width, page.height));
+        assert_pixels_eq(&page, &data);
+    }
+
+    /// Check that decoded data matches the pixels of a page.
+    #[track_caller]
+    fn assert_pixels_eq(page: &TiffPage, data: &[u8]) {
+        match &page.pixels {
+            TiffPixels::Rgb(expected) | TiffPixels::Rgba(expected) => {
+                assert_eq!(data, expected.as_slice());
+            }
+            // Bilevel pixels are decoded with black as zero, and the padding
+            // bits at the end of each row are unspecified.
+            TiffPixels::Bilevel(expected) => {
+                let width = page.width as usize;
+                let stride = width.div_ceil(8);
+                let rows = data.chunks_exact(stride).zip(expected.chunks_exact(stride));
+                for (y, (row, expected)) in rows.enumerate() {
+                    for x in 0..width {
+                        assert_ne!(
+                            is_black(row, x),
+                            is_black(expected, x),
+                            "pixel ({x}, {y}) of {}x{} page",
+                            page.width,
+                            page.height,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deterministic pseudo-random bytes.
+    fn noise(len: usize, mut seed: u32) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as u8
+            })
+            .collect()
+    }
+
+    /// A bilevel page where each pixel is black if `f` returns true for it.
+    fn bilevel(width: u32, height: u32, f: impl Fn(u32, u32) -> bool) -> TiffPage {
+        let stride = width.div_ceil(8) as usize;
+        let mut data = vec![0; stride * height as usize];
+        for y in 0..height {
+            for x in (0..width).filter(|&x| f(x, y)) {
+                data[y as usize * stride + x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        TiffPage { width, height, pixels: TiffPixels::Bilevel(data) }
+    }
+
+    #[test]
+    fn test_lzw_code_widths() {
+        // Noise adds a table entry for almost every byte, so these lengths
+        // cover every switch from 9 to 12 bits, including those caused by the
+        // final code, and the table being cleared once it is full.
+        for len in (1..5000).step_by(7).chain(500..530).chain(4070..4110) {
+            let data = noise(len, len as u32);
+            let page = TiffPage {
+                width: 8 * len as u32,
+                height: 1,
+                pixels: TiffPixels::Bilevel(data),
+            };
+            roundtrip(page, TiffCompression::Lzw);
+        }
+    }
+
+    #[test]
+    fn test_lzw_multiple_clears() {
+        let mut data = noise(3 * 120 * 90, 1);
+        data.resize(3 * 120 * 100, 7);
+        let page = TiffPage {
+            width: 120,
+            height: 100,
+            pixels: TiffPixels::Rgb(data),
+        };
+        roundtrip(page, TiffCompression::Lzw);
+
+        let data = noise(4 * 33 * 77, 2);
+        let page = TiffPage {
+            width: 33,
+            height: 77,
+            pixels: TiffPixels::Rgba(data),
+        };
+        roundtrip(page, TiffCompression::Lzw);
+    }
+
+    #[test]
+    fn test_g4_modes() {
+        // The second line is white below a black run (pass mode), follows the
+        // edges of the first one (vertical mode), and has a run far from any
+        // edge above (horizontal mode).
+        let first = |x| (2..5).contains(&x) || (20..30).contains(&x);
+        let second = |x| (21..27).contains(&x) || (40..43).contains(&x);
+        for width in [45, 51, 63, 77] {
+            let page = bilevel(width, 3, |x, y| match y {
+                0 => first(x),
+                1 => second(x),
+                _ => x == width - 1,
+            });
+            roundtrip(page, TiffCompression::CcittG4);
+        }
+    }
+
+    #[test]
+    fn test_g4_noise() {
+        for (i, width) in [1, 7, 9, 13, 31, 101, 333].into_iter().enumerate() {
+            let noise = noise(width as usize * 40, i as u32 + 1);
+            let page = bilevel(width, 40, |x, y| {
+                // Keep some runs longer by thresholding the noise differently
+                // on each line.
+                noise[(y * width + x) as usize] < 40 + 5 * y as u8
+            });
+            roundtrip(page, TiffCompression::CcittG4);
+        }
+    }
+
+    #[test]
+    fn test_g4_long_runs() {
+        // Runs longer than 64 and 2560 pixels need make-up codes.
+        let width = 5301;
+        let page = bilevel(width, 4, |x, y| match y {
+            0 => x >= 70,
+            1 => (1800..4500).contains(&x),
+            2 => x < 2561 || x == width - 1,
+            _ => false,
+        });
+        roundtrip(page, TiffCompression::CcittG4);
+    }
+
+    #[test]
+    fn test_multiple_pages() {
+        let pages =
+            [bilevel(17, 5, |x, y| (x + y) % 3 == 0), bilevel(9, 2, |x, _| x % 2 == 0)];
+        let buf = encode(&pages, TiffCompression::CcittG4, 300.0);
+        let decoded = decode(&buf);
+        assert_eq!(decoded.len(), 2);
+        for (page, (width, height, data)) in pages.iter().zip(decoded) {
+            assert_eq!((width, height), (page.width, page.height));
+            assert_pixels_eq(page, &data);
+        }
+    }
+}
@@ -13,6 +13,7 @@ use typst::syntax::{FileId, Source, VirtualPath};
 use typst::text::{Font, FontBook};
 use typst::utils::LazyHash;
 use typst::{Library, World};
+use typst_ide::IdeWorld;
 use typst_kit::fonts::{FontSlot, Fonts};
 use typst_kit::package::PackageStorage;
 use typst_timing::timed;
@@ -237,6 +238,12 @@ impl World for SystemWorld {
     }
 }
 
+impl IdeWorld for SystemWorld {
+    fn upcast(&self) -> &dyn World {
+        self
+    }
+}
+
 impl SystemWorld {
     /// Access the canonical slot for the given file id.
     fn slot<F, T>(&self, id: FileId, f: F) -> T
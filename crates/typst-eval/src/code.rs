@@ -31,7 +31,7 @@ fn eval_code<'a>(
         let span = expr.span();
         let value = match expr {
             ast::Expr::Set(set) => {
-                let styles = set.eval(vm)?;
+                let Some(styles) = vm.recover(|vm| set.eval(vm))? else { continue };
                 if vm.flow.is_some() {
                     break;
                 }
@@ -40,22 +40,31 @@ fn eval_code<'a>(
                 Value::Content(tail.styled_with_map(styles))
             }
             ast::Expr::Show(show) => {
-                let recipe = show.eval(vm)?;
+                let Some(recipe) = vm.recover(|vm| show.eval(vm))? else { continue };
                 if vm.flow.is_some() {
                     break;
                 }
 
                 let tail = eval_code(vm, exprs)?.display();
-                Value::Content(tail.styled_with_recipe(
-                    &mut vm.engine,
-                    vm.context,
-                    recipe,
-                )?)
+                let Some(styled) = vm.recover(|vm| {
+                    tail.styled_with_recipe(&mut vm.engine, vm.context, recipe)
+                })?
+                else {
+                    continue;
+                };
+                Value::Content(styled)
+            }
+            _ => {
+                let Some(value) = vm.recover(|vm| expr.eval(vm))? else { continue };
+                value
             }
-            _ => expr.eval(vm)?,
         };
 
-        output = ops::join(output, value).at(span)?;
+        if !vm.tolerant {
+            output = ops::join(output, value).at(span)?;
+        } else if let Ok(joined) = ops::join(output.clone(), value) {
+            output = joined;
+        }
 
         if let Some(event) = &vm.flow {
             warn_for_discarded_content(&mut vm.engine, event, &output);
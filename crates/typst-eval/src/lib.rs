@@ -68,9 +68,10 @@ pub fn eval(
     let root = source.root();
     let mut vm = Vm::new(engine, context.track(), scopes, root.span());
 
-    // Check for well-formedness unless we are in trace mode.
+    // Check for well-formedness unless we are in tolerant mode, which is
+    // enabled while tracing.
     let errors = root.errors();
-    if !errors.is_empty() && vm.inspected.is_none() {
+    if !errors.is_empty() && !vm.tolerant {
         return Err(errors.into_iter().map(Into::into).collect());
     }
 
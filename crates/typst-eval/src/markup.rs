@@ -33,7 +33,7 @@ fn eval_markup<'a>(
     while let Some(expr) = exprs.next() {
         match expr {
             ast::Expr::Set(set) => {
-                let styles = set.eval(vm)?;
+                let Some(styles) = vm.recover(|vm| set.eval(vm))? else { continue };
                 if vm.flow.is_some() {
                     break;
                 }
@@ -41,16 +41,20 @@ fn eval_markup<'a>(
                 seq.push(eval_markup(vm, exprs)?.styled_with_map(styles))
             }
             ast::Expr::Show(show) => {
-                let recipe = show.eval(vm)?;
+                let Some(recipe) = vm.recover(|vm| show.eval(vm))? else { continue };
                 if vm.flow.is_some() {
                     break;
                 }
 
                 let tail = eval_markup(vm, exprs)?;
-                seq.push(tail.styled_with_recipe(&mut vm.engine, vm.context, recipe)?)
+                if let Some(styled) = vm.recover(|vm| {
+                    tail.styled_with_recipe(&mut vm.engine, vm.context, recipe)
+                })? {
+                    seq.push(styled);
+                }
             }
-            expr => match expr.eval(vm)? {
-                Value::Label(label) => {
+            expr => match vm.recover(|vm| expr.eval(vm))? {
+                Some(Value::Label(label)) => {
                     if let Some(elem) =
                         seq.iter_mut().rev().find(|node| !node.can::<dyn Unlabellable>())
                     {
@@ -70,7 +74,8 @@ fn eval_markup<'a>(
                         ));
                     }
                 }
-                value => seq.push(value.display().spanned(expr.span())),
+                Some(value) => seq.push(value.display().spanned(expr.span())),
+                None => {}
             },
         }
 
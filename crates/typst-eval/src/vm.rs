@@ -1,5 +1,5 @@
 use comemo::Tracked;
-use typst_library::diag::warning;
+use typst_library::diag::{warning, SourceResult};
 use typst_library::engine::Engine;
 use typst_library::foundations::{Context, IntoValue, Scopes, Value};
 use typst_library::World;
@@ -23,6 +23,14 @@ pub struct Vm<'a> {
     pub inspected: Option<Span>,
     /// Data that is contextually made accessible to code behind the scenes.
     pub context: Tracked<'a, Context<'a>>,
+    /// Whether to skip expressions that fail to evaluate instead of aborting.
+    ///
+    /// This is enabled while a span in the evaluated file is under
+    /// inspection, such that values can still be traced in files with errors.
+    /// Other modules, including those imported by this file, are evaluated by
+    /// their own VM and are thus not tolerant: An error in an imported file
+    /// still fails the import.
+    pub tolerant: bool,
 }
 
 impl<'a> Vm<'a> {
@@ -34,7 +42,15 @@ impl<'a> Vm<'a> {
         target: Span,
     ) -> Self {
         let inspected = target.id().and_then(|id| engine.traced.get(id));
-        Self { engine, context, flow: None, scopes, inspected }
+        let tolerant = inspected.is_some();
+        Self {
+            engine,
+            context,
+            flow: None,
+            scopes,
+            inspected,
+            tolerant,
+        }
     }
 
     /// Access the underlying world.
@@ -61,6 +77,31 @@ impl<'a> Vm<'a> {
         self.scopes.top.define_ident(var, value);
     }
 
+    /// Evaluate an expression in a sequence of expressions.
+    ///
+    /// In tolerant mode, errors are discarded and `None` is returned, such
+    /// that evaluation can continue with the next expression. Scopes that
+    /// were entered by the failed expression are exited.
+    pub fn recover<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> SourceResult<T>,
+    ) -> SourceResult<Option<T>> {
+        if !self.tolerant {
+            return f(self).map(Some);
+        }
+
+        let depth = self.scopes.scopes.len();
+        match f(self) {
+            Ok(output) => Ok(Some(output)),
+            Err(_) => {
+                while self.scopes.scopes.len() > depth {
+                    self.scopes.exit();
+                }
+                Ok(None)
+            }
+        }
+    }
+
     /// Trace a value.
     #[cold]
     pub fn trace(&mut self, value: Value) {
@@ -0,0 +1,388 @@
+use std::ffi::OsStr;
+use std::ops::Range;
+use std::path::Path;
+
+use ecow::{eco_format, EcoString};
+use typst::diag::SourceDiagnostic;
+use typst::syntax::{ast, FileId, LinkedNode, Source, SyntaxKind};
+
+use crate::utils::{closest_names, globals};
+use crate::{named_items, IdeWorld};
+
+/// A fix for a diagnostic.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CodeAction {
+    /// A short description of the fix.
+    pub title: EcoString,
+    /// The index of the diagnostic the fix applies to.
+    pub diagnostic: usize,
+    /// The edits to the source file that make up the fix.
+    pub edits: Vec<TextEdit>,
+}
+
+/// A replacement of a range of a source file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TextEdit {
+    /// The byte range to replace. Empty for insertions.
+    pub range: Range<usize>,
+    /// The text to replace the range with.
+    pub text: EcoString,
+}
+
+/// Find fixes for diagnostics that overlap with a range of a source file.
+///
+/// The diagnostics are typically those of the last compilation. Fixes are
+/// offered for unknown variables (corrected spellings and imports from other
+/// files), missing files, unknown font families, and references to missing
+/// labels, as well as for hints that suggest a replacement.
+pub fn code_actions(
+    world: &dyn IdeWorld,
+    source: &Source,
+    range: Range<usize>,
+    diagnostics: &[SourceDiagnostic],
+) -> Vec<CodeAction> {
+    let root = LinkedNode::new(source.root());
+    let mut actions = vec![];
+
+    for (i, diagnostic) in diagnostics.iter().enumerate() {
+        if diagnostic.span.id() != Some(source.id()) {
+            continue;
+        }
+
+        let Some(node) = root.find(diagnostic.span) else { continue };
+        let node_range = node.range();
+        if node_range.start > range.end || range.start > node_range.end {
+            continue;
+        }
+
+        let mut fixer = Fixer {
+            world,
+            source,
+            node,
+            diagnostic: i,
+            actions: vec![],
+        };
+        fixer.fix(diagnostic);
+        actions.extend(fixer.actions);
+    }
+
+    actions
+}
+
+/// The advice of hints whose code is a replacement for the node the
+/// diagnostic points at, as opposed to an example.
+const REPLACEMENT_HINTS: &[&str] = &[
+    "try adding spaces around the minus sign",
+    "try adding spaces around the minus signs",
+    "try adding a hash before it",
+    "try adding spaces between each letter",
+    "try placing it in quotes",
+];
+
+/// Collects fixes for a single diagnostic.
+struct Fixer<'a> {
+    world: &'a dyn IdeWorld,
+    source: &'a Source,
+    node: LinkedNode<'a>,
+    diagnostic: usize,
+    actions: Vec<CodeAction>,
+}
+
+impl Fixer<'_> {
+    /// Add fixes based on the message and hints of the diagnostic.
+    fn fix(&mut self, diagnostic: &SourceDiagnostic) {
+        let message = diagnostic.message.as_str();
+        if let Some(var) = message.strip_prefix("unknown variable: ") {
+            self.unknown_variable(var);
+        } else if let Some(family) = message.strip_prefix("unknown font family: ") {
+            self.unknown_font(family);
+        } else if message.starts_with("file not found") {
+            self.file_not_found();
+        } else if let Some(label) = message
+            .strip_prefix("label `<")
+            .and_then(|rest| rest.strip_suffix(">` does not exist in the document"))
+        {
+            self.missing_label(label);
+        }
+
+        // Some hints of the form "try doing this: `replacement`" describe a
+        // replacement of the erroneous code. Others only show an example.
+        for hint in &diagnostic.hints {
+            let Some((advice, rest)) = hint.rsplit_once(": `") else { continue };
+            let Some(replacement) = rest.strip_suffix('`') else { continue };
+            if REPLACEMENT_HINTS.iter().any(|known| advice.ends_with(known)) {
+                self.replace(self.node.range(), replacement);
+            }
+        }
+    }
+
+    /// Offer similarly named items in scope and imports of top-level
+    /// definitions from other files.
+    fn unknown_variable(&mut self, var: &str) {
+        let mut names = vec![];
+        named_items(self.world, self.node.clone(), |item| {
+            names.push(item.name().clone());
+            None::<()>
+        });
+        names.extend(
+            globals(self.world, &self.node).iter().map(|(name, ..)| name.clone()),
+        );
+
+        for name in closest_names(var, names.iter().map(EcoString::as_str)) {
+            self.replace(self.node.range(), name);
+        }
+
+        for id in self.world.files() {
+            let extension = id.vpath().as_rootless_path().extension();
+            if id == self.source.id() || extension.and_then(OsStr::to_str) != Some("typ")
+            {
+                continue;
+            }
+
+            let Ok(other) = self.world.source(id) else { continue };
+            let defined = other
+                .root()
+                .children()
+                .filter_map(|child| child.cast::<ast::LetBinding>())
+                .any(|binding| {
+                    binding.kind().bindings().iter().any(|ident| ident.as_str() == var)
+                });
+
+            if let Some(path) = defined.then(|| self.relative_path(id)).flatten() {
+                self.push(
+                    eco_format!("Import `{var}` from `{path}`"),
+                    vec![TextEdit {
+                        range: 0..0,
+                        text: eco_format!("#import {}: {var}\n", quote(&path)),
+                    }],
+                );
+            }
+        }
+    }
+
+    /// Offer installed font families with similar names.
+    fn unknown_font(&mut self, family: &str) {
+        let Some(string) = find_str(&self.node, |string| string.to_lowercase() == family)
+        else {
+            return;
+        };
+
+        let book = self.world.book();
+        let families = closest_names(family, book.families().map(|(name, _)| name));
+        for name in families {
+            self.replace(string.range(), &quote(name));
+        }
+    }
+
+    /// Offer existing files with a similar path and the same extension.
+    fn file_not_found(&mut self) {
+        let Some(string) = self.node.cast::<ast::Str>() else { return };
+        let missing = string.get();
+        let extension = Path::new(missing.as_str()).extension();
+
+        let paths: Vec<EcoString> = self
+            .world
+            .files()
+            .into_iter()
+            .filter(|&id| {
+                id != self.source.id()
+                    && id.vpath().as_rootless_path().extension() == extension
+            })
+            .filter_map(|id| self.relative_path(id))
+            .collect();
+
+        for path in closest_names(&missing, paths.iter().map(EcoString::as_str)) {
+            self.replace(self.node.range(), &quote(path));
+        }
+    }
+
+    /// Offer labels with similar names and adding the label to the closest
+    /// preceding heading.
+    fn missing_label(&mut self, label: &str) {
+        // References replace their marker, label literals the whole label.
+        let (range, prefix, suffix) = match self.node.kind() {
+            SyntaxKind::Ref => match self
+                .node
+                .children()
+                .find(|child| child.kind() == SyntaxKind::RefMarker)
+            {
+                Some(marker) => (marker.range(), "@", ""),
+                None => return,
+            },
+            SyntaxKind::Label => (self.node.range(), "<", ">"),
+            _ => return,
+        };
+
+        let mut labels = vec![];
+        let mut headings = vec![];
+        collect_labels(&LinkedNode::new(self.source.root()), &mut labels, &mut headings);
+
+        for name in closest_names(label, labels.iter().map(EcoString::as_str)) {
+            self.replace(range.clone(), &eco_format!("{prefix}{name}{suffix}"));
+        }
+
+        // A heading can only have one label, so labeled headings are left
+        // alone.
+        let offset = self.node.offset();
+        if let Some((end, false)) =
+            headings.into_iter().rev().find(|&(end, _)| end <= offset)
+        {
+            self.push(
+                eco_format!("Add label `<{label}>` to the preceding heading"),
+                vec![TextEdit { range: end..end, text: eco_format!(" <{label}>") }],
+            );
+        }
+    }
+
+    /// Offer replacing a range with some text.
+    fn replace(&mut self, range: Range<usize>, text: &str) {
+        self.push(
+            eco_format!("Replace with `{text}`"),
+            vec![TextEdit { range, text: text.into() }],
+        );
+    }
+
+    fn push(&mut self, title: EcoString, edits: Vec<TextEdit>) {
+        let action = CodeAction { title, diagnostic: self.diagnostic, edits };
+        if !self.actions.contains(&action) {
+            self.actions.push(action);
+        }
+    }
+
+    /// The path of a file relative to the source file's directory.
+    fn relative_path(&self, id: FileId) -> Option<EcoString> {
+        if id.package() != self.source.id().package() {
+            return None;
+        }
+        let base = self.source.id().vpath().as_rooted_path().parent()?;
+        let path = pathdiff::diff_paths(id.vpath().as_rooted_path(), base)?;
+        Some(path.to_string_lossy().replace('\\', "/").into())
+    }
+}
+
+/// Find a string literal in a node whose value matches a predicate.
+fn find_str<'a>(
+    node: &LinkedNode<'a>,
+    predicate: impl Fn(&str) -> bool + Copy,
+) -> Option<LinkedNode<'a>> {
+    if let Some(string) = node.cast::<ast::Str>() {
+        return predicate(&string.get()).then(|| node.clone());
+    }
+    node.children().find_map(|child| find_str(&child, predicate))
+}
+
+/// Collect the names of all labels and the end offsets of all headings'
+/// text, along with whether the heading already has a label.
+fn collect_labels(
+    node: &LinkedNode,
+    labels: &mut Vec<EcoString>,
+    headings: &mut Vec<(usize, bool)>,
+) {
+    match node.kind() {
+        SyntaxKind::Label => {
+            let text = node.text();
+            labels.push(text.trim_start_matches('<').trim_end_matches('>').into());
+        }
+        SyntaxKind::Heading => {
+            let text = node.get().clone().into_text();
+            let labeled = node.children().any(|child| has_label(&child));
+            headings.push((node.offset() + text.trim_end().len(), labeled));
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect_labels(&child, labels, headings);
+    }
+}
+
+/// Whether a node is or contains a label.
+fn has_label(node: &LinkedNode) -> bool {
+    node.kind() == SyntaxKind::Label || node.children().any(|child| has_label(&child))
+}
+
+/// A string literal with the given value.
+fn quote(string: &str) -> EcoString {
+    eco_format!("\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::code_actions;
+    use crate::tests::TestWorld;
+
+    /// The titles of the actions for the first diagnostic and the text after
+    /// applying the first action.
+    #[track_caller]
+    fn test(world: &TestWorld) -> (Vec<String>, String) {
+        let warned = typst::compile(world);
+        let mut diagnostics = warned.warnings.to_vec();
+        if let Err(errors) = warned.output {
+            diagnostics.extend(errors);
+        }
+
+        let source = &world.main;
+        let actions = code_actions(world, source, 0..source.len_bytes(), &diagnostics);
+        let titles = actions.iter().map(|action| action.title.to_string()).collect();
+
+        let mut text = source.text().to_string();
+        if let Some(action) = actions.first() {
+            for edit in action.edits.iter().rev() {
+                text.replace_range(edit.range.clone(), &edit.text);
+            }
+        }
+
+        (titles, text)
+    }
+
+    #[test]
+    fn test_code_actions_spelling() {
+        let (titles, text) = test(&TestWorld::new("#hedaing[A]"));
+        assert!(titles.contains(&"Replace with `heading`".into()));
+        assert_eq!(text, "#heading[A]");
+    }
+
+    #[test]
+    fn test_code_actions_hint() {
+        let world = TestWorld::new("#let a = 1; #let b = 2; #{a-b}");
+        assert_eq!(test(&world).1, "#let a = 1; #let b = 2; #{a - b}");
+    }
+
+    #[test]
+    fn test_code_actions_hint_example() {
+        // The hint shows how to write the whole call, which is no
+        // replacement for the float literal it points at.
+        let world = TestWorld::new("#decimal(1.5)");
+        let (titles, text) = test(&world);
+        assert!(titles.is_empty());
+        assert_eq!(text, "#decimal(1.5)");
+    }
+
+    #[test]
+    fn test_code_actions_import() {
+        let world = TestWorld::new("#foo").with_source("other.typ", "#let foo = 1");
+        let (titles, text) = test(&world);
+        assert_eq!(titles, ["Import `foo` from `other.typ`"]);
+        assert_eq!(text, "#import \"other.typ\": foo\n#foo");
+    }
+
+    #[test]
+    fn test_code_actions_font() {
+        let world = TestWorld::new("#set text(font: \"Libertinus Serf\")");
+        assert_eq!(test(&world).1, "#set text(font: \"Libertinus Serif\")");
+    }
+
+    #[test]
+    fn test_code_actions_label() {
+        let (titles, text) = test(&TestWorld::new("= Intro\nSee @intro."));
+        assert_eq!(titles, ["Add label `<intro>` to the preceding heading"]);
+        assert_eq!(text, "= Intro <intro>\nSee @intro.");
+    }
+
+    #[test]
+    fn test_code_actions_label_existing() {
+        let (titles, text) = test(&TestWorld::new("= Intro <intr>\nSee @intro."));
+        assert_eq!(titles, ["Replace with `@intr`"]);
+        assert_eq!(text, "= Intro <intr>\nSee @intr.");
+    }
+}
@@ -1624,6 +1624,21 @@ mod tests {
         test("#{ let x = (1, 2, 3); x. }", -2).must_include(["at", "push", "pop"]);
     }
 
+    /// Test that values are still traced after an expression that fails to
+    /// evaluate.
+    #[test]
+    fn test_autocomplete_after_error() {
+        test("#let a = nope\n#{ let x = (1, 2); x. }", -2).must_include(["push", "pop"]);
+    }
+
+    /// Test that values are still traced after syntax errors.
+    #[test]
+    fn test_autocomplete_after_syntax_error() {
+        test("*unclosed\n\n#{ let x = (1, 2); x. }", -2).must_include(["push", "pop"]);
+        test("#let a = (1, +)\n#{ let x = (1, 2); x. }", -2)
+            .must_include(["push", "pop"]);
+    }
+
     /// Test that extra space before '.' is handled correctly.
     #[test]
     fn test_autocomplete_whitespace() {
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ecow::EcoString;
+use typst::syntax::{ast, FileId, LinkedNode, Source, SyntaxKind};
+use typst::utils::hash128;
+
+use crate::IdeWorld;
+
+/// A named item that is defined somewhere in a project.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Symbol {
+    /// The symbol's name. For headings, this is the heading's text.
+    pub name: EcoString,
+    /// What kind of item the symbol is.
+    pub kind: SymbolKind,
+    /// The file the symbol is defined in.
+    pub id: FileId,
+    /// The byte range of the symbol's definition in its file.
+    pub range: Range<usize>,
+}
+
+/// What kind of item a symbol is.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SymbolKind {
+    /// A label, without the angle brackets.
+    Label,
+    /// A heading.
+    Heading,
+    /// A top-level let binding of a function.
+    Function,
+    /// Any other top-level let binding.
+    Variable,
+}
+
+/// An index of the symbols in all files reachable from the main file.
+///
+/// Files are reached through imports and includes of paths. Package imports
+/// are not followed. Calling [`update`](Self::update) after sources changed
+/// only rescans the files whose content is different.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    /// The reachable files in the order they were discovered.
+    order: Vec<FileId>,
+    /// The scanned files.
+    files: HashMap<FileId, IndexedFile>,
+}
+
+/// The symbols and dependencies of a single file.
+#[derive(Debug, Clone)]
+struct IndexedFile {
+    /// The hash of the source the file was scanned from.
+    hash: u128,
+    /// The symbols defined in the file.
+    symbols: Vec<Symbol>,
+    /// The files imported or included by the file.
+    deps: Vec<FileId>,
+}
+
+impl SymbolIndex {
+    /// Create a new, empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bring the index up to date with the world's sources.
+    ///
+    /// Files that are no longer reachable from the main file are dropped.
+    pub fn update(&mut self, world: &dyn IdeWorld) {
+        let mut old = std::mem::take(&mut self.files);
+        self.order.clear();
+
+        let mut queue = vec![world.main()];
+        while let Some(id) = queue.pop() {
+            if self.files.contains_key(&id) {
+                continue;
+            }
+
+            let Ok(source) = world.source(id) else { continue };
+            let hash = hash128(&source);
+            let file = match old.remove(&id) {
+                Some(file) if file.hash == hash => file,
+                _ => scan(&source, hash),
+            };
+
+            queue.extend(file.deps.iter().rev().copied());
+            self.order.push(id);
+            self.files.insert(id, file);
+        }
+    }
+
+    /// The indexed files in the order they were discovered.
+    pub fn files(&self) -> &[FileId] {
+        &self.order
+    }
+
+    /// All symbols, grouped by file.
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.order.iter().flat_map(|id| &self.files[id].symbols)
+    }
+
+    /// The symbols defined in a file.
+    pub fn symbols_in(&self, id: FileId) -> &[Symbol] {
+        self.files.get(&id).map_or(&[], |file| &file.symbols)
+    }
+
+    /// The symbols whose name contains the query, ignoring case.
+    pub fn search(&self, query: &str) -> Vec<&Symbol> {
+        let query = query.to_lowercase();
+        self.symbols()
+            .filter(|symbol| symbol.name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// The symbols of a kind with exactly the given name.
+    pub fn find(&self, kind: SymbolKind, name: &str) -> Vec<&Symbol> {
+        self.symbols()
+            .filter(|symbol| symbol.kind == kind && symbol.name == name)
+            .collect()
+    }
+}
+
+/// Scan a source for its symbols and dependencies.
+fn scan(source: &Source, hash: u128) -> IndexedFile {
+    let mut file = IndexedFile { hash, symbols: vec![], deps: vec![] };
+    let id = source.id();
+    let root = LinkedNode::new(source.root());
+
+    // Only let bindings at the top level are visible to other files.
+    for child in root.children() {
+        let Some(binding) = child.cast::<ast::LetBinding>() else { continue };
+        let kind = match binding.kind() {
+            ast::LetBindingKind::Closure(_) => SymbolKind::Function,
+            ast::LetBindingKind::Normal(_) => SymbolKind::Variable,
+        };
+
+        for ident in binding.kind().bindings() {
+            let Some(node) = child.find(ident.span()) else { continue };
+            file.symbols.push(Symbol {
+                name: ident.get().clone(),
+                kind,
+                id,
+                range: node.range(),
+            });
+        }
+    }
+
+    collect(&root, id, &mut file);
+    file
+}
+
+/// Collect the labels, headings, and dependencies in a node and its
+/// descendants.
+fn collect(node: &LinkedNode, id: FileId, file: &mut IndexedFile) {
+    match node.kind() {
+        // Labels in code, like in `#ref(<x>)`, are uses, not definitions.
+        SyntaxKind::Label if node.parent_kind() == Some(SyntaxKind::Markup) => {
+            let text = node.text();
+            file.symbols.push(Symbol {
+                name: text.trim_start_matches('<').trim_end_matches('>').into(),
+                kind: SymbolKind::Label,
+                id,
+                range: node.range(),
+            });
+        }
+        SyntaxKind::Heading => {
+            if let Some(heading) = node.cast::<ast::Heading>() {
+                // The heading's own label is not part of its name.
+                let text: String = heading
+                    .body()
+                    .to_untyped()
+                    .children()
+                    .filter(|child| child.kind() != SyntaxKind::Label)
+                    .map(|child| child.clone().into_text())
+                    .collect();
+                file.symbols.push(Symbol {
+                    name: text.trim().into(),
+                    kind: SymbolKind::Heading,
+                    id,
+                    range: node.range(),
+                });
+            }
+        }
+        SyntaxKind::ModuleImport | SyntaxKind::ModuleInclude => {
+            let source = node
+                .cast::<ast::ModuleImport>()
+                .map(ast::ModuleImport::source)
+                .or_else(|| {
+                    node.cast::<ast::ModuleInclude>().map(ast::ModuleInclude::source)
+                });
+            if let Some(ast::Expr::Str(path)) = source {
+                let path = path.get();
+                if !path.starts_with('@') {
+                    let dep = id.join(&path);
+                    if !file.deps.contains(&dep) {
+                        file.deps.push(dep);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect(&child, id, file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SymbolIndex, SymbolKind};
+    use crate::tests::TestWorld;
+
+    /// The names and kinds of all indexed symbols.
+    #[track_caller]
+    fn test(world: &TestWorld) -> Vec<(String, SymbolKind)> {
+        let mut index = SymbolIndex::new();
+        index.update(world);
+        index
+            .symbols()
+            .map(|symbol| (symbol.name.to_string(), symbol.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_index_single_file() {
+        let world =
+            TestWorld::new("#let f(x) = x\n#let (a, b) = (1, 2)\n= Intro <intro>");
+        assert_eq!(
+            test(&world),
+            [
+                ("f".into(), SymbolKind::Function),
+                ("a".into(), SymbolKind::Variable),
+                ("b".into(), SymbolKind::Variable),
+                ("Intro".into(), SymbolKind::Heading),
+                ("intro".into(), SymbolKind::Label),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_reachable_files() {
+        let world = TestWorld::new("#import \"lib.typ\": x\n#include \"ch.typ\"")
+            .with_source("lib.typ", "#let x = 1")
+            .with_source("ch.typ", "#figure[] <fig:pipeline>")
+            .with_source("unused.typ", "#let y = 2");
+
+        let mut index = SymbolIndex::new();
+        index.update(&world);
+        assert_eq!(index.files().len(), 3);
+        assert_eq!(index.find(SymbolKind::Label, "fig:pipeline").len(), 1);
+        assert_eq!(index.search("X").len(), 1);
+        assert!(index.search("y").is_empty());
+    }
+
+    #[test]
+    fn test_index_label_uses() {
+        let world = TestWorld::new(
+            "#show <x>: strong
+#ref(<x>)
+[A] <x>",
+        );
+        let mut index = SymbolIndex::new();
+        index.update(&world);
+        let found = index.find(SymbolKind::Label, "x");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].range, 32..35);
+    }
+}
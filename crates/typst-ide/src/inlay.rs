@@ -0,0 +1,323 @@
+use std::ops::Range;
+
+use ecow::{eco_format, EcoString};
+use typst::foundations::{Repr, Value};
+use typst::syntax::{ast, LinkedNode, Source, Span, SyntaxKind};
+
+use crate::utils::static_value;
+use crate::{analyze_expr, named_items, IdeWorld, NamedItem};
+
+/// The maximum length of a value's representation in a hint.
+const MAX_VALUE_LEN: usize = 40;
+
+/// The maximum number of value hints per request. Each one traces a full
+/// compilation of the document.
+const MAX_VALUE_HINTS: usize = 8;
+
+/// An annotation that is displayed inline in the source code.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InlayHint {
+    /// The byte offset at which the hint is displayed.
+    pub offset: usize,
+    /// The text of the hint.
+    pub label: EcoString,
+    /// What the hint describes.
+    pub kind: InlayHintKind,
+}
+
+/// What an inlay hint describes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum InlayHintKind {
+    /// The name of the parameter a positional argument is passed to, e.g.
+    /// `children:`. Displayed before the argument.
+    Parameter,
+    /// The value a let binding is initialized with, e.g. `= 3`. Displayed
+    /// after the initializing expression.
+    Value,
+}
+
+/// Find inlay hints for a range of a source file.
+///
+/// Positional arguments are annotated with the names of the parameters they
+/// are passed to, unless the argument is a variable of the same name. The
+/// parameters are determined from the [`ParamInfo`](typst::foundations::ParamInfo)
+/// of native functions and from the definition of closures.
+///
+/// Let bindings are annotated with the value of their initializing expression
+/// if it is not a literal and the value could be determined through
+/// [`analyze_expr`]. Since that traces a compilation of the whole document
+/// per binding, only the first eight bindings in the range that need it are
+/// annotated.
+pub fn inlay_hints(
+    world: &dyn IdeWorld,
+    source: &Source,
+    range: Range<usize>,
+) -> Vec<InlayHint> {
+    let mut hints = vec![];
+    let mut traces = MAX_VALUE_HINTS;
+    collect(world, &LinkedNode::new(source.root()), &range, &mut traces, &mut hints);
+    hints
+}
+
+/// Collect hints for a node and its descendants that overlap with the range.
+fn collect(
+    world: &dyn IdeWorld,
+    node: &LinkedNode,
+    range: &Range<usize>,
+    traces: &mut usize,
+    hints: &mut Vec<InlayHint>,
+) {
+    let node_range = node.range();
+    if node_range.start > range.end || range.start > node_range.end {
+        return;
+    }
+
+    if node.kind() == SyntaxKind::FuncCall {
+        parameter_hints(world, node, hints);
+    } else if node.kind() == SyntaxKind::LetBinding {
+        value_hint(world, node, traces, hints);
+    }
+
+    for child in node.children() {
+        collect(world, &child, range, traces, hints);
+    }
+}
+
+/// A positional parameter of a function.
+struct Param {
+    /// The parameter's name, if it is a plain identifier.
+    name: Option<EcoString>,
+    /// Whether the parameter takes any number of arguments.
+    variadic: bool,
+}
+
+/// Add hints with parameter names for the positional arguments of a call.
+fn parameter_hints(world: &dyn IdeWorld, node: &LinkedNode, hints: &mut Vec<InlayHint>) {
+    let Some(call) = node.cast::<ast::FuncCall>() else { return };
+    let Some(callee) = node.find(call.callee().span()) else { return };
+    let Some(args) = node.children().find(|child| child.kind() == SyntaxKind::Args)
+    else {
+        return;
+    };
+    let Some(params) = callee_params(world, &callee) else { return };
+
+    // Trailing content blocks are passed positionally, but they are obvious
+    // enough without a hint.
+    let mut positional = vec![];
+    let mut in_parens = false;
+    for child in args.children() {
+        match child.kind() {
+            SyntaxKind::LeftParen => in_parens = true,
+            SyntaxKind::RightParen => in_parens = false,
+            // The number of spread arguments is unknown, so subsequent
+            // arguments can't be matched up.
+            SyntaxKind::Spread => break,
+            SyntaxKind::ContentBlock if !in_parens => positional.push(None),
+            _ if child.is::<ast::Expr>() => positional.push(Some(child)),
+            _ => {}
+        }
+    }
+
+    // A variadic parameter takes all arguments that are not needed by the
+    // other parameters.
+    let fixed = params.iter().filter(|param| !param.variadic).count();
+    let spare = positional.len().saturating_sub(fixed);
+    let mut assigned = vec![];
+    for param in &params {
+        if param.variadic {
+            assigned.extend((0..spare).map(|i| (param, i == 0)));
+        } else {
+            assigned.push((param, true));
+        }
+    }
+
+    for (arg, (param, first)) in positional.into_iter().zip(assigned) {
+        let (Some(arg), Some(name), true) = (arg, &param.name, first) else { continue };
+        if arg.cast::<ast::Ident>().is_some_and(|ident| ident.get() == name) {
+            continue;
+        }
+
+        hints.push(InlayHint {
+            offset: arg.offset(),
+            label: eco_format!("{name}:"),
+            kind: InlayHintKind::Parameter,
+        });
+    }
+}
+
+/// Determine the positional parameters of the function a callee refers to.
+fn callee_params(world: &dyn IdeWorld, callee: &LinkedNode) -> Option<Vec<Param>> {
+    // Local closures have no value, but their definition can be found.
+    if callee.kind() == SyntaxKind::Ident {
+        let name = callee.text();
+        let found = named_items(world, callee.clone(), |item: NamedItem| {
+            if item.name() != name {
+                return None;
+            }
+            Some(match item {
+                NamedItem::Var(ident) | NamedItem::Fn(ident) => Err(ident.span()),
+                _ => Ok(()),
+            })
+        });
+        if let Some(Err(span)) = found {
+            return closure_params(world, span);
+        }
+    }
+
+    let Value::Func(func) = static_value(world, callee)?.0 else { return None };
+    match func.params() {
+        Some(params) => Some(
+            params
+                .iter()
+                .filter(|param| param.positional)
+                .map(|param| Param {
+                    name: Some(param.name.into()),
+                    variadic: param.variadic,
+                })
+                .collect(),
+        ),
+        None => closure_params(world, func.span()),
+    }
+}
+
+/// Determine the positional parameters of the closure defined at the span,
+/// which is either the span of its name, of the identifier it is bound to,
+/// or of its parameter list.
+fn closure_params(world: &dyn IdeWorld, span: Span) -> Option<Vec<Param>> {
+    let source = world.source(span.id()?).ok()?;
+    let node = LinkedNode::new(source.root()).find(span)?;
+    let closure =
+        [Some(&node), node.parent()].into_iter().flatten().find_map(|node| {
+            node.cast::<ast::Closure>().or_else(|| {
+                match node.cast::<ast::LetBinding>()?.init()? {
+                    ast::Expr::Closure(closure) => Some(closure),
+                    _ => None,
+                }
+            })
+        })?;
+
+    let params = closure
+        .params()
+        .children()
+        .filter_map(|param| match param {
+            ast::Param::Pos(ast::Pattern::Normal(ast::Expr::Ident(ident))) => {
+                Some(Param { name: Some(ident.get().clone()), variadic: false })
+            }
+            ast::Param::Pos(_) => Some(Param { name: None, variadic: false }),
+            ast::Param::Spread(spread) => Some(Param {
+                name: spread.sink_ident().map(|ident| ident.get().clone()),
+                variadic: true,
+            }),
+            ast::Param::Named(_) => None,
+        })
+        .collect();
+
+    Some(params)
+}
+
+/// Add a hint with the value of a let binding's initializer, as long as
+/// there are traces left.
+fn value_hint(
+    world: &dyn IdeWorld,
+    node: &LinkedNode,
+    traces: &mut usize,
+    hints: &mut Vec<InlayHint>,
+) {
+    let Some(binding) = node.cast::<ast::LetBinding>() else { return };
+    let Some(init) = binding.init() else { return };
+
+    // The value of literals is already visible and closures and content are
+    // not interesting.
+    if matches!(
+        init,
+        ast::Expr::None(_)
+            | ast::Expr::Auto(_)
+            | ast::Expr::Bool(_)
+            | ast::Expr::Int(_)
+            | ast::Expr::Float(_)
+            | ast::Expr::Numeric(_)
+            | ast::Expr::Str(_)
+            | ast::Expr::Closure(_)
+            | ast::Expr::Content(_)
+            | ast::Expr::Contextual(_)
+    ) {
+        return;
+    }
+
+    let Some(init) = node.find(init.span()) else { return };
+    if *traces == 0 {
+        return;
+    }
+    *traces -= 1;
+
+    let values = analyze_expr(world, &init);
+    let Some((value, _)) = values.first() else { return };
+
+    // A binding that is evaluated multiple times with different values has
+    // no single value to show.
+    if values.iter().any(|(other, _)| other != value)
+        || matches!(value, Value::Content(_) | Value::Func(_) | Value::Module(_))
+    {
+        return;
+    }
+
+    let mut repr = value.repr();
+    if repr.chars().count() > MAX_VALUE_LEN {
+        repr = repr.chars().take(MAX_VALUE_LEN - 1).collect();
+        repr.push('…');
+    }
+
+    hints.push(InlayHint {
+        offset: init.range().end,
+        label: eco_format!("= {repr}"),
+        kind: InlayHintKind::Value,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inlay_hints, InlayHintKind, MAX_VALUE_HINTS};
+    use crate::tests::TestWorld;
+
+    /// The hints of a kind as pairs of offsets and labels.
+    #[track_caller]
+    fn test(text: &str, kind: InlayHintKind) -> Vec<(usize, String)> {
+        let world = TestWorld::new(text);
+        inlay_hints(&world, &world.main, 0..text.len())
+            .into_iter()
+            .filter(|hint| hint.kind == kind)
+            .map(|hint| (hint.offset, hint.label.into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_inlay_hints_native() {
+        let hints = test("#grid(columns: 2, [a], [b])", InlayHintKind::Parameter);
+        assert_eq!(hints, [(18, "children:".into())]);
+    }
+
+    #[test]
+    fn test_inlay_hints_closure() {
+        let text = "#let f(x, y, z: 1) = x; #let x = 1; #f(x, 2)";
+        assert_eq!(test(text, InlayHintKind::Parameter), [(42, "y:".into())]);
+    }
+
+    #[test]
+    fn test_inlay_hints_trailing_content() {
+        assert_eq!(test("#heading[A]", InlayHintKind::Parameter), []);
+    }
+
+    #[test]
+    fn test_inlay_hints_value() {
+        let hints = test("#let x = 1 + 2; #x", InlayHintKind::Value);
+        assert_eq!(hints, [(14, "= 3".into())]);
+        assert_eq!(test("#let x = 1", InlayHintKind::Value), []);
+    }
+
+    #[test]
+    fn test_inlay_hints_value_limit() {
+        let text = "#let x = 1 + 2;".repeat(MAX_VALUE_HINTS + 2);
+        let hints = test(&text, InlayHintKind::Value);
+        assert_eq!(hints.len(), MAX_VALUE_HINTS);
+    }
+}
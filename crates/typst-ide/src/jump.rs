@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::num::NonZeroUsize;
+use std::ops::Range;
 
-use typst::layout::{Frame, FrameItem, Point, Position, Size};
+use typst::layout::{Abs, Frame, FrameItem, Point, Position, Size, Transform};
 use typst::model::{Destination, Document, Url};
 use typst::syntax::{FileId, LinkedNode, Side, Source, Span, SyntaxKind};
 use typst::visualize::Geometry;
@@ -162,6 +164,246 @@ fn find_in_frame(frame: &Frame, span: Span) -> Option<Point> {
     None
 }
 
+/// A rectangular region on a page.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PageRect {
+    /// The page, starting at 1.
+    pub page: NonZeroUsize,
+    /// The top-left corner of the region.
+    pub point: Point,
+    /// The size of the region.
+    pub size: Size,
+}
+
+/// Find the regions in the document that were produced by a range of a
+/// source file.
+///
+/// Glyphs are mapped to the exact part of a text node they were shaped from,
+/// while other items are included if their syntax node overlaps with the
+/// range. Adjacent regions on the same line are merged.
+pub fn rects_from_range(
+    world: &dyn IdeWorld,
+    document: &Document,
+    id: FileId,
+    range: Range<usize>,
+) -> Vec<PageRect> {
+    let mut resolver = Resolver::new(world);
+    let mut rects: Vec<PageRect> = vec![];
+
+    for (i, page) in document.pages.iter().enumerate() {
+        let number = NonZeroUsize::new(i + 1).unwrap();
+        let mut fragments = vec![];
+        collect_fragments(&page.frame, Transform::identity(), &mut fragments);
+
+        for fragment in fragments {
+            let Some((file, source_range)) = resolver.resolve(&fragment) else {
+                continue;
+            };
+            if file != id || !overlaps(&source_range, &range) {
+                continue;
+            }
+
+            if let Some(last) = rects.last_mut() {
+                if last.page == number
+                    && last.point.y == fragment.point.y
+                    && last.size.y == fragment.size.y
+                    && (last.point.x + last.size.x - fragment.point.x).abs()
+                        < Abs::pt(0.01)
+                {
+                    last.size.x = fragment.point.x + fragment.size.x - last.point.x;
+                    continue;
+                }
+            }
+
+            rects.push(PageRect {
+                page: number,
+                point: fragment.point,
+                size: fragment.size,
+            });
+        }
+    }
+
+    rects
+}
+
+/// Find the source ranges that produced the content in a rectangular region
+/// of a frame.
+///
+/// Overlapping and adjacent ranges in the same file are merged. The ranges
+/// are sorted by file and start offset.
+pub fn ranges_from_rect(
+    world: &dyn IdeWorld,
+    frame: &Frame,
+    point: Point,
+    size: Size,
+) -> Vec<(FileId, Range<usize>)> {
+    let mut resolver = Resolver::new(world);
+    let mut fragments = vec![];
+    collect_fragments(frame, Transform::identity(), &mut fragments);
+
+    let mut found: Vec<(FileId, Range<usize>)> = fragments
+        .iter()
+        .filter(|fragment| {
+            fragment.point.x <= point.x + size.x
+                && point.x <= fragment.point.x + fragment.size.x
+                && fragment.point.y <= point.y + size.y
+                && point.y <= fragment.point.y + fragment.size.y
+        })
+        .filter_map(|fragment| resolver.resolve(fragment))
+        .collect();
+
+    found.sort_by_key(|(id, range)| (*id, range.start));
+
+    let mut merged: Vec<(FileId, Range<usize>)> = vec![];
+    for (id, range) in found {
+        match merged.last_mut() {
+            Some((last_id, last)) if *last_id == id && range.start <= last.end => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push((id, range)),
+        }
+    }
+
+    merged
+}
+
+/// A part of a frame that was produced by a single span.
+struct Fragment {
+    /// The top-left corner of the part's bounding box, relative to the page.
+    point: Point,
+    /// The size of the part's bounding box.
+    size: Size,
+    /// The span the part was produced by.
+    span: Span,
+    /// For glyphs, the byte range within the span's text node.
+    glyph: Option<Range<usize>>,
+}
+
+/// Collect the fragments of a frame and its groups.
+///
+/// The transformation maps from the frame's coordinates to the page's.
+fn collect_fragments(frame: &Frame, ts: Transform, fragments: &mut Vec<Fragment>) {
+    for (pos, item) in frame.items() {
+        let mut pos = *pos;
+        match item {
+            FrameItem::Group(group) => {
+                let ts = ts
+                    .pre_concat(Transform::translate(pos.x, pos.y))
+                    .pre_concat(group.transform);
+                collect_fragments(&group.frame, ts, fragments);
+            }
+
+            FrameItem::Text(text) => {
+                for glyph in &text.glyphs {
+                    let width = glyph.x_advance.at(text.size);
+                    let (span, span_offset) = glyph.span;
+                    let start = usize::from(span_offset);
+                    fragments.push(Fragment::new(
+                        Point::new(pos.x, pos.y - text.size),
+                        Size::new(width, text.size),
+                        ts,
+                        span,
+                        Some(start..start + glyph.range().len()),
+                    ));
+                    pos.x += width;
+                }
+            }
+
+            FrameItem::Shape(shape, span) => {
+                let Geometry::Rect(size) = shape.geometry else { continue };
+                fragments.push(Fragment::new(pos, size, ts, *span, None));
+            }
+
+            FrameItem::Image(_, size, span) => {
+                fragments.push(Fragment::new(pos, *size, ts, *span, None));
+            }
+
+            _ => {}
+        }
+    }
+}
+
+impl Fragment {
+    /// Create a fragment from a rectangle in a frame's coordinates and the
+    /// transformation from the frame to the page.
+    fn new(
+        point: Point,
+        size: Size,
+        ts: Transform,
+        span: Span,
+        glyph: Option<Range<usize>>,
+    ) -> Self {
+        let (point, size) = bounding_box(point, size, ts);
+        Self { point, size, span, glyph }
+    }
+}
+
+/// The axis-aligned bounding box of a transformed rectangle.
+fn bounding_box(point: Point, size: Size, ts: Transform) -> (Point, Size) {
+    if ts.is_identity() {
+        return (point, size);
+    }
+
+    let corners = [
+        point,
+        point + Point::with_x(size.x),
+        point + Point::with_y(size.y),
+        point + size.to_point(),
+    ]
+    .map(|corner| corner.transform(ts));
+
+    let min = corners.iter().fold(corners[0], |min, p| min.min(*p));
+    let max = corners.iter().fold(corners[0], |max, p| max.max(*p));
+    (min, (max - min).to_size())
+}
+
+/// Maps spans to source ranges, caching the syntax node lookups.
+struct Resolver<'a> {
+    world: &'a dyn IdeWorld,
+    /// The file, range, and whether the node is text for each span.
+    cache: HashMap<Span, Option<(FileId, Range<usize>, bool)>>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(world: &'a dyn IdeWorld) -> Self {
+        Self { world, cache: HashMap::new() }
+    }
+
+    /// The source range a fragment was produced by.
+    fn resolve(&mut self, fragment: &Fragment) -> Option<(FileId, Range<usize>)> {
+        let world = self.world;
+        let (id, range, is_text) = self
+            .cache
+            .entry(fragment.span)
+            .or_insert_with(|| {
+                let id = fragment.span.id()?;
+                let source = world.source(id).ok()?;
+                let node = source.find(fragment.span)?;
+                Some((id, node.range(), node.kind() == SyntaxKind::Text))
+            })
+            .clone()?;
+
+        match &fragment.glyph {
+            Some(glyph) if is_text => {
+                let start = (range.start + glyph.start).min(range.end);
+                let end = (range.start + glyph.end).min(range.end);
+                Some((id, start..end))
+            }
+            _ => Some((id, range)),
+        }
+    }
+}
+
+/// Whether two ranges overlap. Empty ranges overlap with ranges that contain
+/// or touch them.
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    if a.is_empty() || b.is_empty() {
+        a.start <= b.end && b.start <= a.end
+    } else {
+        a.start < b.end && b.start < a.end
+    }
+}
+
 /// Whether a rectangle with the given size at the given position contains the
 /// click position.
 fn is_in_rect(pos: Point, size: Size, click: Point) -> bool {
@@ -184,9 +426,11 @@ mod tests {
 
     use std::num::NonZeroUsize;
 
-    use typst::layout::{Abs, Point, Position};
+    use typst::layout::{Abs, Point, Position, Size};
 
-    use super::{jump_from_click, jump_from_cursor, Jump};
+    use super::{
+        jump_from_click, jump_from_cursor, ranges_from_rect, rects_from_range, Jump,
+    };
     use crate::tests::TestWorld;
 
     fn point(x: f64, y: f64) -> Point {
@@ -269,4 +513,42 @@ mod tests {
         let s = "#footnote[Hi]";
         test_click(s, point(10.0, 10.0), pos(1, 18.5, 37.1).map(Jump::Position));
     }
+
+    #[test]
+    fn test_rects_from_range() {
+        let s = "*Hello* #box[ABC] World";
+        let world = TestWorld::new(s);
+        let doc = typst::compile(&world).output.unwrap();
+        let rects = rects_from_range(&world, &doc, TestWorld::main_id(), 13..16);
+        assert_eq!(rects.len(), 1);
+
+        let rect = rects[0];
+        let inset = Point::new(Abs::pt(0.5), Abs::pt(0.5));
+        let size = Size::new(rect.size.x - Abs::pt(1.0), rect.size.y - Abs::pt(1.0));
+        let ranges =
+            ranges_from_rect(&world, &doc.pages[0].frame, rect.point + inset, size);
+        assert_eq!(ranges, [(TestWorld::main_id(), 13..16)]);
+    }
+
+    #[test]
+    fn test_rects_from_range_transformed() {
+        let rect = |s: &str, range| {
+            let world = TestWorld::new(s);
+            let doc = typst::compile(&world).output.unwrap();
+            let rects = rects_from_range(&world, &doc, TestWorld::main_id(), range);
+            assert_eq!(rects.len(), 1);
+            (world, doc, rects[0])
+        };
+
+        let (_, _, plain) = rect("ABC", 0..3);
+        let (world, doc, scaled) = rect("#scale(200%, reflow: true)[ABC]", 27..30);
+        assert_approx_eq!(scaled.size.x, 2.0 * plain.size.x);
+        assert_approx_eq!(scaled.size.y, 2.0 * plain.size.y);
+
+        let inset = Point::new(Abs::pt(0.5), Abs::pt(0.5));
+        let size = Size::new(scaled.size.x - Abs::pt(1.0), scaled.size.y - Abs::pt(1.0));
+        let ranges =
+            ranges_from_rect(&world, &doc.pages[0].frame, scaled.point + inset, size);
+        assert_eq!(ranges, [(TestWorld::main_id(), 27..30)]);
+    }
 }
@@ -1,18 +1,36 @@
 //! Capabilities for Typst IDE support.
 
+mod action;
 mod analyze;
 mod complete;
 mod definition;
+mod index;
+mod inlay;
 mod jump;
+mod lint;
 mod matchers;
+mod ranges;
+mod semantic;
 mod tooltip;
 mod utils;
 
+pub use self::action::{code_actions, CodeAction, TextEdit};
 pub use self::analyze::{analyze_expr, analyze_import, analyze_labels};
 pub use self::complete::{autocomplete, Completion, CompletionKind};
 pub use self::definition::{definition, Definition};
-pub use self::jump::{jump_from_click, jump_from_cursor, Jump};
+pub use self::index::{Symbol, SymbolIndex, SymbolKind};
+pub use self::inlay::{inlay_hints, InlayHint, InlayHintKind};
+pub use self::jump::{
+    jump_from_click, jump_from_cursor, ranges_from_rect, rects_from_range, Jump, PageRect,
+};
+pub use self::lint::{lint, Lint, LintConfig};
 pub use self::matchers::{deref_target, named_items, DerefTarget, NamedItem};
+pub use self::ranges::{
+    folding_ranges, selection_ranges, FoldingRange, FoldingRangeKind,
+};
+pub use self::semantic::{
+    semantic_tokens, SemanticModifiers, SemanticToken, SemanticTokenKind,
+};
 pub use self::tooltip::{tooltip, Tooltip};
 
 use ecow::EcoString;
@@ -0,0 +1,592 @@
+use std::collections::HashSet;
+
+use ecow::{eco_format, EcoString};
+use typst::diag::SourceDiagnostic;
+use typst::foundations::{StyleChain, Value};
+use typst::model::{Document, HeadingElem, RefElem};
+use typst::syntax::{ast, LinkedNode, Source, Span, SyntaxKind};
+
+use crate::utils::static_value;
+use crate::{IdeWorld, SymbolIndex};
+
+/// A check for a style problem that the compiler accepts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Lint {
+    /// A `let` binding that is never used.
+    UnusedBinding,
+    /// An imported item that is never used.
+    UnusedImport,
+    /// A `set` rule that is not followed by anything it could apply to.
+    IneffectiveSet,
+    /// A label that is never referenced.
+    UnreferencedLabel,
+    /// A heading that is more than one level deeper than the previous one.
+    SkippedHeadingLevel,
+    /// A figure with an empty caption.
+    EmptyCaption,
+    /// A content block in code that only embeds an element function call,
+    /// like `[#heading[A]]`, which could be written without the `#`.
+    EmbeddedCode,
+}
+
+impl Lint {
+    /// All lints.
+    pub const ALL: &'static [Self] = &[
+        Self::UnusedBinding,
+        Self::UnusedImport,
+        Self::IneffectiveSet,
+        Self::UnreferencedLabel,
+        Self::SkippedHeadingLevel,
+        Self::EmptyCaption,
+        Self::EmbeddedCode,
+    ];
+
+    /// The lint's name in kebab-case.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UnusedBinding => "unused-binding",
+            Self::UnusedImport => "unused-import",
+            Self::IneffectiveSet => "ineffective-set",
+            Self::UnreferencedLabel => "unreferenced-label",
+            Self::SkippedHeadingLevel => "skipped-heading-level",
+            Self::EmptyCaption => "empty-caption",
+            Self::EmbeddedCode => "embedded-code",
+        }
+    }
+
+    /// Find a lint by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+
+    /// Whether the lint is enabled by default.
+    ///
+    /// Labels are often only used for styling or by other tools, so
+    /// unreferenced labels are not reported by default.
+    pub fn default_enabled(self) -> bool {
+        self != Self::UnreferencedLabel
+    }
+}
+
+/// Which lints to run.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LintConfig {
+    enabled: HashSet<Lint>,
+}
+
+impl LintConfig {
+    /// A configuration with all lints enabled.
+    pub fn all() -> Self {
+        Self { enabled: Lint::ALL.iter().copied().collect() }
+    }
+
+    /// A configuration with no lints enabled.
+    pub fn none() -> Self {
+        Self { enabled: HashSet::new() }
+    }
+
+    /// Enable a lint.
+    pub fn enable(&mut self, lint: Lint) {
+        self.enabled.insert(lint);
+    }
+
+    /// Disable a lint.
+    pub fn disable(&mut self, lint: Lint) {
+        self.enabled.remove(&lint);
+    }
+
+    /// Whether a lint is enabled.
+    pub fn is_enabled(&self, lint: Lint) -> bool {
+        self.enabled.contains(&lint)
+    }
+}
+
+impl Default for LintConfig {
+    /// The lints that are [enabled by default](Lint::default_enabled).
+    fn default() -> Self {
+        Self {
+            enabled: Lint::ALL
+                .iter()
+                .copied()
+                .filter(|lint| lint.default_enabled())
+                .collect(),
+        }
+    }
+}
+
+/// Check the files reachable from the main file and the compiled document
+/// for style problems.
+///
+/// Returns a warning for each problem found by an enabled lint. Top-level
+/// bindings and imports in files other than the main file are not reported
+/// as unused because other files may import them.
+pub fn lint(
+    world: &dyn IdeWorld,
+    document: &Document,
+    config: &LintConfig,
+) -> Vec<SourceDiagnostic> {
+    let mut index = SymbolIndex::new();
+    index.update(world);
+
+    let sources: Vec<Source> =
+        index.files().iter().filter_map(|&id| world.source(id).ok()).collect();
+
+    let mut linter = Linter { world, config, diagnostics: vec![] };
+    for source in &sources {
+        linter.source(source, source.id() == world.main());
+    }
+
+    linter.labels(&sources, document);
+    linter.headings(document);
+    linter.diagnostics
+}
+
+/// Runs the lints and collects their warnings.
+struct Linter<'a> {
+    world: &'a dyn IdeWorld,
+    config: &'a LintConfig,
+    diagnostics: Vec<SourceDiagnostic>,
+}
+
+impl Linter<'_> {
+    /// Run the syntactic lints on a source file.
+    fn source(&mut self, source: &Source, main: bool) {
+        let root = LinkedNode::new(source.root());
+        let mut bindings = vec![];
+        let mut uses = HashSet::new();
+        collect_names(&root, &mut bindings, &mut uses);
+
+        for binding in bindings {
+            let exported = binding.top_level && !main;
+            if exported || binding.name.starts_with('_') || uses.contains(&binding.name) {
+                continue;
+            }
+
+            match binding.kind {
+                BindingKind::Let => self.warn(
+                    Lint::UnusedBinding,
+                    binding.span,
+                    eco_format!("unused variable: {}", binding.name),
+                ),
+                BindingKind::Import => self.warn(
+                    Lint::UnusedImport,
+                    binding.span,
+                    eco_format!("unused import: {}", binding.name),
+                ),
+                BindingKind::Other => {}
+            }
+        }
+
+        self.node(&root);
+    }
+
+    /// Run the lints that look at single nodes on a node and its descendants.
+    fn node(&mut self, node: &LinkedNode) {
+        match node.kind() {
+            SyntaxKind::SetRule => self.set_rule(node),
+            SyntaxKind::FuncCall => self.figure(node),
+            SyntaxKind::ContentBlock => self.content_block(node),
+            _ => {}
+        }
+
+        for child in node.children() {
+            self.node(&child);
+        }
+    }
+
+    /// Check that a set rule is followed by something in its block.
+    fn set_rule(&mut self, node: &LinkedNode) {
+        if !matches!(node.parent_kind(), Some(SyntaxKind::Markup | SyntaxKind::Code)) {
+            return;
+        }
+
+        let mut next = node.next_sibling();
+        while let Some(sibling) = &next {
+            if !sibling.kind().is_trivia() && sibling.kind() != SyntaxKind::Semicolon {
+                return;
+            }
+            next = sibling.next_sibling();
+        }
+
+        self.report(
+            Lint::IneffectiveSet,
+            SourceDiagnostic::warning(node.span(), "set rule has no effect")
+                .with_hint("set rules only apply to the rest of the enclosing block"),
+        );
+    }
+
+    /// Check that a figure's caption is not empty.
+    fn figure(&mut self, node: &LinkedNode) {
+        let Some(call) = node.cast::<ast::FuncCall>() else { return };
+        let ast::Expr::Ident(callee) = call.callee() else { return };
+        if callee.as_str() != "figure" {
+            return;
+        }
+
+        for arg in call.args().items() {
+            let ast::Arg::Named(named) = arg else { continue };
+            if named.name().as_str() != "caption" {
+                continue;
+            }
+
+            let empty = match named.expr() {
+                ast::Expr::Content(block) => block
+                    .body()
+                    .to_untyped()
+                    .children()
+                    .all(|child| child.kind().is_trivia()),
+                ast::Expr::Str(string) => string.get().trim().is_empty(),
+                _ => false,
+            };
+
+            if empty {
+                self.warn(
+                    Lint::EmptyCaption,
+                    named.expr().span(),
+                    "figure caption is empty",
+                );
+            }
+        }
+    }
+
+    /// Check for content blocks in code that only embed an element function
+    /// call.
+    fn content_block(&mut self, node: &LinkedNode) {
+        if !in_code(node) {
+            return;
+        }
+
+        let Some(markup) =
+            node.children().find(|child| child.kind() == SyntaxKind::Markup)
+        else {
+            return;
+        };
+
+        let mut children = markup.children().filter(|child| !child.kind().is_trivia());
+        let (Some(hash), Some(expr), None) =
+            (children.next(), children.next(), children.next())
+        else {
+            return;
+        };
+
+        if hash.kind() != SyntaxKind::Hash || expr.kind() != SyntaxKind::FuncCall {
+            return;
+        }
+
+        let Some(call) = expr.cast::<ast::FuncCall>() else { return };
+        let Some(callee) = expr.find(call.callee().span()) else { return };
+        let Some((Value::Func(func), _)) = static_value(self.world, &callee) else {
+            return;
+        };
+
+        if func.element().is_some() {
+            let call = expr.get().clone().into_text();
+            self.report(
+                Lint::EmbeddedCode,
+                SourceDiagnostic::warning(node.span(), "unnecessary content block")
+                    .with_hint(eco_format!(
+                        "try calling the function directly: `{call}`"
+                    )),
+            );
+        }
+    }
+
+    /// Check that all labels attached to markup are referenced somewhere.
+    fn labels(&mut self, sources: &[Source], document: &Document) {
+        let mut defined = vec![];
+        let mut used = HashSet::new();
+        for source in sources {
+            collect_labels(&LinkedNode::new(source.root()), &mut defined, &mut used);
+        }
+
+        // References created by code, e.g. through `ref`, only show up in the
+        // document.
+        for elem in document.introspector.all() {
+            if let Some(reference) = elem.to_packed::<RefElem>() {
+                used.insert(EcoString::from(reference.target.as_str()));
+            }
+        }
+
+        for (name, span) in defined {
+            if !used.contains(&name) {
+                self.warn(
+                    Lint::UnreferencedLabel,
+                    span,
+                    eco_format!("label `<{name}>` is never referenced"),
+                );
+            }
+        }
+    }
+
+    /// Check that no heading levels are skipped.
+    fn headings(&mut self, document: &Document) {
+        let mut previous = 0;
+        for elem in document.introspector.all() {
+            let Some(heading) = elem.to_packed::<HeadingElem>() else { continue };
+            let level = heading.resolve_level(StyleChain::default()).get();
+            let span = heading.span();
+            if level > previous + 1 && span.id().is_some_and(|id| id.package().is_none())
+            {
+                self.report(
+                    Lint::SkippedHeadingLevel,
+                    SourceDiagnostic::warning(
+                        span,
+                        eco_format!("heading level skipped from {previous} to {level}"),
+                    )
+                    .with_hint(eco_format!(
+                        "consider using a level {} heading",
+                        previous + 1
+                    )),
+                );
+            }
+            previous = level;
+        }
+    }
+
+    /// Add a warning for a lint if it is enabled.
+    fn warn(&mut self, lint: Lint, span: Span, message: impl Into<EcoString>) {
+        self.report(lint, SourceDiagnostic::warning(span, message));
+    }
+
+    /// Add a diagnostic for a lint if it is enabled, naming the lint in a
+    /// hint.
+    fn report(&mut self, lint: Lint, diagnostic: SourceDiagnostic) {
+        if self.config.is_enabled(lint) {
+            self.diagnostics.push(diagnostic.with_hint(eco_format!(
+                "this warning is produced by the `{}` lint",
+                lint.name()
+            )));
+        }
+    }
+}
+
+/// A name that is bound in a source file.
+struct Binding {
+    name: EcoString,
+    span: Span,
+    kind: BindingKind,
+    /// Whether the binding is visible to files that import this file.
+    top_level: bool,
+}
+
+/// How a name was bound.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum BindingKind {
+    /// By a `let` binding.
+    Let,
+    /// By an import.
+    Import,
+    /// By a loop or a closure parameter. These are not reported.
+    Other,
+}
+
+/// Collect the names bound in a node and its descendants and the names of
+/// all identifiers that are not bindings.
+fn collect_names(
+    node: &LinkedNode,
+    bindings: &mut Vec<Binding>,
+    uses: &mut HashSet<EcoString>,
+) {
+    let mut idents = vec![];
+    let mut kind = BindingKind::Other;
+    if let Some(binding) = node.cast::<ast::LetBinding>() {
+        idents = binding.kind().bindings();
+        kind = BindingKind::Let;
+    } else if let Some(for_loop) = node.cast::<ast::ForLoop>() {
+        idents = for_loop.pattern().bindings();
+    } else if let Some(closure) = node.cast::<ast::Closure>() {
+        for param in closure.params().children() {
+            match param {
+                ast::Param::Pos(pattern) => idents.extend(pattern.bindings()),
+                ast::Param::Named(named) => idents.push(named.name()),
+                ast::Param::Spread(spread) => idents.extend(spread.sink_ident()),
+            }
+        }
+    } else if let Some(import) = node.cast::<ast::ModuleImport>() {
+        if let Some(ast::Imports::Items(items)) = import.imports() {
+            idents.extend(items.iter().map(ast::ImportItem::bound_name));
+        }
+        idents.extend(import.new_name());
+        kind = BindingKind::Import;
+    } else if matches!(node.kind(), SyntaxKind::Ident | SyntaxKind::MathIdent)
+        && !is_binding(bindings, node.span())
+        && !is_key(node)
+    {
+        uses.insert(node.text().clone());
+    }
+
+    let top_level = node.parent_kind() == Some(SyntaxKind::Markup)
+        && node.parent().and_then(LinkedNode::parent).is_none();
+    bindings.extend(idents.into_iter().map(|ident| Binding {
+        name: ident.get().clone(),
+        span: ident.span(),
+        kind,
+        top_level,
+    }));
+
+    for child in node.children() {
+        collect_names(&child, bindings, uses);
+    }
+}
+
+/// Whether an identifier is one of the bindings collected so far.
+///
+/// Bindings are always collected before their identifiers are visited.
+fn is_binding(bindings: &[Binding], span: Span) -> bool {
+    bindings.iter().rev().any(|binding| binding.span == span)
+}
+
+/// Whether an identifier is not a variable but the name of an argument, a
+/// dictionary key, a field, or a path in an import.
+fn is_key(node: &LinkedNode) -> bool {
+    match node.parent_kind() {
+        Some(SyntaxKind::Named) => node.index() == 0,
+        Some(SyntaxKind::FieldAccess) => node.index() > 0,
+        Some(SyntaxKind::ImportItemPath) => true,
+        _ => false,
+    }
+}
+
+/// Collect the labels attached to markup and the names of all labels that
+/// are referenced or used in code.
+fn collect_labels(
+    node: &LinkedNode,
+    defined: &mut Vec<(EcoString, Span)>,
+    used: &mut HashSet<EcoString>,
+) {
+    match node.kind() {
+        SyntaxKind::Label => {
+            let name: EcoString =
+                node.text().trim_start_matches('<').trim_end_matches('>').into();
+            if node.parent_kind() == Some(SyntaxKind::Markup) {
+                defined.push((name, node.span()));
+            } else {
+                used.insert(name);
+            }
+        }
+        SyntaxKind::Ref => {
+            if let Some(reference) = node.cast::<ast::Ref>() {
+                used.insert(reference.target().into());
+            }
+        }
+        SyntaxKind::FuncCall => {
+            // Labels created from strings, e.g. `label("intro")`.
+            if let Some(call) = node.cast::<ast::FuncCall>() {
+                if let ast::Expr::Ident(callee) = call.callee() {
+                    if callee.as_str() == "label" {
+                        for arg in call.args().items() {
+                            if let ast::Arg::Pos(ast::Expr::Str(string)) = arg {
+                                used.insert(string.get());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect_labels(&child, defined, used);
+    }
+}
+
+/// Whether a content block is used as an expression in code, rather than as
+/// markup or as a trailing content argument.
+fn in_code(node: &LinkedNode) -> bool {
+    let Some(parent) = node.parent() else { return false };
+    match parent.kind() {
+        SyntaxKind::Markup => false,
+        SyntaxKind::Args => parent
+            .children()
+            .find(|child| child.kind() == SyntaxKind::RightParen)
+            .is_some_and(|paren| node.offset() < paren.offset()),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint, Lint, LintConfig};
+    use crate::tests::TestWorld;
+
+    /// The messages of the warnings produced by a single lint.
+    #[track_caller]
+    fn test(text: &str, only: Lint) -> Vec<String> {
+        let world = TestWorld::new(text);
+        let document = typst::compile(&world).output.unwrap();
+        let mut config = LintConfig::none();
+        config.enable(only);
+        lint(&world, &document, &config)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message.into())
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_unused_binding() {
+        let text = "#let x = 1\n#let y = 2\n#let _z = 3\n#y";
+        assert_eq!(test(text, Lint::UnusedBinding), ["unused variable: x"]);
+        assert!(test("#let f(x) = 1; #f(2)", Lint::UnusedBinding).is_empty());
+    }
+
+    #[test]
+    fn test_lint_unused_import() {
+        let world = TestWorld::new("#import \"lib.typ\": a, b\n#a")
+            .with_source("lib.typ", "#let a = 1\n#let b = 2\n#let c = 3");
+        let document = typst::compile(&world).output.unwrap();
+        let mut config = LintConfig::none();
+        config.enable(Lint::UnusedImport);
+        config.enable(Lint::UnusedBinding);
+        let messages: Vec<_> = lint(&world, &document, &config)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(messages, ["unused import: b"]);
+    }
+
+    #[test]
+    fn test_lint_ineffective_set() {
+        let text = "#{ set text(red) }\n#set text(blue)\nA";
+        assert_eq!(test(text, Lint::IneffectiveSet), ["set rule has no effect"]);
+    }
+
+    #[test]
+    fn test_lint_ineffective_set_trailing_space() {
+        let text = "#[#set text(red) ]\n#set text(blue) // end\n";
+        assert_eq!(
+            test(text, Lint::IneffectiveSet),
+            ["set rule has no effect", "set rule has no effect"]
+        );
+    }
+
+    #[test]
+    fn test_lint_unreferenced_label() {
+        let text = "= A <a>\n= B <b>\n@a";
+        assert_eq!(
+            test(text, Lint::UnreferencedLabel),
+            ["label `<b>` is never referenced"]
+        );
+    }
+
+    #[test]
+    fn test_lint_skipped_heading_level() {
+        let text = "= A\n=== B\n== C";
+        assert_eq!(
+            test(text, Lint::SkippedHeadingLevel),
+            ["heading level skipped from 1 to 3"]
+        );
+    }
+
+    #[test]
+    fn test_lint_empty_caption() {
+        let text = "#figure([A], caption: [ ])";
+        assert_eq!(test(text, Lint::EmptyCaption), ["figure caption is empty"]);
+    }
+
+    #[test]
+    fn test_lint_embedded_code() {
+        let text = "#let a = ([#heading[A]], [#1])";
+        assert_eq!(test(text, Lint::EmbeddedCode), ["unnecessary content block"]);
+    }
+}
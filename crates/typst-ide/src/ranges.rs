@@ -0,0 +1,200 @@
+use std::cmp::Reverse;
+use std::ops::Range;
+
+use typst::syntax::{ast, LinkedNode, Side, Source, SyntaxKind};
+
+/// A range of a source file that can be folded.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FoldingRange {
+    /// The byte range that is folded.
+    pub range: Range<usize>,
+    /// What is folded.
+    pub kind: FoldingRangeKind,
+}
+
+/// What a folding range contains.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FoldingRangeKind {
+    /// A heading and the content up to the next heading of the same or a
+    /// higher level.
+    Section,
+    /// A code or content block.
+    Block,
+    /// A raw block.
+    Raw,
+    /// A block comment or a run of line comments.
+    Comment,
+}
+
+/// Find the ranges of a source file that can be folded.
+///
+/// Only ranges that span multiple lines are returned. They are ordered by
+/// their start offset, with outer ranges before inner ones.
+pub fn folding_ranges(source: &Source) -> Vec<FoldingRange> {
+    let mut ranges = vec![];
+    collect(source, &LinkedNode::new(source.root()), &mut ranges);
+    ranges.retain(|folding| source.text()[folding.range.clone()].contains('\n'));
+    ranges.sort_by_key(|folding| (folding.range.start, Reverse(folding.range.end)));
+    ranges
+}
+
+/// Collect the folding ranges of a node and its descendants.
+fn collect(source: &Source, node: &LinkedNode, ranges: &mut Vec<FoldingRange>) {
+    let kind = match node.kind() {
+        SyntaxKind::CodeBlock | SyntaxKind::ContentBlock => Some(FoldingRangeKind::Block),
+        SyntaxKind::Raw if node.cast::<ast::Raw>().is_some_and(|raw| raw.block()) => {
+            Some(FoldingRangeKind::Raw)
+        }
+        SyntaxKind::BlockComment => Some(FoldingRangeKind::Comment),
+        _ => None,
+    };
+
+    if let Some(kind) = kind {
+        ranges.push(FoldingRange { range: node.range(), kind });
+    }
+
+    if node.kind() == SyntaxKind::Markup {
+        sections(source, node, ranges);
+    }
+
+    comment_runs(node, ranges);
+
+    for child in node.children() {
+        collect(source, &child, ranges);
+    }
+}
+
+/// Collect the sections started by the headings in a markup node.
+fn sections(source: &Source, markup: &LinkedNode, ranges: &mut Vec<FoldingRange>) {
+    let children: Vec<_> = markup.children().collect();
+    for (i, child) in children.iter().enumerate() {
+        let Some(heading) = child.cast::<ast::Heading>() else { continue };
+        let depth = heading.depth();
+
+        // The section ends before the next heading of the same or a higher
+        // level or at the end of the markup.
+        let end = children[i + 1..]
+            .iter()
+            .find(|next| next.cast::<ast::Heading>().is_some_and(|h| h.depth() <= depth))
+            .map_or(markup.range().end, LinkedNode::offset);
+
+        let text = &source.text()[child.offset()..end];
+        let end = child.offset() + text.trim_end().len();
+        ranges.push(FoldingRange {
+            range: child.offset()..end,
+            kind: FoldingRangeKind::Section,
+        });
+    }
+}
+
+/// Collect runs of line comments among the children of a node, which are
+/// only separated by single line breaks.
+fn comment_runs(node: &LinkedNode, ranges: &mut Vec<FoldingRange>) {
+    let mut run: Option<Range<usize>> = None;
+    let mut count = 0;
+    let mut flush = |run: &mut Option<Range<usize>>, count: &mut usize| {
+        if let Some(range) = run.take().filter(|_| *count > 1) {
+            ranges.push(FoldingRange { range, kind: FoldingRangeKind::Comment });
+        }
+        *count = 0;
+    };
+
+    for child in node.children() {
+        match child.kind() {
+            SyntaxKind::LineComment => {
+                let range = child.range();
+                match &mut run {
+                    Some(run) => run.end = range.end,
+                    None => run = Some(range),
+                }
+                count += 1;
+            }
+            SyntaxKind::Space if child.text().matches('\n').count() <= 1 => {}
+            _ => flush(&mut run, &mut count),
+        }
+    }
+
+    flush(&mut run, &mut count);
+}
+
+/// Find the ranges for expanding a selection around the cursor, from the
+/// innermost to the outermost.
+///
+/// The ranges are those of the syntax nodes containing the cursor, skipping
+/// ranges that are equal to the previous one.
+pub fn selection_ranges(source: &Source, cursor: usize) -> Vec<Range<usize>> {
+    let root = LinkedNode::new(source.root());
+
+    // Prefer a leaf that is not whitespace if the cursor is at a boundary.
+    let leaf = root
+        .leaf_at(cursor, Side::After)
+        .filter(|leaf| leaf.kind() != SyntaxKind::Space)
+        .or_else(|| root.leaf_at(cursor, Side::Before));
+
+    let mut ranges: Vec<Range<usize>> = vec![];
+    let mut node = leaf;
+    while let Some(current) = node {
+        let range = current.range();
+        if !range.is_empty() && ranges.last() != Some(&range) {
+            ranges.push(range);
+        }
+        node = current.parent().cloned();
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use typst::syntax::Source;
+
+    use super::{folding_ranges, selection_ranges, FoldingRangeKind};
+
+    #[track_caller]
+    fn test_folding(text: &str) -> Vec<(&str, FoldingRangeKind)> {
+        let source = Source::detached(text);
+        folding_ranges(&source)
+            .into_iter()
+            .map(|folding| (&text[folding.range], folding.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_folding_sections() {
+        let text = "= A\nIntro\n== B\nMore\n= C\nEnd\n";
+        assert_eq!(
+            test_folding(text),
+            [
+                ("= A\nIntro\n== B\nMore", FoldingRangeKind::Section),
+                ("== B\nMore", FoldingRangeKind::Section),
+                ("= C\nEnd", FoldingRangeKind::Section),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_folding_blocks_and_comments() {
+        let text = "// a\n// b\n#let f() = {\n  1\n}\n#[x]\n```\nraw\n```";
+        assert_eq!(
+            test_folding(text),
+            [
+                ("// a\n// b", FoldingRangeKind::Comment),
+                ("{\n  1\n}", FoldingRangeKind::Block),
+                ("```\nraw\n```", FoldingRangeKind::Raw),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selection_ranges() {
+        let text = "#f(a, b + c)";
+        let source = Source::detached(text);
+        let ranges: Vec<&str> = selection_ranges(&source, 6)
+            .into_iter()
+            .map(|range: Range<usize>| &text[range])
+            .collect();
+        assert_eq!(ranges, ["b", "b + c", "(a, b + c)", "f(a, b + c)", "#f(a, b + c)"]);
+    }
+}
@@ -0,0 +1,599 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use ecow::EcoString;
+use typst::foundations::Value;
+use typst::syntax::{
+    ast, highlight, LinkedNode, Source, Span, SyntaxKind, SyntaxNode, Tag,
+};
+
+use crate::utils::{globals, static_value};
+use crate::{named_items, IdeWorld, NamedItem};
+
+/// A classified range of a source file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SemanticToken {
+    /// The byte range of the token in the source.
+    pub range: Range<usize>,
+    /// What the token is.
+    pub kind: SemanticTokenKind,
+    /// Additional properties of the token.
+    pub modifiers: SemanticModifiers,
+}
+
+/// The kind of a semantic token.
+///
+/// Most kinds correspond to a highlighting [`Tag`], but identifiers are
+/// classified by what they resolve to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SemanticTokenKind {
+    /// A line or block comment.
+    Comment,
+    /// Punctuation in code.
+    Punctuation,
+    /// An escape sequence or shorthand.
+    Escape,
+    /// Strong markup.
+    Strong,
+    /// Emphasized markup.
+    Emph,
+    /// A hyperlink.
+    Link,
+    /// Raw text.
+    Raw,
+    /// A label.
+    Label,
+    /// A reference to a label.
+    Ref,
+    /// A section heading.
+    Heading,
+    /// A marker of a list, enumeration, or term list.
+    ListMarker,
+    /// A term in a term list.
+    ListTerm,
+    /// The delimiters of an equation.
+    MathDelimiter,
+    /// An operator with special meaning in an equation.
+    MathOperator,
+    /// A keyword.
+    Keyword,
+    /// An operator in code.
+    Operator,
+    /// A numeric literal.
+    Number,
+    /// A string literal.
+    String,
+    /// A variable that holds no function, type, or module.
+    Variable,
+    /// A parameter of a closure or a named argument.
+    Parameter,
+    /// A function that is not an element.
+    Function,
+    /// A method called on a value.
+    Method,
+    /// An element function, e.g. `heading`.
+    Element,
+    /// A type, e.g. `int`.
+    Type,
+    /// A module, e.g. `calc`.
+    Module,
+    /// A field of a value or a key in a dictionary.
+    Field,
+    /// A syntax error.
+    Error,
+}
+
+impl SemanticTokenKind {
+    /// The list of all kinds, in the same order as they are defined.
+    ///
+    /// Can be used as the counter-part to `kind as usize`, e.g. to build a
+    /// token legend.
+    pub const LIST: &'static [Self] = &[
+        Self::Comment,
+        Self::Punctuation,
+        Self::Escape,
+        Self::Strong,
+        Self::Emph,
+        Self::Link,
+        Self::Raw,
+        Self::Label,
+        Self::Ref,
+        Self::Heading,
+        Self::ListMarker,
+        Self::ListTerm,
+        Self::MathDelimiter,
+        Self::MathOperator,
+        Self::Keyword,
+        Self::Operator,
+        Self::Number,
+        Self::String,
+        Self::Variable,
+        Self::Parameter,
+        Self::Function,
+        Self::Method,
+        Self::Element,
+        Self::Type,
+        Self::Module,
+        Self::Field,
+        Self::Error,
+    ];
+
+    /// The kind for a purely syntactic highlighting tag.
+    fn from_tag(tag: Tag) -> Self {
+        match tag {
+            Tag::Comment => Self::Comment,
+            Tag::Punctuation => Self::Punctuation,
+            Tag::Escape => Self::Escape,
+            Tag::Strong => Self::Strong,
+            Tag::Emph => Self::Emph,
+            Tag::Link => Self::Link,
+            Tag::Raw => Self::Raw,
+            Tag::Label => Self::Label,
+            Tag::Ref => Self::Ref,
+            Tag::Heading => Self::Heading,
+            Tag::ListMarker => Self::ListMarker,
+            Tag::ListTerm => Self::ListTerm,
+            Tag::MathDelimiter => Self::MathDelimiter,
+            Tag::MathOperator => Self::MathOperator,
+            Tag::Keyword => Self::Keyword,
+            Tag::Operator => Self::Operator,
+            Tag::Number => Self::Number,
+            Tag::String => Self::String,
+            Tag::Function => Self::Function,
+            Tag::Interpolated => Self::Variable,
+            Tag::Error => Self::Error,
+        }
+    }
+
+    /// The kind for an identifier that resolves to the given value.
+    fn from_value(value: &Value) -> Self {
+        match value {
+            Value::Func(func) if func.element().is_some() => Self::Element,
+            Value::Func(_) => Self::Function,
+            Value::Type(_) => Self::Type,
+            Value::Module(_) => Self::Module,
+            _ => Self::Variable,
+        }
+    }
+}
+
+/// Additional properties of a semantic token.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SemanticModifiers {
+    /// The token is the place where a name is bound.
+    pub definition: bool,
+    /// The token refers to an item from the standard library.
+    pub library: bool,
+    /// The token is a local binding that shadows a global or another local
+    /// binding with the same name.
+    pub shadows: bool,
+}
+
+impl SemanticModifiers {
+    /// The modifiers as a bit set, in the order in which they are defined.
+    pub fn bits(self) -> u32 {
+        u32::from(self.definition)
+            | u32::from(self.library) << 1
+            | u32::from(self.shadows) << 2
+    }
+}
+
+/// Classify all tokens of a source file.
+///
+/// Syntactic tokens are classified like [`highlight`] does. Identifiers are
+/// resolved through the local bindings in scope and the standard library, so
+/// that e.g. element functions, parameters, and shadowed names can be told
+/// apart. This is purely syntactic and doesn't require a compilation, so
+/// fields whose values can't be determined statically are classified as
+/// fields.
+///
+/// The tokens are ordered by their position and don't overlap. Whitespace
+/// and plain markup text produce no tokens.
+pub fn semantic_tokens(world: &dyn IdeWorld, source: &Source) -> Vec<SemanticToken> {
+    let mut tokens = vec![];
+    let bound = Bound::new(source.root());
+    let root = LinkedNode::new(source.root());
+    collect(world, source, &bound, &root, None, &mut tokens);
+    tokens
+}
+
+/// Collect the tokens of a node, which inherits the tag of its ancestors.
+fn collect(
+    world: &dyn IdeWorld,
+    source: &Source,
+    bound: &Bound,
+    node: &LinkedNode,
+    inherited: Option<Tag>,
+    tokens: &mut Vec<SemanticToken>,
+) {
+    let tag = highlight(node).or(inherited);
+    if node.get().children().len() > 0 {
+        for child in node.children() {
+            collect(world, source, bound, &child, tag, tokens);
+        }
+        return;
+    }
+
+    if node.kind() == SyntaxKind::Space || node.is_empty() {
+        return;
+    }
+
+    let classified = match node.kind() {
+        SyntaxKind::Ident | SyntaxKind::MathIdent => {
+            Some(classify_ident(world, source, bound, node, tag))
+        }
+        // An embedded expression's hash is colored like the identifier it
+        // introduces.
+        SyntaxKind::Hash => match node
+            .next_sibling()
+            .and_then(|next| next.leftmost_leaf())
+        {
+            Some(ident) if ident.kind() == SyntaxKind::Ident => {
+                let tag = highlight(&ident);
+                let (kind, _) = classify_ident(world, source, bound, &ident, tag);
+                Some((kind, SemanticModifiers::default()))
+            }
+            _ => tag.map(|tag| (SemanticTokenKind::from_tag(tag), Default::default())),
+        },
+        _ => tag.map(|tag| (SemanticTokenKind::from_tag(tag), Default::default())),
+    };
+
+    if let Some((kind, modifiers)) = classified {
+        tokens.push(SemanticToken { range: node.range(), kind, modifiers });
+    }
+}
+
+/// Classify an identifier.
+fn classify_ident(
+    world: &dyn IdeWorld,
+    source: &Source,
+    bound: &Bound,
+    node: &LinkedNode,
+    tag: Option<Tag>,
+) -> (SemanticTokenKind, SemanticModifiers) {
+    let name = node.text().as_str();
+    let callee = tag == Some(Tag::Function);
+    let parent = node.parent();
+    let mut modifiers = SemanticModifiers::default();
+
+    // The name of a named argument or dictionary key.
+    if let Some(parent) = parent.filter(|parent| parent.kind() == SyntaxKind::Named) {
+        if node.index() == 0 && parent.parent_kind() != Some(SyntaxKind::Params) {
+            let kind = match parent.parent_kind() {
+                Some(SyntaxKind::Args) => SemanticTokenKind::Parameter,
+                _ => SemanticTokenKind::Field,
+            };
+            return (kind, modifiers);
+        }
+    }
+
+    // A field access, which is resolved through its target.
+    if let Some(parent) = parent
+        .filter(|parent| parent.kind() == SyntaxKind::FieldAccess && node.index() > 0)
+    {
+        if let Some((value, library)) = static_value(world, parent) {
+            modifiers.library = library;
+            return (SemanticTokenKind::from_value(&value), modifiers);
+        }
+
+        // Analyzing would require a compilation, so methods and fields are
+        // only detected syntactically.
+        let kind =
+            if callee { SemanticTokenKind::Method } else { SemanticTokenKind::Field };
+        return (kind, modifiers);
+    }
+
+    // Only search the scopes for names that may be bound locally at all.
+    let local = if bound.may_bind(name) {
+        resolve_local(world, source, node, name)
+    } else {
+        Local { kind: None, shadows: false }
+    };
+    let global = globals(world, node).get(name);
+
+    if let Some(kind) = binding_kind(node) {
+        modifiers.definition = true;
+        modifiers.shadows = local.shadows || local.kind.is_some() || global.is_some();
+        return (kind, modifiers);
+    }
+
+    modifiers.shadows = local.shadows || (local.kind.is_some() && global.is_some());
+    match (local.kind, global) {
+        (Some(SemanticTokenKind::Variable), _) if callee => {
+            (SemanticTokenKind::Function, modifiers)
+        }
+        (Some(kind), _) => (kind, modifiers),
+        (None, Some(value)) => {
+            modifiers.library = true;
+            (SemanticTokenKind::from_value(value), modifiers)
+        }
+        (None, None) if callee => (SemanticTokenKind::Function, modifiers),
+        (None, None) => (SemanticTokenKind::Variable, modifiers),
+    }
+}
+
+/// Determine the kind of binding if the identifier is bound at its position.
+fn binding_kind(node: &LinkedNode) -> Option<SemanticTokenKind> {
+    let span = node.span();
+    let binds = |idents: Vec<ast::Ident>| idents.iter().any(|ident| ident.span() == span);
+
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        if let Some(closure) = parent.cast::<ast::Closure>() {
+            if closure.name().is_some_and(|name| name.span() == span) {
+                return Some(SemanticTokenKind::Function);
+            }
+            if closure.params().children().any(|param| binds(param_bindings(param))) {
+                return Some(SemanticTokenKind::Parameter);
+            }
+        } else if let Some(binding) = parent.cast::<ast::LetBinding>() {
+            if binds(binding.kind().bindings()) {
+                return Some(match binding.init() {
+                    Some(ast::Expr::Closure(_)) => SemanticTokenKind::Function,
+                    _ => SemanticTokenKind::Variable,
+                });
+            }
+        } else if let Some(for_loop) = parent.cast::<ast::ForLoop>() {
+            if binds(for_loop.pattern().bindings()) {
+                return Some(SemanticTokenKind::Variable);
+            }
+        }
+        ancestor = parent.parent();
+    }
+
+    None
+}
+
+/// The identifiers bound by a closure parameter.
+fn param_bindings<'a>(param: ast::Param<'a>) -> Vec<ast::Ident<'a>> {
+    match param {
+        ast::Param::Pos(pattern) => pattern.bindings(),
+        ast::Param::Named(named) => vec![named.name()],
+        ast::Param::Spread(spread) => spread.sink_ident().into_iter().collect(),
+    }
+}
+
+/// The names that are bound anywhere in a source file.
+///
+/// Collected once per request so that identifiers which are never bound
+/// locally don't need to search the scopes.
+struct Bound {
+    /// The bound names.
+    names: HashSet<EcoString>,
+    /// Whether the file contains bindings with names that aren't known
+    /// syntactically, i.e. wildcard imports or module imports without a new
+    /// name.
+    unknown: bool,
+}
+
+impl Bound {
+    /// Collect the bound names of a syntax tree.
+    fn new(root: &SyntaxNode) -> Self {
+        let mut bound = Self { names: HashSet::new(), unknown: false };
+        bound.visit(root);
+        bound
+    }
+
+    /// Whether an identifier with the given name may refer to a local
+    /// binding.
+    fn may_bind(&self, name: &str) -> bool {
+        self.unknown || self.names.contains(name)
+    }
+
+    /// Collect the names bound by a node and its descendants.
+    fn visit(&mut self, node: &SyntaxNode) {
+        let mut insert = |idents: Vec<ast::Ident>| {
+            self.names.extend(idents.into_iter().map(|ident| ident.get().clone()));
+        };
+
+        if let Some(closure) = node.cast::<ast::Closure>() {
+            insert(closure.name().into_iter().collect());
+            for param in closure.params().children() {
+                insert(param_bindings(param));
+            }
+        } else if let Some(binding) = node.cast::<ast::LetBinding>() {
+            insert(binding.kind().bindings());
+        } else if let Some(for_loop) = node.cast::<ast::ForLoop>() {
+            insert(for_loop.pattern().bindings());
+        } else if let Some(import) = node.cast::<ast::ModuleImport>() {
+            insert(import.new_name().into_iter().collect());
+            match import.imports() {
+                Some(ast::Imports::Items(items)) => {
+                    insert(items.iter().map(ast::ImportItem::bound_name).collect());
+                }
+                Some(ast::Imports::Wildcard) => self.unknown = true,
+                None if import.new_name().is_none() => self.unknown = true,
+                None => {}
+            }
+        }
+
+        for child in node.children() {
+            self.visit(child);
+        }
+    }
+}
+
+/// The result of resolving an identifier in the local scopes.
+struct Local {
+    /// The kind of the innermost binding, if any.
+    kind: Option<SemanticTokenKind>,
+    /// Whether the innermost binding shadows another local binding.
+    shadows: bool,
+}
+
+/// Resolve an identifier through the local bindings in scope, i.e. let
+/// bindings, imports, loop variables, and closure parameters.
+fn resolve_local(
+    world: &dyn IdeWorld,
+    source: &Source,
+    node: &LinkedNode,
+    name: &str,
+) -> Local {
+    let span = node.span();
+
+    // Find the two innermost named items, skipping the identifier itself if
+    // it is a binding.
+    let mut items = vec![];
+    named_items(world, node.clone(), |item: NamedItem| {
+        if item.name().as_str() == name && item.span() != span {
+            items.push((item_kind(&item), item.span()));
+        }
+        (items.len() >= 2).then_some(())
+    });
+
+    // Find the innermost closure with a parameter of this name. It takes
+    // precedence over named items outside of it.
+    let closure = param_closure(node, name);
+    let inside = |closure: &LinkedNode, item_span: Span| {
+        source.range(item_span).is_some_and(|range| {
+            closure.range().start <= range.start && range.end <= closure.range().end
+        })
+    };
+
+    let param = closure.as_ref().is_some_and(|closure| {
+        items
+            .first()
+            .map_or(true, |&(_, item_span)| !inside(closure, item_span))
+    });
+
+    if param {
+        Local {
+            kind: Some(SemanticTokenKind::Parameter),
+            shadows: !items.is_empty() || param_is_shadowing(node, name),
+        }
+    } else if let Some(&(kind, _)) = items.first() {
+        Local {
+            kind: Some(kind),
+            shadows: items.len() > 1 || closure.is_some(),
+        }
+    } else {
+        Local { kind: None, shadows: false }
+    }
+}
+
+/// Find the innermost closure whose body can refer to a parameter with the
+/// given name.
+fn param_closure<'a>(node: &LinkedNode<'a>, name: &str) -> Option<LinkedNode<'a>> {
+    let mut ancestor = node.parent().cloned();
+    while let Some(parent) = ancestor {
+        if let Some(closure) = parent.cast::<ast::Closure>() {
+            // Default values of parameters are evaluated outside of the
+            // closure.
+            let in_params = parent
+                .children()
+                .find(|child| child.kind() == SyntaxKind::Params)
+                .is_some_and(|params| params.range().contains(&node.offset()));
+            let bound = closure.params().children().any(|param| {
+                param_bindings(param).iter().any(|ident| ident.as_str() == name)
+            });
+            if bound && !in_params {
+                return Some(parent);
+            }
+        }
+        ancestor = parent.parent().cloned();
+    }
+    None
+}
+
+/// Whether a closure parameter with the given name is itself inside of
+/// another closure with a parameter of the same name.
+fn param_is_shadowing(node: &LinkedNode, name: &str) -> bool {
+    param_closure(node, name)
+        .and_then(|closure| param_closure(&closure, name))
+        .is_some()
+}
+
+/// The token kind for a named item.
+fn item_kind(item: &NamedItem) -> SemanticTokenKind {
+    match item {
+        NamedItem::Var(_) => SemanticTokenKind::Variable,
+        NamedItem::Fn(_) => SemanticTokenKind::Function,
+        NamedItem::Module(..) => SemanticTokenKind::Module,
+        NamedItem::Import(_, _, Some(value)) => SemanticTokenKind::from_value(value),
+        NamedItem::Import(_, _, None) => SemanticTokenKind::Variable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{semantic_tokens, SemanticTokenKind};
+    use crate::tests::TestWorld;
+
+    /// Find the kind and the `definition`, `library`, and `shadows` modifiers
+    /// of the token with the given text.
+    #[track_caller]
+    fn test(text: &str, token: &str, nth: usize) -> (SemanticTokenKind, [bool; 3]) {
+        let world = TestWorld::new(text);
+        let found = semantic_tokens(&world, &world.main)
+            .into_iter()
+            .filter(|found| &text[found.range.clone()] == token)
+            .nth(nth)
+            .expect("token not found");
+        let m = found.modifiers;
+        (found.kind, [m.definition, m.library, m.shadows])
+    }
+
+    #[test]
+    fn test_semantic_tokens_syntax() {
+        assert_eq!(test("// hi", "// hi", 0).0, SemanticTokenKind::Comment);
+        assert_eq!(test("#let x = 1", "let", 0).0, SemanticTokenKind::Keyword);
+        assert_eq!(test("#let x = 1", "1", 0).0, SemanticTokenKind::Number);
+    }
+
+    #[test]
+    fn test_semantic_tokens_library() {
+        let (kind, modifiers) = test("#heading[A]", "heading", 0);
+        assert_eq!(kind, SemanticTokenKind::Element);
+        assert_eq!(modifiers, [false, true, false]);
+        assert_eq!(test("#calc.pow(2, 3)", "calc", 0).0, SemanticTokenKind::Module);
+        assert_eq!(test("#calc.pow(2, 3)", "pow", 0).0, SemanticTokenKind::Function);
+        assert_eq!(test("#int(\"1\")", "int", 0).0, SemanticTokenKind::Type);
+    }
+
+    #[test]
+    fn test_semantic_tokens_locals() {
+        let text = "#let f(x, y: 1) = x + y; #f(1, y: 2)";
+        assert_eq!(
+            test(text, "f", 0),
+            (SemanticTokenKind::Function, [true, false, false])
+        );
+        assert_eq!(
+            test(text, "x", 0),
+            (SemanticTokenKind::Parameter, [true, false, false])
+        );
+        assert_eq!(
+            test(text, "x", 1),
+            (SemanticTokenKind::Parameter, [false, false, false])
+        );
+        assert_eq!(test(text, "y", 2).0, SemanticTokenKind::Parameter);
+        assert_eq!(test(text, "f", 1).0, SemanticTokenKind::Function);
+    }
+
+    #[test]
+    fn test_semantic_tokens_shadowing() {
+        let text = "#let text = 1; #text";
+        assert_eq!(
+            test(text, "text", 0),
+            (SemanticTokenKind::Variable, [true, false, true])
+        );
+        assert_eq!(
+            test(text, "text", 1),
+            (SemanticTokenKind::Variable, [false, false, true])
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_fields() {
+        let text = "#let d = (a: 1); #d.a; #d.len()";
+        assert_eq!(test(text, "a", 1).0, SemanticTokenKind::Field);
+        assert_eq!(test(text, "len", 0).0, SemanticTokenKind::Method);
+    }
+
+    #[test]
+    fn test_semantic_tokens_imports() {
+        let text = "#import \"other.typ\": text; #text";
+        assert_eq!(test(text, "text", 1).0, SemanticTokenKind::Variable);
+        let text = "#import \"other.typ\": *; #text";
+        assert_eq!(test(text, "text", 0).0, SemanticTokenKind::Element);
+    }
+}
@@ -9,7 +9,7 @@ use typst::introspection::Introspector;
 use typst::syntax::{LinkedNode, SyntaxKind};
 use typst::text::{FontInfo, FontStyle};
 
-use crate::IdeWorld;
+use crate::{named_items, IdeWorld, NamedItem};
 
 /// Create a temporary engine and run a task on it.
 pub fn with_engine<F, T>(world: &dyn IdeWorld, f: F) -> T
@@ -127,6 +127,33 @@ pub fn globals<'a>(world: &'a dyn IdeWorld, leaf: &LinkedNode) -> &'a Scope {
     }
 }
 
+/// Try to determine the value of an identifier or field access without
+/// evaluating anything. Also returns whether the value comes from the
+/// standard library.
+pub fn static_value(world: &dyn IdeWorld, node: &LinkedNode) -> Option<(Value, bool)> {
+    match node.kind() {
+        SyntaxKind::Ident | SyntaxKind::MathIdent => {
+            let name = node.text();
+            let mut local = None;
+            let found = named_items(world, node.clone(), |item: NamedItem| {
+                (item.name() == name).then(|| local = item.value())
+            });
+            match found {
+                Some(()) => local.map(|value| (value, false)),
+                None => globals(world, node).get(name).map(|value| (value.clone(), true)),
+            }
+        }
+        SyntaxKind::FieldAccess => {
+            let target = node.children().next()?;
+            let field = node.children().last()?;
+            let (value, library) = static_value(world, &target)?;
+            let value = value.scope()?.get(field.text())?.clone();
+            Some((value, library))
+        }
+        _ => None,
+    }
+}
+
 /// Checks whether the given value or any of its constituent parts satisfy the
 /// predicate.
 pub fn check_value_recursively(
@@ -189,3 +216,43 @@ where
         ControlFlow::Continue(())
     }
 }
+
+/// The Levenshtein distance between two strings, in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The candidates that are most likely meant instead of a misspelled name,
+/// ordered from closest to farthest. Comparison is case-insensitive.
+pub fn closest_names<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    const MAX: usize = 3;
+
+    let lower = name.to_lowercase();
+    let len = lower.chars().count();
+    let threshold = len.div_ceil(3).max(1);
+
+    let mut found: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|&candidate| candidate != name)
+        .map(|candidate| (edit_distance(&lower, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= threshold && distance < len)
+        .collect();
+
+    found.sort();
+    found.dedup_by_key(|(_, candidate)| *candidate);
+    found.into_iter().take(MAX).map(|(_, candidate)| candidate).collect()
+}
@@ -12,6 +12,7 @@ use typst_library::layout::{
 };
 use typst_library::math::{EquationElem, MathSize};
 use typst_library::model::{Destination, LinkElem};
+use typst_library::pdf::{Layer, LayerElem};
 use typst_library::text::{Font, Glyph, Lang, Region, TextElem, TextItem};
 use typst_library::visualize::Paint;
 use typst_syntax::Span;
@@ -247,6 +248,7 @@ pub struct GlyphFragment {
     pub span: Span,
     pub dests: SmallVec<[Destination; 1]>,
     pub hidden: bool,
+    pub layer: Option<Layer>,
     pub limits: Limits,
     pub extended_shape: bool,
 }
@@ -304,6 +306,7 @@ impl GlyphFragment {
             span,
             dests: LinkElem::dests_in(styles),
             hidden: HideElem::hidden_in(styles),
+            layer: LayerElem::current_in(styles),
             extended_shape: false,
         };
         fragment.set_id(ctx, id);
@@ -390,7 +393,7 @@ impl GlyphFragment {
         let mut frame = Frame::soft(size);
         frame.set_baseline(self.ascent);
         frame.push(Point::with_y(self.ascent + self.shift), FrameItem::Text(item));
-        frame.post_process_raw(self.dests, self.hidden);
+        frame.post_process_raw(self.dests, self.hidden, self.layer);
         frame
     }
 
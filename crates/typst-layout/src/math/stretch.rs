@@ -268,7 +268,7 @@ fn assemble(
     let mut frame = Frame::soft(size);
     let mut offset = Abs::zero();
     frame.set_baseline(baseline);
-    frame.post_process_raw(base.dests, base.hidden);
+    frame.post_process_raw(base.dests, base.hidden, base.layer);
 
     for (fragment, advance) in selected {
         let pos = match axis {
@@ -22,6 +22,8 @@ pub fn finalize(
         background,
         foreground,
         fill,
+        bleed,
+        crop_marks,
         numbering,
         supplement,
     }: LayoutedPage,
@@ -70,5 +72,13 @@ pub fn finalize(
     let number = counter.logical();
     counter.step();
 
-    Ok(Page { frame, fill, numbering, supplement, number })
+    Ok(Page {
+        frame,
+        fill,
+        numbering,
+        supplement,
+        number,
+        bleed,
+        crop_marks,
+    })
 }
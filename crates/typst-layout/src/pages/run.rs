@@ -35,6 +35,8 @@ pub struct LayoutedPage {
     pub background: Option<Frame>,
     pub foreground: Option<Frame>,
     pub fill: Smart<Option<Paint>>,
+    pub bleed: Abs,
+    pub crop_marks: bool,
     pub numbering: Option<Numbering>,
     pub supplement: Content,
 }
@@ -124,6 +126,8 @@ fn layout_page_run_impl(
         .relative_to(size);
 
     let fill = PageElem::fill_in(styles);
+    let bleed = PageElem::bleed_in(styles);
+    let crop_marks = PageElem::crop_marks_in(styles);
     let foreground = PageElem::foreground_in(styles);
     let background = PageElem::background_in(styles);
     let header_ascent = PageElem::header_ascent_in(styles).relative_to(margin.top);
@@ -208,6 +212,8 @@ fn layout_page_run_impl(
         layouted.push(LayoutedPage {
             inner,
             fill: fill.clone(),
+            bleed,
+            crop_marks,
             numbering: numbering.clone(),
             supplement: supplement.clone(),
             header: layout_marginal(header, header_size, Alignment::BOTTOM)?,
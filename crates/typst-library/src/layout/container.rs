@@ -131,7 +131,7 @@ pub struct InlineElem {
     /// inline items.
     #[required]
     #[internal]
-    body: callbacks::InlineCallback,
+    pub body: callbacks::InlineCallback,
 }
 
 impl Construct for InlineElem {
@@ -530,6 +530,11 @@ mod callbacks {
                 pub fn call(&self, $($param: $param_ty),*) -> $ret {
                     (self.f)(&self.captured, $($param),*)
                 }
+
+                /// The element that is laid out by this callback.
+                pub fn captured(&self) -> &Content {
+                    &self.captured
+                }
             }
         };
     }
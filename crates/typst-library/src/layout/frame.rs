@@ -14,6 +14,7 @@ use crate::layout::{
     Abs, Axes, FixedAlignment, HideElem, Length, Point, Size, Transform,
 };
 use crate::model::{Destination, LinkElem};
+use crate::pdf::{Layer, LayerElem};
 use crate::text::TextItem;
 use crate::visualize::{Color, FixedStroke, Geometry, Image, Paint, Path, Shape};
 
@@ -308,6 +309,7 @@ impl Frame {
     /// includes:
     /// - `HideElem::hidden`
     /// - `LinkElem::dests`
+    /// - `LayerElem::current`
     ///
     /// This must be called on all frames produced by elements
     /// that manually handle styles (because their children can have varying
@@ -328,12 +330,18 @@ impl Frame {
             self.post_process_raw(
                 LinkElem::dests_in(styles),
                 HideElem::hidden_in(styles),
+                LayerElem::current_in(styles),
             );
         }
     }
 
     /// Apply raw late-stage properties from the raw data.
-    pub fn post_process_raw(&mut self, dests: SmallVec<[Destination; 1]>, hide: bool) {
+    pub fn post_process_raw(
+        &mut self,
+        dests: SmallVec<[Destination; 1]>,
+        hide: bool,
+        layer: Option<Layer>,
+    ) {
         if !self.is_empty() {
             let size = self.size;
             self.push_multiple(
@@ -344,6 +352,9 @@ impl Frame {
             if hide {
                 self.hide();
             }
+            if let Some(layer) = layer {
+                self.layer(layer);
+            }
         }
     }
 
@@ -359,6 +370,13 @@ impl Frame {
         });
     }
 
+    /// Put the contents of the frame on a layer.
+    pub fn layer(&mut self, layer: Layer) {
+        if !self.is_empty() {
+            self.group(|g| g.layer = Some(layer));
+        }
+    }
+
     /// Add a background fill.
     pub fn fill(&mut self, fill: impl Into<Paint>) {
         self.prepend(
@@ -552,6 +570,8 @@ pub struct GroupItem {
     /// The group's logical parent. All elements in this group are logically
     /// ordered immediately after the parent's start location.
     pub parent: Option<Location>,
+    /// The layer the group's contents are on.
+    pub layer: Option<Layer>,
 }
 
 impl GroupItem {
@@ -563,6 +583,7 @@ impl GroupItem {
             clip_path: None,
             label: None,
             parent: None,
+            layer: None,
         }
     }
 }
@@ -4,6 +4,7 @@ use std::ops::RangeInclusive;
 use std::str::FromStr;
 
 use comemo::Track;
+use typst_syntax::Spanned;
 use typst_utils::{singleton, NonZeroExt, Scalar};
 
 use crate::diag::{bail, SourceResult};
@@ -205,6 +206,57 @@ pub struct PageElem {
     #[ghost]
     pub fill: Smart<Option<Paint>>,
 
+    /// How far the page extends beyond its edges in PDF export.
+    ///
+    /// In professional printing, pages are typically printed on larger sheets
+    /// and then trimmed to their final size. To avoid thin white edges where
+    /// the cut is slightly off, anything that should reach the edge of the
+    /// page must extend a bit beyond it. The page's [`fill`]($page.fill)
+    /// covers this _bleed_ area. The bleed does not change the page's size or
+    /// the layout of its contents, including its
+    /// [`background`]($page.background). Content that is
+    /// [placed]($place) beyond the page's edges shows up in the bleed.
+    ///
+    /// In PDF export, the page is enlarged by the bleed and its final size is
+    /// recorded as its _trim box._ Other export formats ignore the bleed.
+    ///
+    /// ```typ
+    /// #set page(
+    ///   bleed: 3mm,
+    ///   fill: aqua,
+    ///   background: place(dx: -3mm, dy: -3mm, rect(
+    ///     width: 100% + 6mm,
+    ///     height: 30% + 3mm,
+    ///     fill: eastern,
+    ///   )),
+    /// )
+    /// ```
+    #[parse(
+        let bleed: Option<Spanned<Length>> = args.named("bleed")?;
+        if let Some(Spanned { v, span }) = bleed {
+            if v.abs < Abs::zero() || v.em.get() < 0.0 {
+                bail!(span, "bleed must not be negative");
+            }
+        }
+        bleed.map(|bleed| bleed.v)
+    )]
+    #[resolve]
+    #[ghost]
+    pub bleed: Length,
+
+    /// Whether to draw crop marks around the page in PDF export.
+    ///
+    /// Crop marks tell the print shop where to trim the page. They are drawn
+    /// outside of the [bleed]($page.bleed), enlarging the exported page
+    /// further. Other export formats do not show crop marks.
+    ///
+    /// ```typ
+    /// #set page(bleed: 3mm, crop-marks: true)
+    /// ```
+    #[default(false)]
+    #[ghost]
+    pub crop_marks: bool,
+
     /// How to [number]($numbering) the pages.
     ///
     /// If an explicit `footer` (or `header` for top-aligned numbering) is
@@ -472,6 +524,10 @@ pub struct Page {
     /// The logical page number (controlled by `counter(page)` and may thus not
     /// match the physical number).
     pub number: usize,
+    /// How far the page's fill extends beyond the frame on each side.
+    pub bleed: Abs,
+    /// Whether to draw crop marks around the bleed.
+    pub crop_marks: bool,
 }
 
 impl Page {
@@ -20,6 +20,7 @@ pub mod layout;
 pub mod loading;
 pub mod math;
 pub mod model;
+pub mod pdf;
 pub mod routines;
 pub mod symbols;
 pub mod text;
@@ -246,6 +247,8 @@ fn global(math: Module, inputs: Dict) -> Module {
     self::introspection::define(&mut global);
     self::loading::define(&mut global);
     self::symbols::define(&mut global);
+    global.reset_category();
+    global.define_module(self::pdf::module());
     prelude(&mut global);
     Module::new("global", global)
 }
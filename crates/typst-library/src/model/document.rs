@@ -1,13 +1,14 @@
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 
 use crate::diag::{bail, HintedStrResult, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, Args, Array, Construct, Content, Datetime, Fields, Smart, StyleChain,
-    Styles, Value,
+    cast, dict, elem, Args, Array, Construct, Content, Datetime, Dict, Fields, Smart,
+    Str, StyleChain, Styles, Value,
 };
 use crate::introspection::Introspector;
 use crate::layout::Page;
+use crate::text::Lang;
 
 /// The root element of a document and its metadata.
 ///
@@ -56,6 +57,71 @@ pub struct DocumentElem {
     /// something other than `{auto}`.
     #[ghost]
     pub date: Smart<Option<Datetime>>,
+
+    /// The document's subject, i.e. a short description of what it is about.
+    ///
+    /// Like the title, this can be arbitrary content, but is converted to
+    /// plain text for the PDF metadata.
+    #[ghost]
+    pub subject: Option<Content>,
+
+    /// The document's main language.
+    ///
+    /// If this is `{auto}` (default), Typst uses the [language]($text.lang)
+    /// of most of the document's text. Assistive technology relies on this to
+    /// read the document correctly.
+    #[ghost]
+    pub lang: Smart<Lang>,
+
+    /// A copyright notice for the document.
+    ///
+    /// This is only embedded into the PDF's XMP metadata, as its document
+    /// information dictionary has no entry for it.
+    ///
+    /// ```example
+    /// #set document(copyright: "© 2024 Typst GmbH")
+    /// ```
+    #[ghost]
+    pub copyright: Option<EcoString>,
+
+    /// A URL under which the document's license can be found.
+    ///
+    /// Like the copyright notice, this is only embedded into the PDF's XMP
+    /// metadata.
+    #[ghost]
+    pub license: Option<EcoString>,
+
+    /// Custom XMP metadata.
+    ///
+    /// This is a dictionary that maps namespace prefixes to dictionaries with
+    /// the `namespace` URI and the namespace's `properties`. A property's value
+    /// can be a string, number, boolean, or array of strings. Arrays are
+    /// written as ordered lists (`seq`). For unordered lists (`bag`), write
+    /// `{(bag: ("a", "b"))}` instead. This can be used to embed metadata from
+    /// vocabularies like Dublin Core or PRISM.
+    ///
+    /// ```example
+    /// #set document(xmp: (
+    ///   prism: (
+    ///     namespace: "http://prismstandard.org/namespaces/basic/3.0/",
+    ///     schema: "PRISM Basic Metadata",
+    ///     properties: (
+    ///       doi: "10.1000/182",
+    ///       volume: "4",
+    ///       keyword: (bag: ("metadata", "typesetting")),
+    ///     ),
+    ///   ),
+    /// ))
+    /// ```
+    ///
+    /// PDF/A requires a description of each namespace that the XMP
+    /// specification does not predefine (like Dublin Core). When exporting to
+    /// PDF/A, such namespaces must thus have a `schema` with a short
+    /// description of the namespace, from which Typst writes the description.
+    /// Each property is described with the generic text "Custom property of
+    /// the document", followed by the namespace's `schema` in parentheses.
+    #[ghost]
+    pub xmp: XmpMetadata,
 }
 
 impl Construct for DocumentElem {
@@ -86,6 +152,134 @@ cast! {
     v: Array => Self(v.into_iter().map(Value::cast).collect::<HintedStrResult<_>>()?),
 }
 
+/// Custom XMP metadata, grouped by namespace.
+#[derive(Debug, Default, Clone, PartialEq, Hash)]
+pub struct XmpMetadata(pub Vec<XmpNamespace>);
+
+cast! {
+    XmpMetadata,
+    self => self
+        .0
+        .into_iter()
+        .map(|namespace| (namespace.prefix.clone().into(), namespace.into_value()))
+        .collect::<Dict>()
+        .into_value(),
+    v: Dict => Self(v.into_iter().map(XmpNamespace::parse).collect::<HintedStrResult<_>>()?),
+}
+
+/// Properties in a custom XMP namespace.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct XmpNamespace {
+    /// The prefix that is used for the namespace.
+    pub prefix: EcoString,
+    /// The namespace's URI.
+    pub uri: EcoString,
+    /// A short description of the namespace for PDF/A extension schemas.
+    pub schema: Option<EcoString>,
+    /// The properties and their values.
+    pub properties: Vec<(EcoString, XmpValue)>,
+}
+
+impl XmpNamespace {
+    /// Parse a namespace from its prefix and its dictionary.
+    fn parse((prefix, value): (Str, Value)) -> HintedStrResult<Self> {
+        if !is_xml_name(&prefix) {
+            bail!("`{prefix}` is not a valid XMP namespace prefix");
+        }
+
+        let mut dict: Dict = value.cast()?;
+        let uri = dict.take("namespace")?.cast()?;
+        let schema = dict.take("schema").ok().map(Value::cast).transpose()?;
+        let properties: Dict = dict.take("properties")?.cast()?;
+        dict.finish(&["namespace", "schema", "properties"])?;
+
+        let properties = properties
+            .into_iter()
+            .map(|(name, value)| {
+                if !is_xml_name(&name) {
+                    bail!("`{name}` is not a valid XMP property name");
+                }
+                Ok((name.into(), value.cast()?))
+            })
+            .collect::<HintedStrResult<_>>()?;
+
+        Ok(Self { prefix: prefix.into(), uri, schema, properties })
+    }
+}
+
+cast! {
+    XmpNamespace,
+    self => {
+        let mut dict = dict! { "namespace" => self.uri };
+        if let Some(schema) = self.schema {
+            dict.insert("schema".into(), schema.into_value());
+        }
+        dict.insert(
+            "properties".into(),
+            self.properties
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into_value()))
+                .collect::<Dict>()
+                .into_value(),
+        );
+        dict.into_value()
+    },
+}
+
+/// The value of a custom XMP property.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum XmpValue {
+    /// A text value.
+    Text(EcoString),
+    /// An ordered list of text values.
+    Seq(Vec<EcoString>),
+    /// An unordered list of text values.
+    Bag(Vec<EcoString>),
+}
+
+impl XmpValue {
+    /// The value type of the property in XMP's notation, e.g. `seq Text`.
+    pub fn value_type(&self) -> &'static str {
+        match self {
+            Self::Text(_) => "Text",
+            Self::Seq(_) => "seq Text",
+            Self::Bag(_) => "bag Text",
+        }
+    }
+}
+
+cast! {
+    XmpValue,
+    self => match self {
+        Self::Text(v) => v.into_value(),
+        Self::Seq(v) => v.into_value(),
+        Self::Bag(v) => dict! { "bag" => v }.into_value(),
+    },
+    v: EcoString => Self::Text(v),
+    v: i64 => Self::Text(eco_format!("{v}")),
+    v: f64 => Self::Text(eco_format!("{v}")),
+    // XMP's boolean type is capitalized.
+    v: bool => Self::Text(if v { "True".into() } else { "False".into() }),
+    v: Array => Self::Seq(v.into_iter().map(Value::cast).collect::<HintedStrResult<_>>()?),
+    mut v: Dict => {
+        let value = match (v.take("bag").ok(), v.take("seq").ok()) {
+            (Some(items), None) => Self::Bag(items.cast()?),
+            (None, Some(items)) => Self::Seq(items.cast()?),
+            _ => bail!("expected a dictionary with either `bag` or `seq`"),
+        };
+        v.finish(&["bag", "seq"])?;
+        value
+    },
+}
+
+/// Whether a string is a valid XML name without a colon, as required for
+/// namespace prefixes and property names.
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
 /// A finished document with metadata and page frames.
 #[derive(Debug, Default, Clone)]
 pub struct Document {
@@ -108,6 +302,16 @@ pub struct DocumentInfo {
     pub keywords: Vec<EcoString>,
     /// The document's creation date.
     pub date: Smart<Option<Datetime>>,
+    /// The document's subject.
+    pub subject: Option<EcoString>,
+    /// The document's main language.
+    pub lang: Smart<Lang>,
+    /// The document's copyright notice.
+    pub copyright: Option<EcoString>,
+    /// A URL under which the document's license can be found.
+    pub license: Option<EcoString>,
+    /// Custom XMP metadata.
+    pub xmp: Vec<XmpNamespace>,
 }
 
 impl DocumentInfo {
@@ -130,6 +334,22 @@ impl DocumentInfo {
         if has(<DocumentElem as Fields>::Enum::Date) {
             self.date = DocumentElem::date_in(chain);
         }
+        if has(<DocumentElem as Fields>::Enum::Subject) {
+            self.subject =
+                DocumentElem::subject_in(chain).map(|content| content.plain_text());
+        }
+        if has(<DocumentElem as Fields>::Enum::Lang) {
+            self.lang = DocumentElem::lang_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::Copyright) {
+            self.copyright = DocumentElem::copyright_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::License) {
+            self.license = DocumentElem::license_in(chain);
+        }
+        if has(<DocumentElem as Fields>::Enum::Xmp) {
+            self.xmp = DocumentElem::xmp_in(chain).0;
+        }
     }
 }
 
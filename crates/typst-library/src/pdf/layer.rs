@@ -0,0 +1,68 @@
+use ecow::EcoString;
+
+use crate::diag::SourceResult;
+use crate::engine::Engine;
+use crate::foundations::{elem, Content, Packed, Show, StyleChain};
+
+/// Puts content on a layer that can be shown or hidden in a PDF viewer.
+///
+/// In the exported PDF, each distinct layer name becomes an _optional content
+/// group._ Viewers list these groups in their layer panel, where readers can
+/// toggle them. This is useful for overlays like watermarks, crop marks, or
+/// the solutions to an exercise sheet.
+///
+/// Layers do not affect the layout: Content on a hidden layer still takes up
+/// space. When exporting to PNG or SVG, content on layers that are not
+/// [`visible`]($pdf.layer.visible) is omitted.
+///
+/// # Example
+/// ```typ
+/// = Exercise
+/// What is $2 + 2$?
+///
+/// #pdf.layer("Solutions", visible: false)[
+///   The answer is $4$.
+/// ]
+/// ```
+///
+/// If the same layer name is used multiple times, all content ends up on one
+/// layer. Its initial visibility is taken from its first use.
+#[elem(Show)]
+pub struct LayerElem {
+    /// The name of the layer as shown in a PDF viewer.
+    #[required]
+    pub name: EcoString,
+
+    /// Whether the layer is visible when the document is opened.
+    #[default(true)]
+    pub visible: bool,
+
+    /// The content to put on the layer.
+    #[required]
+    pub body: Content,
+
+    /// This style is set on the content contained in the `layer` element.
+    #[internal]
+    #[ghost]
+    pub current: Option<Layer>,
+}
+
+impl Show for Packed<LayerElem> {
+    #[typst_macros::time(name = "pdf.layer", span = self.span())]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let layer = Layer {
+            name: self.name().clone(),
+            visible: self.visible(styles),
+        };
+        Ok(self.body().clone().styled(LayerElem::set_current(Some(layer))))
+    }
+}
+
+/// A layer that frame contents can be put on.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Layer {
+    /// The name of the layer.
+    pub name: EcoString,
+    /// Whether the layer is initially visible.
+    pub visible: bool,
+}
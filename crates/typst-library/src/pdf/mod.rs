@@ -0,0 +1,26 @@
+//! PDF-specific functionality.
+
+mod layer;
+
+pub use self::layer::*;
+
+use crate::foundations::{category, Category, Module, Scope};
+
+/// PDF-specific functionality.
+///
+/// These functions only have an effect when exporting to PDF. Other export
+/// formats ignore them or approximate their behaviour.
+///
+/// # PDF module
+/// All PDF functions are part of the `pdf` [module]($scripting/#modules) and
+/// are accessed with the `pdf.` prefix.
+#[category]
+pub static PDF: Category;
+
+/// Create a module with all PDF definitions.
+pub fn module() -> Module {
+    let mut pdf = Scope::deduplicating();
+    pdf.category(PDF);
+    pdf.define_elem::<LayerElem>();
+    Module::new("pdf", pdf)
+}
@@ -0,0 +1,445 @@
+//! Reuse of encoded pages across exports.
+//!
+//! Encoding a page into a content stream registers the resources it uses
+//! (fonts, glyphs, images, ...) with the document-wide [`Resources`]. The
+//! content stream refers to these resources by their index, so an encoded page
+//! can only be reused if the resources were in exactly the same state before
+//! the page as when it was first encoded.
+//!
+//! To check this cheaply, we keep a fingerprint of the resources which is only
+//! updated when a page introduces new resources. Pages that only use resources
+//! that already exist leave it unchanged. This way, editing a page does not
+//! invalidate the pages after it, as long as the edit didn't bring in a new
+//! font, glyph, image, or layer.
+//!
+//! Only the content streams are cached. The objects for fonts, images, and
+//! gradients as well as the resource dictionaries are still written on every
+//! export, but the expensive parts of that (font subsetting and image
+//! encoding) are memoized separately. Pages that use patterns or color glyphs
+//! are never cached, as their nested resources can't be replayed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, MutexGuard};
+
+use ecow::EcoString;
+use typst_library::diag::{SourceResult, StrResult};
+use typst_library::layout::Page;
+use typst_library::pdf::Layer;
+use typst_library::text::{Font, Lang};
+use typst_library::visualize::Image;
+use typst_syntax::Span;
+use typst_utils::Deferred;
+
+use crate::color::ColorSpaces;
+use crate::content;
+use crate::extg::ExtGState;
+use crate::gradient::PdfGradient;
+use crate::image::EncodedImage;
+use crate::resources::Resources;
+use crate::PdfOptions;
+
+/// Caches encoded pages across multiple PDF exports.
+///
+/// Pass the same cache to consecutive exports of a changing document (for
+/// example, when recompiling on every keystroke) through
+/// [`PdfOptions::cache`]. The content streams of unchanged pages will then be
+/// taken from the cache instead of being encoded again. Entries that were not
+/// used by the latest export are evicted.
+#[derive(Default)]
+pub struct PdfCache {
+    pages: Mutex<HashMap<u128, CachedPage>>,
+}
+
+impl PdfCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Debug for PdfCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad("PdfCache(..)")
+    }
+}
+
+/// Access to the cache during a single export.
+pub(crate) struct CacheSession<'a> {
+    /// The pages of the previous export.
+    old: MutexGuard<'a, HashMap<u128, CachedPage>>,
+    /// The pages of this export.
+    new: HashMap<u128, CachedPage>,
+    /// The fingerprint of the resources at the current page.
+    fingerprint: u128,
+}
+
+impl<'a> CacheSession<'a> {
+    /// Start using the cache for an export.
+    pub fn new(cache: &'a PdfCache) -> Self {
+        Self {
+            old: cache.pages.lock().unwrap_or_else(|poisoned| poisoned.into_inner()),
+            new: HashMap::new(),
+            fingerprint: 0,
+        }
+    }
+
+    /// Encode the next exported page with `encode` or take it from the cache.
+    pub fn page(
+        &mut self,
+        options: &PdfOptions,
+        resources: &mut Resources<()>,
+        page: &Page,
+        encode: impl FnOnce(&mut Resources<()>) -> SourceResult<content::Encoded>,
+    ) -> SourceResult<content::Encoded> {
+        let key = key(self.fingerprint, options, page);
+
+        // The page may have been encoded by the previous export or, if the
+        // document contains the same page twice in a row, by this one.
+        if let Some(cached) = self.old.remove(&key) {
+            self.new.insert(key, cached);
+        }
+
+        if let Some(cached) = self.new.get(&key) {
+            cached.delta.apply(resources);
+            self.fingerprint = cached.fingerprint;
+            return Ok(cached.encoded.clone());
+        }
+
+        let snapshot = Snapshot::new(resources);
+        let encoded = encode(resources)?;
+        match ResourcesDelta::since(&snapshot, resources) {
+            Some(delta) => {
+                self.fingerprint = delta.advance(self.fingerprint);
+                let cached = CachedPage {
+                    encoded: encoded.clone(),
+                    delta,
+                    fingerprint: self.fingerprint,
+                };
+                self.new.insert(key, cached);
+            }
+            // The page can't be cached, but the pages after it can still be
+            // reused as long as this one stays the same.
+            None => self.fingerprint = typst_utils::hash128(&key),
+        }
+
+        Ok(encoded)
+    }
+
+    /// Keep the pages of this export and evict all others.
+    pub fn finish(mut self) {
+        *self.old = self.new;
+    }
+}
+
+/// The key of a page that is encoded with resources with the given
+/// fingerprint.
+fn key(fingerprint: u128, options: &PdfOptions, page: &Page) -> u128 {
+    typst_utils::hash128(&(
+        fingerprint,
+        options.standards.pdfa,
+        &page.frame,
+        page.fill_or_transparent(),
+        page.bleed,
+        page.crop_marks,
+    ))
+}
+
+/// A page that was encoded by a previous export.
+struct CachedPage {
+    /// The encoded content stream.
+    encoded: content::Encoded,
+    /// The resources that encoding the page added.
+    delta: ResourcesDelta,
+    /// The fingerprint of the resources after the page.
+    fingerprint: u128,
+}
+
+/// The state of the resources before a page was encoded.
+struct Snapshot {
+    fonts: usize,
+    images: usize,
+    gradients: usize,
+    ext_gs: usize,
+    layers: usize,
+    glyphs: HashMap<Font, u16>,
+    colors: ColorSpaces,
+    languages: BTreeMap<Lang, usize>,
+    nested: usize,
+}
+
+impl Snapshot {
+    /// Take a snapshot of the current state of the resources.
+    pub fn new(resources: &Resources<()>) -> Self {
+        Self {
+            fonts: resources.fonts.len(),
+            images: resources.images.len(),
+            gradients: resources.gradients.len(),
+            ext_gs: resources.ext_gs.len(),
+            layers: resources.layers.len(),
+            glyphs: resources
+                .glyph_remappers
+                .iter()
+                .map(|(font, remapper)| (font.clone(), remapper.num_gids()))
+                .collect(),
+            colors: resources.colors.clone(),
+            languages: resources.languages.clone(),
+            nested: nested_size(resources),
+        }
+    }
+}
+
+/// The resources that were added while encoding a page.
+struct ResourcesDelta {
+    fonts: Vec<Font>,
+    images: Vec<(Image, Deferred<StrResult<EncodedImage>>, Span)>,
+    gradients: Vec<PdfGradient>,
+    ext_gs: Vec<ExtGState>,
+    layers: Vec<Layer>,
+    glyphs: Vec<(Font, Vec<(u16, EcoString)>)>,
+    colors: ColorSpaces,
+    languages: Vec<(Lang, usize)>,
+}
+
+impl ResourcesDelta {
+    /// Determine what was added to the resources since the snapshot was taken.
+    ///
+    /// Returns `None` if the page added patterns or color glyphs, which we
+    /// can't replay.
+    pub fn since(snapshot: &Snapshot, resources: &Resources<()>) -> Option<Self> {
+        if nested_size(resources) != snapshot.nested {
+            return None;
+        }
+
+        let images = resources
+            .images
+            .items()
+            .enumerate()
+            .skip(snapshot.images)
+            .map(|(i, image)| {
+                let (deferred, span) = &resources.deferred_images[&i];
+                (image.clone(), deferred.clone(), *span)
+            })
+            .collect();
+
+        // Iterate over the fonts in the order of the remapper, so that the
+        // fingerprint doesn't depend on the hash map's iteration order.
+        let glyphs = resources
+            .fonts
+            .items()
+            .filter_map(|font| {
+                let remapper = resources.glyph_remappers.get(font)?;
+                let before = snapshot.glyphs.get(font).copied().unwrap_or(0);
+                let set = &resources.glyph_sets[font];
+                let added: Vec<_> = remapper
+                    .remapped_gids()
+                    .skip(before as usize)
+                    .map(|gid| (gid, set[&gid].clone()))
+                    .collect();
+                (!added.is_empty()).then(|| (font.clone(), added))
+            })
+            .collect();
+
+        let languages = resources
+            .languages
+            .iter()
+            .map(|(&lang, &count)| {
+                (lang, count - snapshot.languages.get(&lang).copied().unwrap_or(0))
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect();
+
+        Some(Self {
+            fonts: resources.fonts.items().skip(snapshot.fonts).cloned().collect(),
+            images,
+            gradients: resources
+                .gradients
+                .items()
+                .skip(snapshot.gradients)
+                .cloned()
+                .collect(),
+            ext_gs: resources.ext_gs.items().skip(snapshot.ext_gs).copied().collect(),
+            layers: resources.layers.items().skip(snapshot.layers).cloned().collect(),
+            glyphs,
+            colors: resources.colors.without(&snapshot.colors),
+            languages,
+        })
+    }
+
+    /// Add the resources to another resource collection that is in the same
+    /// state as the one this delta was computed from.
+    pub fn apply(&self, resources: &mut Resources<()>) {
+        for font in &self.fonts {
+            resources.fonts.insert(font.clone());
+        }
+
+        for (image, deferred, span) in &self.images {
+            let index = resources.images.insert(image.clone());
+            resources.deferred_images.insert(index, (deferred.clone(), *span));
+        }
+
+        for gradient in &self.gradients {
+            resources.gradients.insert(gradient.clone());
+        }
+
+        for ext_gs in &self.ext_gs {
+            resources.ext_gs.insert(*ext_gs);
+        }
+
+        for layer in &self.layers {
+            resources.layers.insert(layer.clone());
+        }
+
+        for (font, glyphs) in &self.glyphs {
+            let remapper = resources.glyph_remappers.entry(font.clone()).or_default();
+            let set = resources.glyph_sets.entry(font.clone()).or_default();
+            for (gid, text) in glyphs {
+                remapper.remap(*gid);
+                set.entry(*gid).or_insert_with(|| text.clone());
+            }
+        }
+
+        resources.colors.merge(&self.colors);
+
+        for &(lang, count) in &self.languages {
+            *resources.languages.entry(lang).or_insert(0) += count;
+        }
+    }
+
+    /// Compute the fingerprint of the resources after this delta was applied
+    /// to resources with the given fingerprint.
+    pub fn advance(&self, fingerprint: u128) -> u128 {
+        let unchanged = self.fonts.is_empty()
+            && self.images.is_empty()
+            && self.gradients.is_empty()
+            && self.ext_gs.is_empty()
+            && self.layers.is_empty()
+            && self.glyphs.is_empty()
+            && self.colors == ColorSpaces::default();
+
+        if unchanged {
+            fingerprint
+        } else {
+            typst_utils::hash128(&(fingerprint, self))
+        }
+    }
+}
+
+impl Hash for ResourcesDelta {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The languages are not included, as they don't affect how
+        // subsequent pages are encoded.
+        self.fonts.hash(state);
+        for (image, _, span) in &self.images {
+            image.hash(state);
+            span.hash(state);
+        }
+        self.gradients.hash(state);
+        self.ext_gs.hash(state);
+        self.layers.hash(state);
+        self.glyphs.hash(state);
+        self.colors.hash(state);
+    }
+}
+
+/// The number of patterns and color glyphs (and the resources they use) in a
+/// resource collection.
+fn nested_size<R>(resources: &Resources<R>) -> usize {
+    let mut size = resources.color_glyph_sets.values().map(BTreeMap::len).sum();
+    if let Some(patterns) = &resources.patterns {
+        size += patterns.remapper.len() + total_size(&patterns.resources);
+    }
+    if let Some(color_fonts) = &resources.color_fonts {
+        size += color_fonts.glyph_count() + total_size(&color_fonts.resources);
+    }
+    size
+}
+
+/// The number of all resources in a resource collection.
+fn total_size<R>(resources: &Resources<R>) -> usize {
+    resources.fonts.len()
+        + resources.images.len()
+        + resources.gradients.len()
+        + resources.ext_gs.len()
+        + resources.layers.len()
+        + resources
+            .glyph_remappers
+            .values()
+            .map(|remapper| remapper.num_gids() as usize)
+            .sum::<usize>()
+        + nested_size(resources)
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_library::foundations::{Content, Smart};
+    use typst_library::introspection::Introspector;
+    use typst_library::layout::{Abs, Frame, FrameItem, Page, Point, Size};
+    use typst_library::model::{Document, DocumentInfo};
+    use typst_library::visualize::{Color, Geometry};
+    use typst_syntax::Span;
+
+    use super::{key, PdfCache};
+    use crate::PdfOptions;
+
+    /// A page with a square in the given color.
+    fn page(color: Color) -> Page {
+        let mut frame = Frame::hard(Size::splat(Abs::pt(100.0)));
+        let shape = Geometry::Rect(Size::splat(Abs::pt(50.0))).filled(color);
+        frame.push(Point::zero(), FrameItem::Shape(shape, Span::detached()));
+        Page {
+            frame,
+            fill: Smart::Auto,
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+            bleed: Abs::zero(),
+            crop_marks: false,
+        }
+    }
+
+    fn document(pages: Vec<Page>) -> Document {
+        Document {
+            pages,
+            info: DocumentInfo::default(),
+            introspector: Introspector::default(),
+        }
+    }
+
+    fn options(cache: Option<&PdfCache>) -> PdfOptions<'_> {
+        PdfOptions {
+            ident: Smart::Custom("test"),
+            cache,
+            ..PdfOptions::default()
+        }
+    }
+
+    /// The content stream that is cached for a page with the given key.
+    fn cached(cache: &PdfCache, key: u128) -> Option<Vec<u8>> {
+        let pages = cache.pages.lock().unwrap();
+        pages.get(&key).map(|page| page.encoded.content.wait().clone())
+    }
+
+    #[test]
+    fn test_cache_reuses_unchanged_pages() {
+        let cache = PdfCache::new();
+        let before = document(vec![page(Color::RED), page(Color::GREEN)]);
+        let after = document(vec![page(Color::RED), page(Color::BLUE)]);
+
+        // The first page is encoded with empty resources.
+        let first = key(0, &options(None), &before.pages[0]);
+        crate::pdf(&before, &options(Some(&cache))).unwrap();
+        let encoded = cached(&cache, first).unwrap();
+        assert_eq!(cache.pages.lock().unwrap().len(), 2);
+
+        // Changing the second page keeps the first one and evicts the old
+        // second one.
+        let pdf = crate::pdf(&after, &options(Some(&cache))).unwrap();
+        assert_eq!(cached(&cache, first), Some(encoded));
+        assert_eq!(cache.pages.lock().unwrap().len(), 2);
+
+        // Using the cache doesn't change the output.
+        assert_eq!(pdf, crate::pdf(&after, &options(None)).unwrap());
+    }
+}
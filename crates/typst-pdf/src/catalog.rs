@@ -1,31 +1,37 @@
 use std::num::NonZeroUsize;
 
-use ecow::eco_format;
-use pdf_writer::types::Direction;
+use ecow::{eco_format, EcoString};
+use pdf_writer::types::{Direction, OutputIntentSubtype};
 use pdf_writer::writers::PageLabel;
 use pdf_writer::{Finish, Name, Pdf, Ref, Str, TextStr};
-use typst_library::diag::{bail, SourceResult};
+use typst_library::diag::{bail, At, SourceResult};
 use typst_library::foundations::{Datetime, Smart};
 use typst_library::layout::Dir;
+use typst_library::model::{XmpNamespace, XmpValue};
 use typst_library::text::Lang;
 use typst_syntax::Span;
 use xmp_writer::{DateTime, LangId, RenditionClass, Timezone, XmpWriter};
 
+use crate::layer::write_oc_properties;
+use crate::linearize::{linearize, LINEARIZATION_OBJECTS};
 use crate::page::PdfPageLabel;
 use crate::{hash_base64, outline, TextStrExt, WithEverything};
 
-/// Write the document catalog.
+/// Write the document catalog and finish the file.
 pub fn write_catalog(
     ctx: WithEverything,
-    pdf: &mut Pdf,
+    mut pdf: Pdf,
     alloc: &mut Ref,
-) -> SourceResult<()> {
-    let lang = ctx
-        .resources
-        .languages
-        .iter()
-        .max_by_key(|(_, &count)| count)
-        .map(|(&l, _)| l);
+) -> SourceResult<Vec<u8>> {
+    // An explicitly given document language takes precedence over the one
+    // that is used most in the text.
+    let lang = ctx.document.info.lang.custom().or_else(|| {
+        ctx.resources
+            .languages
+            .iter()
+            .max_by_key(|(_, &count)| count)
+            .map(|(&l, _)| l)
+    });
 
     let dir = if lang.map(Lang::dir) == Some(Dir::RTL) {
         Direction::R2L
@@ -34,10 +40,10 @@ pub fn write_catalog(
     };
 
     // Write the outline tree.
-    let outline_root_id = outline::write_outline(pdf, alloc, &ctx);
+    let outline_root_id = outline::write_outline(&mut pdf, alloc, &ctx);
 
     // Write the page labels.
-    let page_labels = write_page_labels(pdf, alloc, &ctx);
+    let page_labels = write_page_labels(&mut pdf, alloc, &ctx);
 
     // Write the document information.
     let info_ref = alloc.bump();
@@ -71,6 +77,21 @@ pub fn write_catalog(
         xmp.creator([joined.as_str()]);
     }
 
+    if let Some(subject) = &ctx.document.info.subject {
+        info.subject(TextStr::trimmed(subject));
+        xmp.description([(None, subject.as_str())]);
+    }
+
+    // The document information dictionary has no entries for the copyright
+    // and license, so they only end up in the XMP metadata.
+    if let Some(copyright) = &ctx.document.info.copyright {
+        xmp.rights([(None, copyright.as_str())]);
+    }
+
+    if let Some(license) = &ctx.document.info.license {
+        xmp.web_statement(license);
+    }
+
     let creator = eco_format!("Typst {}", env!("CARGO_PKG_VERSION"));
     info.creator(TextStr(&creator));
     xmp.creator_tool(&creator);
@@ -115,7 +136,11 @@ pub fn write_catalog(
     xmp.instance_id(&instance_id);
     xmp.format("application/pdf");
     xmp.pdf_version("1.7");
-    xmp.language(ctx.resources.languages.keys().map(|lang| LangId(lang.as_str())));
+    if let Smart::Custom(lang) = ctx.document.info.lang {
+        xmp.language([LangId(lang.as_str())]);
+    } else {
+        xmp.language(ctx.resources.languages.keys().map(|lang| LangId(lang.as_str())));
+    }
     xmp.num_pages(ctx.document.pages.len() as u32);
     xmp.rendition_class(RenditionClass::Proof);
 
@@ -152,14 +177,20 @@ pub fn write_catalog(
         xmp.pdfa_conformance("B");
     }
 
-    let xmp_buf = xmp.finish(None);
+    let xmp_buf = write_custom_xmp(&ctx, xmp.finish(None))?;
     let meta_ref = alloc.bump();
     pdf.stream(meta_ref, xmp_buf.as_bytes())
         .pair(Name(b"Type"), Name(b"Metadata"))
         .pair(Name(b"Subtype"), Name(b"XML"));
 
-    // Set IDs only now, so that we don't need to clone them.
-    pdf.set_file_id((doc_id.into_bytes(), instance_id.into_bytes()));
+    let file_id = (doc_id.into_bytes(), instance_id.into_bytes());
+
+    // Write the profile of the output intent.
+    let intent_profile_ref = ctx.options.output_intent.as_ref().map(|intent| {
+        let id = alloc.bump();
+        intent.write_profile(&mut pdf, id);
+        id
+    });
 
     // Write the document catalog.
     let catalog_ref = alloc.bump();
@@ -195,11 +226,31 @@ pub fn write_catalog(
         catalog.lang(TextStr(lang.as_str()));
     }
 
-    if ctx.options.standards.pdfa {
+    write_oc_properties(&mut catalog, &ctx.references.layers);
+
+    if let Some((intent, profile_ref)) =
+        ctx.options.output_intent.as_ref().zip(intent_profile_ref)
+    {
+        // PDF/A only permits one output intent profile, so the CMYK one takes
+        // the place of sRGB. Otherwise, we use the subtype that print
+        // workflows expect, even though we don't claim PDF/X conformance.
+        let subtype = if ctx.options.standards.pdfa {
+            OutputIntentSubtype::PDFA
+        } else {
+            OutputIntentSubtype::PDFX
+        };
         catalog
             .output_intents()
             .push()
-            .subtype(pdf_writer::types::OutputIntentSubtype::PDFA)
+            .subtype(subtype)
+            .output_condition_identifier(TextStr(intent.condition()))
+            .info(TextStr(intent.condition()))
+            .dest_output_profile(profile_ref);
+    } else if ctx.options.standards.pdfa {
+        catalog
+            .output_intents()
+            .push()
+            .subtype(OutputIntentSubtype::PDFA)
             .output_condition(TextStr("sRGB"))
             .output_condition_identifier(TextStr("Custom"))
             .info(TextStr("sRGB IEC61966-2.1"))
@@ -208,11 +259,162 @@ pub fn write_catalog(
 
     catalog.finish();
 
-    if ctx.options.standards.pdfa && pdf.refs().count() > 8388607 {
+    let pages: Vec<Ref> =
+        ctx.globals.pages.iter().filter_map(Option::as_ref).copied().collect();
+    let linearized = ctx.options.linearize && !pages.is_empty();
+
+    // Linearization adds its own objects, which count towards the limit.
+    let reserved = if linearized { LINEARIZATION_OBJECTS } else { 0 };
+    if ctx.options.standards.pdfa && pdf.refs().count() + reserved > 8388607 {
         bail!(Span::detached(), "too many PDF objects");
     }
 
-    Ok(())
+    if linearized {
+        return linearize(&pdf, catalog_ref, info_ref, &pages, &file_id)
+            .at(Span::detached());
+    }
+
+    pdf.set_file_id(file_id);
+    Ok(pdf.finish())
+}
+
+/// Namespaces whose properties may be used in PDF/A without an extension
+/// schema, along with the properties that Typst already writes itself.
+const PREDEFINED_XMP_NAMESPACES: &[(&str, &[&str])] = &[
+    (
+        "http://purl.org/dc/elements/1.1/",
+        &["title", "creator", "description", "rights", "language", "format"],
+    ),
+    ("http://ns.adobe.com/xap/1.0/rights/", &["WebStatement"]),
+];
+
+/// Insert the document's custom XMP namespaces into the finished XMP packet.
+fn write_custom_xmp(ctx: &WithEverything, mut packet: String) -> SourceResult<String> {
+    let namespaces = &ctx.document.info.xmp;
+    if namespaces.is_empty() {
+        return Ok(packet);
+    }
+
+    let mut descriptions = String::new();
+    let mut schemas = String::new();
+    for namespace in namespaces {
+        let predefined = PREDEFINED_XMP_NAMESPACES
+            .iter()
+            .find(|(uri, _)| *uri == namespace.uri.as_str());
+
+        if ctx.options.standards.pdfa && predefined.is_none() {
+            let Some(schema) = &namespace.schema else {
+                bail!(
+                    Span::detached(),
+                    "PDF/A requires a description of custom XMP namespace `{}`",
+                    namespace.uri;
+                    hint: "add a `schema` with a short description of the namespace"
+                );
+            };
+            write_extension_schema(&mut schemas, namespace, schema);
+        }
+
+        descriptions.push_str("<rdf:Description rdf:about=\"\" xmlns:");
+        descriptions.push_str(&namespace.prefix);
+        descriptions.push_str("=\"");
+        xml_escape(&mut descriptions, &namespace.uri);
+        descriptions.push_str("\">");
+
+        for (name, value) in &namespace.properties {
+            if predefined.is_some_and(|(_, reserved)| reserved.contains(&name.as_str())) {
+                bail!(
+                    Span::detached(),
+                    "XMP property `{}:{name}` is already set by Typst",
+                    namespace.prefix;
+                    hint: "use the corresponding field of `document` instead"
+                );
+            }
+
+            let tag = eco_format!("{}:{name}", namespace.prefix);
+            descriptions.push_str(&eco_format!("<{tag}>"));
+            match value {
+                XmpValue::Text(text) => xml_escape(&mut descriptions, text),
+                XmpValue::Seq(items) => write_xmp_list(&mut descriptions, "Seq", items),
+                XmpValue::Bag(items) => write_xmp_list(&mut descriptions, "Bag", items),
+            }
+            descriptions.push_str(&eco_format!("</{tag}>"));
+        }
+
+        descriptions.push_str("</rdf:Description>");
+    }
+
+    if !schemas.is_empty() {
+        descriptions.push_str(
+            "<rdf:Description rdf:about=\"\" \
+             xmlns:pdfaExtension=\"http://www.aiim.org/pdfa/ns/extension/\" \
+             xmlns:pdfaSchema=\"http://www.aiim.org/pdfa/ns/schema#\" \
+             xmlns:pdfaProperty=\"http://www.aiim.org/pdfa/ns/property#\">\
+             <pdfaExtension:schemas><rdf:Bag>",
+        );
+        descriptions.push_str(&schemas);
+        descriptions.push_str("</rdf:Bag></pdfaExtension:schemas></rdf:Description>");
+    }
+
+    let Some(end) = packet.rfind("</rdf:RDF>") else {
+        bail!(Span::detached(), "failed to write custom XMP metadata");
+    };
+
+    packet.insert_str(end, &descriptions);
+    Ok(packet)
+}
+
+/// Append an XMP list of the given kind (`Seq` or `Bag`) to an XML buffer.
+fn write_xmp_list(buf: &mut String, kind: &str, items: &[EcoString]) {
+    buf.push_str(&eco_format!("<rdf:{kind}>"));
+    for item in items {
+        buf.push_str("<rdf:li>");
+        xml_escape(buf, item);
+        buf.push_str("</rdf:li>");
+    }
+    buf.push_str(&eco_format!("</rdf:{kind}>"));
+}
+
+/// Append the PDF/A extension schema that describes a custom namespace to an
+/// XML buffer.
+///
+/// Users only describe the namespace, so all properties share a generic
+/// description that mentions it.
+fn write_extension_schema(buf: &mut String, namespace: &XmpNamespace, schema: &str) {
+    let field = |buf: &mut String, tag: &str, text: &str| {
+        buf.push_str(&eco_format!("<{tag}>"));
+        xml_escape(buf, text);
+        buf.push_str(&eco_format!("</{tag}>"));
+    };
+
+    buf.push_str("<rdf:li rdf:parseType=\"Resource\">");
+    field(buf, "pdfaSchema:schema", schema);
+    field(buf, "pdfaSchema:namespaceURI", &namespace.uri);
+    field(buf, "pdfaSchema:prefix", &namespace.prefix);
+    let description = eco_format!("Custom property of the document ({schema})");
+    buf.push_str("<pdfaSchema:property><rdf:Seq>");
+    for (name, value) in &namespace.properties {
+        buf.push_str("<rdf:li rdf:parseType=\"Resource\">");
+        field(buf, "pdfaProperty:name", name);
+        field(buf, "pdfaProperty:valueType", value.value_type());
+        field(buf, "pdfaProperty:category", "external");
+        field(buf, "pdfaProperty:description", &description);
+        buf.push_str("</rdf:li>");
+    }
+    buf.push_str("</rdf:Seq></pdfaSchema:property></rdf:li>");
+}
+
+/// Append text to an XML buffer, escaping special characters.
+fn xml_escape(buf: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '&' => buf.push_str("&amp;"),
+            '"' => buf.push_str("&quot;"),
+            '\'' => buf.push_str("&apos;"),
+            c => buf.push(c),
+        }
+    }
 }
 
 /// Write the page labels.
@@ -1,8 +1,10 @@
 use std::sync::LazyLock;
 
 use arrayvec::ArrayVec;
+use ecow::EcoString;
 use pdf_writer::{writers, Chunk, Dict, Filter, Name, Ref};
-use typst_library::diag::{bail, SourceResult};
+use typst_library::diag::{bail, SourceResult, StrResult};
+use typst_library::foundations::Bytes;
 use typst_library::visualize::{Color, ColorSpace, Paint};
 use typst_syntax::Span;
 
@@ -20,7 +22,7 @@ static GRAY_ICC_DEFLATED: LazyLock<Vec<u8>> =
     LazyLock::new(|| deflate(typst_assets::icc::S_GREY_V4));
 
 /// The color spaces present in the PDF document
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Hash)]
 pub struct ColorSpaces {
     use_srgb: bool,
     use_d65_gray: bool,
@@ -92,6 +94,60 @@ impl ColorSpaces {
         self.use_linear_rgb |= other.use_linear_rgb;
         self.use_srgb |= other.use_srgb;
     }
+
+    /// The color spaces that are used here, but not in `other`.
+    pub fn without(&self, other: &Self) -> Self {
+        Self {
+            use_srgb: self.use_srgb && !other.use_srgb,
+            use_d65_gray: self.use_d65_gray && !other.use_d65_gray,
+            use_linear_rgb: self.use_linear_rgb && !other.use_linear_rgb,
+        }
+    }
+}
+
+/// A CMYK printing condition that the document is intended for.
+///
+/// It is written as the document's output intent and tells the print shop
+/// (and viewers that simulate print output) how the colors in the document
+/// should be reproduced.
+///
+/// When exporting to PDF/A, the intent has the `GTS_PDFA1` subtype and
+/// replaces the sRGB intent that is written otherwise. Without a PDF/A
+/// standard, it has the `GTS_PDFX` subtype, since that is the one that print
+/// workflows look for. Note that the PDF does not claim PDF/X conformance in
+/// that case, so validators may point out the mismatch.
+#[derive(Debug, Clone, Hash)]
+pub struct CmykOutputIntent {
+    /// The ICC profile of the printing condition.
+    profile: Bytes,
+    /// An identifier of the printing condition, e.g. `FOGRA39`.
+    condition: EcoString,
+}
+
+impl CmykOutputIntent {
+    /// Create an output intent from an ICC profile and an identifier of the
+    /// printing condition it describes.
+    pub fn new(profile: Bytes, condition: EcoString) -> StrResult<Self> {
+        // The data color space signature is at offset 16 in the ICC header.
+        if !profile.get(16..20).is_some_and(|signature| signature == b"CMYK") {
+            bail!("output intent profile must be a CMYK ICC profile");
+        }
+        Ok(Self { profile, condition })
+    }
+
+    /// The identifier of the printing condition.
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
+
+    /// Write the ICC profile of the output intent.
+    pub(crate) fn write_profile(&self, chunk: &mut Chunk, id: Ref) {
+        chunk
+            .icc_profile(id, &deflate(&self.profile))
+            .n(4)
+            .range([0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0])
+            .filter(Filter::FlateDecode);
+    }
 }
 
 /// Write the color space.
@@ -276,6 +276,11 @@ impl ColorFontMap<()> {
 }
 
 impl<R> ColorFontMap<R> {
+    /// The number of color glyphs across all fonts.
+    pub fn glyph_count(&self) -> usize {
+        self.map.values().map(|font| font.glyphs.len()).sum()
+    }
+
     /// Iterate over all Type3 fonts.
     ///
     /// Each item of this iterator maps to a Type3 font: it contains
@@ -16,6 +16,7 @@ use typst_library::layout::{
     Abs, Em, Frame, FrameItem, GroupItem, Point, Ratio, Size, Transform,
 };
 use typst_library::model::Destination;
+use typst_library::pdf::Layer;
 use typst_library::text::color::should_outline;
 use typst_library::text::{Font, Glyph, TextItem, TextItemView};
 use typst_library::visualize::{
@@ -79,6 +80,7 @@ pub fn build(
 }
 
 /// An encoded content stream.
+#[derive(Clone)]
 pub struct Encoded {
     /// The dimensions of the content.
     pub size: Size,
@@ -157,6 +159,8 @@ struct State {
     stroke_space: Option<Name<'static>>,
     /// The current text rendering mode.
     text_rendering_mode: TextRenderingMode,
+    /// The innermost layer that the current item is on.
+    layer: Option<Layer>,
 }
 
 impl State {
@@ -173,6 +177,7 @@ impl State {
             stroke: None,
             stroke_space: None,
             text_rendering_mode: TextRenderingMode::Fill,
+            layer: None,
         }
     }
 
@@ -410,7 +415,27 @@ fn write_group(ctx: &mut Builder, pos: Point, group: &GroupItem) -> SourceResult
         ctx.content.end_path();
     }
 
+    // Nested groups on the same layer are common because the layer style is
+    // applied at every level of the layout, so we only mark the outermost.
+    let layer = group
+        .layer
+        .as_ref()
+        .filter(|&layer| ctx.state.layer.as_ref() != Some(layer));
+    if let Some(layer) = layer {
+        let index = ctx.resources.layers.insert(layer.clone());
+        let name = eco_format!("Oc{index}");
+        ctx.content
+            .begin_marked_content_with_properties(Name(b"OC"))
+            .properties_named(Name(name.as_bytes()));
+        ctx.state.layer = Some(layer.clone());
+    }
+
     write_frame(ctx, &group.frame)?;
+
+    if layer.is_some() {
+        ctx.content.end_marked_content();
+    }
+
     ctx.restore_state();
 
     Ok(())
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use ecow::EcoString;
+use pdf_writer::writers::Catalog;
+use pdf_writer::{Name, Ref, TextStr};
+use typst_library::diag::SourceResult;
+use typst_library::pdf::Layer;
+
+use crate::{PdfChunk, Renumber, WithGlobalRefs};
+
+/// The optional content groups that were written for the document's layers.
+pub struct LayerRefs {
+    /// The group of each layer.
+    pub refs: HashMap<Layer, Ref>,
+    /// All groups in order of first use, with whether they are initially
+    /// visible.
+    pub groups: Vec<(Ref, bool)>,
+}
+
+impl Renumber for LayerRefs {
+    fn renumber(&mut self, offset: i32) {
+        self.refs.renumber(offset);
+        for (id, _) in &mut self.groups {
+            id.renumber(offset);
+        }
+    }
+}
+
+/// Embed all used layers into the PDF as optional content groups.
+///
+/// Layers with the same name share one group, whose initial visibility is
+/// decided by the first of them.
+pub fn write_layers(context: &WithGlobalRefs) -> SourceResult<(PdfChunk, LayerRefs)> {
+    let mut chunk = PdfChunk::new();
+    let mut out = LayerRefs { refs: HashMap::new(), groups: Vec::new() };
+    let mut by_name: HashMap<EcoString, Ref> = HashMap::new();
+    context.resources.traverse(&mut |resources| {
+        for layer in resources.layers.items() {
+            if out.refs.contains_key(layer) {
+                continue;
+            }
+
+            let id = *by_name.entry(layer.name.clone()).or_insert_with(|| {
+                let id = chunk.alloc();
+                out.groups.push((id, layer.visible));
+                chunk
+                    .indirect(id)
+                    .dict()
+                    .pair(Name(b"Type"), Name(b"OCG"))
+                    .pair(Name(b"Name"), TextStr(&layer.name));
+                id
+            });
+
+            out.refs.insert(layer.clone(), id);
+        }
+
+        Ok(())
+    })?;
+
+    Ok((chunk, out))
+}
+
+/// Write the optional content properties into the document catalog.
+pub fn write_oc_properties(catalog: &mut Catalog, layers: &LayerRefs) {
+    if layers.groups.is_empty() {
+        return;
+    }
+
+    let mut properties = catalog.insert(Name(b"OCProperties")).dict();
+    properties
+        .insert(Name(b"OCGs"))
+        .array()
+        .items(layers.groups.iter().map(|&(id, _)| id));
+
+    let mut config = properties.insert(Name(b"D")).dict();
+    config.pair(Name(b"Name"), TextStr("Default"));
+    config
+        .insert(Name(b"Order"))
+        .array()
+        .items(layers.groups.iter().map(|&(id, _)| id));
+    config.insert(Name(b"OFF")).array().items(
+        layers
+            .groups
+            .iter()
+            .filter(|(_, visible)| !visible)
+            .map(|&(id, _)| id),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_library::foundations::{Content, Smart};
+    use typst_library::introspection::Introspector;
+    use typst_library::layout::{Abs, Frame, FrameItem, GroupItem, Page, Point, Size};
+    use typst_library::model::{Document, DocumentInfo};
+    use typst_library::pdf::Layer;
+    use typst_library::visualize::{Color, Geometry};
+    use typst_syntax::Span;
+
+    use crate::PdfOptions;
+
+    /// A square on a layer with the given name.
+    fn square(name: &str, visible: bool) -> FrameItem {
+        let mut frame = Frame::soft(Size::splat(Abs::pt(50.0)));
+        let shape = Geometry::Rect(Size::splat(Abs::pt(50.0))).filled(Color::RED);
+        frame.push(Point::zero(), FrameItem::Shape(shape, Span::detached()));
+        let mut group = GroupItem::new(frame);
+        group.layer = Some(Layer { name: name.into(), visible });
+        FrameItem::Group(group)
+    }
+
+    /// Whether the haystack contains the needle.
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+
+    /// The decompressed contents of all streams in a PDF.
+    fn streams(pdf: &[u8]) -> Vec<Vec<u8>> {
+        let mut streams = vec![];
+        let mut rest = pdf;
+        while let Some(start) = rest.windows(7).position(|w| w == b"stream\n") {
+            rest = &rest[start + 7..];
+            let end = rest.windows(10).position(|w| w == b"\nendstream").unwrap();
+            streams
+                .extend(miniz_oxide::inflate::decompress_to_vec_zlib(&rest[..end]).ok());
+            rest = &rest[end..];
+        }
+        streams
+    }
+
+    #[test]
+    fn test_layers_are_optional_content() {
+        let mut frame = Frame::hard(Size::splat(Abs::pt(100.0)));
+        frame.push(Point::zero(), square("Notes", true));
+        frame.push(Point::splat(Abs::pt(50.0)), square("Draft", false));
+        let document = Document {
+            pages: vec![Page {
+                frame,
+                fill: Smart::Auto,
+                numbering: None,
+                supplement: Content::empty(),
+                number: 1,
+                bleed: Abs::zero(),
+                crop_marks: false,
+            }],
+            info: DocumentInfo::default(),
+            introspector: Introspector::default(),
+        };
+
+        let options = PdfOptions {
+            ident: Smart::Custom("test"),
+            ..PdfOptions::default()
+        };
+        let pdf = crate::pdf(&document, &options).unwrap();
+
+        // The groups are listed in the catalog, with the hidden one turned
+        // off, and referenced from the page's resources.
+        assert!(contains(&pdf, b"/OCProperties"));
+        assert!(contains(&pdf, b"/Type /OCG"));
+        assert!(contains(&pdf, b"/Name (Notes)"));
+        assert!(contains(&pdf, b"/OFF ["));
+        assert!(contains(&pdf, b"/Properties <<"));
+        assert!(contains(&pdf, b"/Oc0 "));
+        assert!(contains(&pdf, b"/Oc1 "));
+
+        // The content is marked as optional content.
+        let content = streams(&pdf)
+            .into_iter()
+            .find(|stream| contains(stream, b"BDC"))
+            .expect("no marked content");
+        assert!(contains(&content, b"/OC /Oc0 BDC"));
+        assert!(contains(&content, b"/OC /Oc1 BDC"));
+        let begins = content.windows(3).filter(|w| w == b"BDC").count();
+        let ends = content.windows(3).filter(|w| w == b"EMC").count();
+        assert_eq!(begins, ends);
+    }
+}
@@ -1,5 +1,6 @@
 //! Exporting of Typst documents into PDFs.
 
+mod cache;
 mod catalog;
 mod color;
 mod color_font;
@@ -8,6 +9,8 @@ mod extg;
 mod font;
 mod gradient;
 mod image;
+mod layer;
+mod linearize;
 mod named_destination;
 mod outline;
 mod page;
@@ -31,6 +34,9 @@ use typst_library::visualize::Image;
 use typst_syntax::Span;
 use typst_utils::Deferred;
 
+pub use crate::cache::PdfCache;
+pub use crate::color::CmykOutputIntent;
+
 use crate::catalog::write_catalog;
 use crate::color::{alloc_color_functions_refs, ColorFunctionRefs};
 use crate::color_font::{write_color_fonts, ColorFontSlice};
@@ -38,6 +44,7 @@ use crate::extg::{write_graphic_states, ExtGState};
 use crate::font::write_fonts;
 use crate::gradient::{write_gradients, PdfGradient};
 use crate::image::write_images;
+use crate::layer::{write_layers, LayerRefs};
 use crate::named_destination::{write_named_destinations, NamedDestinations};
 use crate::page::{alloc_page_refs, traverse_pages, write_page_tree, EncodedPage};
 use crate::pattern::{write_patterns, PdfPattern};
@@ -68,6 +75,7 @@ pub fn pdf(document: &Document, options: &PdfOptions) -> SourceResult<Vec<u8>> {
                 gradients: builder.run(write_gradients)?,
                 patterns: builder.run(write_patterns)?,
                 ext_gs: builder.run(write_graphic_states)?,
+                layers: builder.run(write_layers)?,
             })
         })?
         .phase(|builder| builder.run(write_page_tree))?
@@ -98,6 +106,17 @@ pub struct PdfOptions<'a> {
     pub page_ranges: Option<PageRanges>,
     /// A list of PDF standards that Typst will enforce conformance with.
     pub standards: PdfStandards,
+    /// Whether to write a linearized PDF, which viewers can start to display
+    /// before the whole file has been downloaded (also known as "fast web
+    /// view").
+    pub linearize: bool,
+    /// If not `None`, pages that didn't change since the previous export with
+    /// the same cache are taken from it instead of being encoded again.
+    pub cache: Option<&'a PdfCache>,
+    /// If not `None`, the CMYK printing condition that the document is
+    /// intended for. Otherwise, an sRGB output intent is written when
+    /// exporting to PDF/A.
+    pub output_intent: Option<CmykOutputIntent>,
 }
 
 /// Encapsulates a list of compatible PDF standards.
@@ -161,7 +180,8 @@ pub enum PdfStandard {
 /// phases, and construct a part of the new state.
 ///
 /// A final step, that has direct access to the global reference allocator and
-/// PDF document, can be run with [`PdfBuilder::export_with`].
+/// PDF document and produces the final file, can be run with
+/// [`PdfBuilder::export_with`].
 struct PdfBuilder<S> {
     /// The context that has been accumulated so far.
     state: S,
@@ -272,6 +292,8 @@ struct References {
     patterns: HashMap<PdfPattern, Ref>,
     /// The IDs of written external graphics states.
     ext_gs: HashMap<ExtGState, Ref>,
+    /// The IDs of written optional content groups.
+    layers: LayerRefs,
 }
 
 /// At this point, the references have been assigned to all resources. The page
@@ -395,10 +417,9 @@ impl<S> PdfBuilder<S> {
     /// document.
     fn export_with<P>(mut self, process: P) -> SourceResult<Vec<u8>>
     where
-        P: Fn(S, &mut Pdf, &mut Ref) -> SourceResult<()>,
+        P: Fn(S, Pdf, &mut Ref) -> SourceResult<Vec<u8>>,
     {
-        process(self.state, &mut self.pdf, &mut self.alloc)?;
-        Ok(self.pdf.finish())
+        process(self.state, self.pdf, &mut self.alloc)
     }
 }
 
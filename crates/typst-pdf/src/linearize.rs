@@ -0,0 +1,1022 @@
+//! Linearization of PDF files ("fast web view").
+//!
+//! A linearized file is organized so that a viewer can display the first page
+//! before the whole file has been downloaded. It starts with a linearization
+//! parameter dictionary and a cross-reference section covering just the
+//! objects needed for the first page. Then come the document catalog, a hint
+//! stream telling the viewer where the objects of all other pages are, the
+//! first page itself, and finally the remaining pages, the objects shared
+//! between them and everything else, covered by the main cross-reference
+//! section at the very end.
+//!
+//! We produce the file in two steps: First, the PDF is written as usual. Then,
+//! its objects are split apart, renumbered, and reordered into the layout
+//! described above. See Annex F of the PDF 1.7 specification for details.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ecow::eco_format;
+use pdf_writer::{Chunk, Ref};
+use typst_library::diag::{bail, StrResult};
+
+use crate::deflate;
+
+/// The width of the numbers that are written before their value is known.
+///
+/// The linearization dictionary and the first-page trailer precede the objects
+/// whose offsets they contain, so we pad them to a fixed width to be able to
+/// compute the layout in a single pass.
+const PLACEHOLDER_WIDTH: usize = 10;
+
+/// The number of objects that linearization adds to the file: the
+/// linearization dictionary and the hint stream.
+pub const LINEARIZATION_OBJECTS: usize = 2;
+
+/// Reorder the objects in `pdf` into a linearized file.
+///
+/// `pages` must contain the references of all page objects in order and must
+/// not be empty.
+pub fn linearize(
+    pdf: &Chunk,
+    catalog: Ref,
+    info: Ref,
+    pages: &[Ref],
+    file_id: &(Vec<u8>, Vec<u8>),
+) -> StrResult<Vec<u8>> {
+    let (header, objects) = split_objects(pdf)?;
+    let index: HashMap<i32, usize> =
+        objects.iter().enumerate().map(|(i, obj)| (obj.id, i)).collect();
+    let lookup = |id: Ref| {
+        index
+            .get(&id.get())
+            .copied()
+            .ok_or_else(|| eco_format!("object {} is missing from the PDF", id.get()))
+    };
+
+    // Find out which objects directly reference which others.
+    let children: Vec<Vec<usize>> = objects
+        .iter()
+        .map(|obj| {
+            let mut refs = vec![];
+            map_refs(obj.body, |id| {
+                refs.extend(index.get(&id).copied());
+                id
+            });
+            refs
+        })
+        .collect();
+
+    let catalog = lookup(catalog)?;
+    let info = lookup(info)?;
+    let pages = pages
+        .iter()
+        .map(|&page| lookup(page))
+        .collect::<StrResult<Vec<_>>>()?;
+    if pages.is_empty() {
+        bail!("cannot linearize a PDF without pages");
+    }
+
+    let mut is_page = vec![false; objects.len()];
+    for &page in &pages {
+        is_page[page] = true;
+    }
+
+    // Collect the objects needed for each page. We never descend into other
+    // pages (which are reachable through the page tree and link annotations)
+    // or into the catalog.
+    let mut users = vec![0; objects.len()];
+    let mut stamp = vec![usize::MAX; objects.len()];
+    let mut closures = Vec::with_capacity(pages.len());
+    for (p, &page) in pages.iter().enumerate() {
+        let mut closure = vec![];
+        let mut stack = vec![page];
+        while let Some(obj) = stack.pop() {
+            if stamp[obj] == p || obj == catalog || (is_page[obj] && obj != page) {
+                continue;
+            }
+            stamp[obj] = p;
+            users[obj] += 1;
+            closure.push(obj);
+            stack.extend(children[obj].iter().rev());
+        }
+        closures.push(closure);
+    }
+
+    // Determine the order of the objects in the file. The first page gets all
+    // of its objects, every other page only those that no other page uses.
+    let mut placed = vec![false; objects.len()];
+    placed[catalog] = true;
+
+    let first: Vec<usize> = closures[0].clone();
+    for &obj in &first {
+        placed[obj] = true;
+    }
+
+    let mut sections: Vec<Vec<usize>> = vec![];
+    for closure in &closures[1..] {
+        let section: Vec<usize> =
+            closure.iter().copied().filter(|&obj| users[obj] == 1).collect();
+        for &obj in &section {
+            placed[obj] = true;
+        }
+        sections.push(section);
+    }
+
+    let mut shared = vec![];
+    for closure in &closures[1..] {
+        for &obj in closure {
+            if !placed[obj] {
+                placed[obj] = true;
+                shared.push(obj);
+            }
+        }
+    }
+
+    let others: Vec<usize> = (0..objects.len()).filter(|&obj| !placed[obj]).collect();
+
+    // Assign the new object numbers. The objects covered by the main
+    // cross-reference section come first, followed by the linearization
+    // dictionary, the catalog, the hint stream, and the first page.
+    let main: Vec<usize> = sections
+        .iter()
+        .flatten()
+        .chain(&shared)
+        .chain(&others)
+        .copied()
+        .collect();
+
+    let mut new_ids = vec![0; objects.len()];
+    for (i, &obj) in main.iter().enumerate() {
+        new_ids[obj] = i as i32 + 1;
+    }
+
+    let main_count = main.len() as i32;
+    let lin_id = main_count + 1;
+    new_ids[catalog] = main_count + 2;
+    let hint_id = main_count + 3;
+    for (i, &obj) in first.iter().enumerate() {
+        new_ids[obj] = main_count + 4 + i as i32;
+    }
+    let total = main_count + 3 + first.len() as i32;
+
+    // Serialize all objects with their new numbers.
+    let encoded: Vec<Vec<u8>> = objects
+        .iter()
+        .enumerate()
+        .map(|(i, obj)| {
+            let body = map_refs(obj.body, |id| {
+                index.get(&id).map_or(id, |&target| new_ids[target])
+            });
+            encode_object(new_ids[i], &body)
+        })
+        .collect();
+
+    let len_of =
+        |objs: &[usize]| -> usize { objs.iter().map(|&obj| encoded[obj].len()).sum() };
+    let info = new_ids[info];
+
+    // Compute the layout without the hint stream, as all offsets in the hint
+    // tables are to be interpreted as if it weren't there.
+    let first_xref_len = first_xref(
+        lin_id,
+        &vec![0; 3 + first.len()],
+        total,
+        0,
+        new_ids[catalog],
+        info,
+        file_id,
+    )
+    .len();
+    let lin_dict_len = linearization_dict(lin_id, &LinearizationParams::default()).len();
+    let first_page_start =
+        header.len() + lin_dict_len + first_xref_len + encoded[catalog].len();
+    let first_page_len: usize = len_of(&first);
+    let main_start = first_page_start + first_page_len;
+
+    let mut main_offsets = Vec::with_capacity(main.len());
+    let mut offset = main_start;
+    for &obj in &main {
+        main_offsets.push(offset);
+        offset += encoded[obj].len();
+    }
+
+    // Write the hint stream.
+    let mut shared_ids = HashMap::new();
+    for (i, &obj) in first.iter().chain(&shared).enumerate() {
+        shared_ids.insert(obj, i as u32);
+    }
+
+    let mut entries = vec![PageEntry {
+        objects: first.len() as u32,
+        length: first_page_len as u32,
+        shared: vec![],
+    }];
+    for (section, closure) in sections.iter().zip(&closures[1..]) {
+        entries.push(PageEntry {
+            objects: section.len() as u32,
+            length: len_of(section) as u32,
+            shared: closure
+                .iter()
+                .filter_map(|obj| shared_ids.get(obj).copied())
+                .collect(),
+        });
+    }
+
+    let shared_start = main_start + len_of(&sections.concat());
+    let groups: Vec<u32> = first
+        .iter()
+        .chain(&shared)
+        .map(|&obj| encoded[obj].len() as u32)
+        .collect();
+
+    let page_table = page_offset_hint_table(&entries, first_page_start as u32);
+    let shared_table = shared_object_hint_table(
+        shared.first().map_or(0, |&obj| new_ids[obj] as u32),
+        if shared.is_empty() { 0 } else { shared_start as u32 },
+        first.len() as u32,
+        &groups,
+    );
+
+    let mut hint_data = page_table;
+    let shared_offset = hint_data.len();
+    hint_data.extend(shared_table);
+    let hint_data = deflate(&hint_data);
+    let hint = encode_stream(hint_id, &hint_data, shared_offset);
+
+    // Now that we know the size of everything, compute the final offsets.
+    let lin_offset = header.len();
+    let first_xref_offset = lin_offset + lin_dict_len;
+    let catalog_offset = first_xref_offset + first_xref_len;
+    let hint_offset = catalog_offset + encoded[catalog].len();
+    let first_page_offset = hint_offset + hint.len();
+    let main_xref_offset = first_page_offset + first_page_len + len_of(&main);
+
+    let mut main_xref = format!("xref\n0 {}\n", main_count + 1).into_bytes();
+    let first_entry = main_xref_offset + main_xref.len() - 1;
+    main_xref.extend(b"0000000000 65535 f\r\n");
+    for &offset in &main_offsets {
+        xref_entry(&mut main_xref, offset + hint.len());
+    }
+    main_xref.extend(
+        format!(
+            "trailer\n<<\n  /Size {}\n>>\nstartxref\n{first_xref_offset}\n%%EOF",
+            main_count + 1,
+        )
+        .as_bytes(),
+    );
+
+    let file_len = main_xref_offset + main_xref.len();
+    let params = LinearizationParams {
+        file_len,
+        hint_offset,
+        hint_len: hint.len(),
+        first_page: new_ids[pages[0]],
+        first_page_end: first_page_offset + first_page_len,
+        page_count: pages.len(),
+        main_xref_entry: first_entry,
+    };
+
+    // Assemble the file.
+    let mut buf = Vec::with_capacity(file_len);
+    buf.extend(header);
+    buf.extend(linearization_dict(lin_id, &params));
+    debug_assert_eq!(buf.len(), first_xref_offset);
+
+    let mut offsets = vec![lin_offset, catalog_offset, hint_offset];
+    let mut offset = first_page_offset;
+    for &obj in &first {
+        offsets.push(offset);
+        offset += encoded[obj].len();
+    }
+
+    buf.extend(first_xref(
+        lin_id,
+        &offsets,
+        total,
+        main_xref_offset,
+        new_ids[catalog],
+        info,
+        file_id,
+    ));
+    debug_assert_eq!(buf.len(), catalog_offset);
+
+    buf.extend(&encoded[catalog]);
+    buf.extend(&hint);
+    for &obj in first.iter().chain(&main) {
+        buf.extend(&encoded[obj]);
+    }
+
+    debug_assert_eq!(buf.len(), main_xref_offset);
+    buf.extend(main_xref);
+    Ok(buf)
+}
+
+/// An indirect object of the non-linearized file.
+struct Object<'a> {
+    /// The object's original number.
+    id: i32,
+    /// Everything between `obj` and `endobj`.
+    body: &'a [u8],
+}
+
+/// Split a chunk into its file header and its indirect objects.
+///
+/// The objects are read one after another in the order in which they were
+/// written. Stream data is skipped based on the stream's length, so it can
+/// contain arbitrary bytes.
+fn split_objects(chunk: &Chunk) -> StrResult<(&[u8], Vec<Object<'_>>)> {
+    let bytes = chunk.as_bytes();
+    let mut header = bytes.len();
+    let mut objects = vec![];
+    let mut cursor = 0;
+
+    for id in chunk.refs() {
+        let id = id.get();
+        let missing = || eco_format!("object {id} is missing from the PDF");
+
+        // The object header: `{id} 0 obj`.
+        let (_, number) = next_token(bytes, cursor).ok_or_else(missing)?;
+        let (_, generation) = next_token(bytes, number.end).ok_or_else(missing)?;
+        let (_, keyword) = next_token(bytes, generation.end).ok_or_else(missing)?;
+        if bytes[number.clone()] != *id.to_string().as_bytes()
+            || bytes[generation] != *b"0"
+            || bytes[keyword.clone()] != *b"obj"
+        {
+            bail!("object {id} is missing from the PDF");
+        }
+
+        header = header.min(number.start);
+        let body_start = keyword.end;
+        let value = scan_value(bytes, body_start, |_| {});
+        let mut end = value.end;
+
+        let malformed = || eco_format!("object {id} is malformed");
+        let (_, mut next) = next_token(bytes, end).ok_or_else(malformed)?;
+        if bytes[next.clone()] == *b"stream" {
+            let length = value.length.ok_or_else(malformed)?;
+            let data = next.end
+                + match &bytes[next.end..] {
+                    [b'\r', b'\n', ..] => 2,
+                    [b'\n', ..] => 1,
+                    _ => bail!("object {id} is malformed"),
+                };
+            (_, next) = next_token(bytes, data + length).ok_or_else(malformed)?;
+            if bytes[next.clone()] != *b"endstream" {
+                bail!("object {id} is malformed");
+            }
+            end = next.end;
+            (_, next) = next_token(bytes, end).ok_or_else(malformed)?;
+        }
+
+        if bytes[next.clone()] != *b"endobj" {
+            bail!("object {id} is malformed");
+        }
+
+        cursor = next.end;
+        objects.push(Object { id, body: bytes[body_start..end].trim_ascii() });
+    }
+
+    Ok((&bytes[..header], objects))
+}
+
+/// Rewrite the object numbers of all indirect references in an object body.
+///
+/// Only the leading value of the body is scanned. If the object is a stream,
+/// its data is copied verbatim.
+fn map_refs(body: &[u8], mut f: impl FnMut(i32) -> i32) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    let mut copied = 0;
+    scan_value(body, 0, |range| {
+        let id = std::str::from_utf8(&body[range.clone()])
+            .ok()
+            .and_then(|s| s.parse().ok());
+        if let Some(id) = id {
+            out.extend(&body[copied..range.start]);
+            out.extend(f(id).to_string().as_bytes());
+            copied = range.end;
+        }
+    });
+    out.extend(&body[copied..]);
+    out
+}
+
+/// What we learned from scanning a value.
+struct Value {
+    /// The index after the value.
+    end: usize,
+    /// The `/Length` entry if the value is a dictionary that has one.
+    length: Option<usize>,
+}
+
+/// Scan the value starting at or after `i`.
+///
+/// Calls `on_ref` with the range of the object number of each indirect
+/// reference in the value.
+fn scan_value(bytes: &[u8], mut i: usize, mut on_ref: impl FnMut(Range<usize>)) -> Value {
+    let mut depth = 0usize;
+    let mut length = None;
+    // The last two tokens, if they were integers.
+    let mut ints: [Option<Range<usize>>; 2] = [None, None];
+    // Whether the last token was the `/Length` key of the outermost dictionary.
+    let mut after_length_key = false;
+
+    while let Some((token, range)) = next_token(bytes, i) {
+        i = range.end;
+        let text = &bytes[range.clone()];
+        let mut int = None;
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => depth = depth.saturating_sub(1),
+            Token::Int => {
+                if after_length_key {
+                    length = std::str::from_utf8(text).ok().and_then(|s| s.parse().ok());
+                }
+                int = Some(range);
+            }
+            Token::Other if text == b"R" => {
+                if let [Some(number), Some(_)] = &ints {
+                    on_ref(number.clone());
+                }
+            }
+            _ => {}
+        }
+
+        after_length_key = token == Token::Name && depth == 1 && text == b"/Length";
+        ints = [ints[1].take(), int];
+        if depth == 0 {
+            break;
+        }
+    }
+
+    Value { end: i, length }
+}
+
+/// The kinds of tokens in a PDF object.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Token {
+    /// An unsigned integer.
+    Int,
+    /// A name like `/Length`.
+    Name,
+    /// The start of a dictionary or array.
+    Open,
+    /// The end of a dictionary or array.
+    Close,
+    /// Anything else, e.g. a keyword, a real number, or a string.
+    Other,
+}
+
+/// Read the next token at or after `i`, skipping white-space and comments.
+fn next_token(bytes: &[u8], mut i: usize) -> Option<(Token, Range<usize>)> {
+    loop {
+        match bytes.get(i)? {
+            c if c.is_ascii_whitespace() => i += 1,
+            b'%' => {
+                while i < bytes.len() && !matches!(bytes[i], b'\r' | b'\n') {
+                    i += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    let start = i;
+    let token = match bytes[i] {
+        b'(' => {
+            i = skip_literal_string(bytes, i);
+            Token::Other
+        }
+        b'<' if bytes.get(i + 1) == Some(&b'<') => {
+            i += 2;
+            Token::Open
+        }
+        b'<' => {
+            while i < bytes.len() && bytes[i] != b'>' {
+                i += 1;
+            }
+            i += 1;
+            Token::Other
+        }
+        b'>' => {
+            i += 2;
+            Token::Close
+        }
+        b'[' => {
+            i += 1;
+            Token::Open
+        }
+        b']' => {
+            i += 1;
+            Token::Close
+        }
+        b'/' => {
+            i += 1;
+            while i < bytes.len() && !is_delimiter(bytes[i]) {
+                i += 1;
+            }
+            Token::Name
+        }
+        _ => {
+            while i < bytes.len() && !is_delimiter(bytes[i]) {
+                i += 1;
+            }
+            if i == start {
+                // A stray delimiter like `)` or `{`.
+                i += 1;
+                Token::Other
+            } else if bytes[start..i].iter().all(u8::is_ascii_digit) {
+                Token::Int
+            } else {
+                Token::Other
+            }
+        }
+    };
+
+    Some((token, start..i.min(bytes.len())))
+}
+
+/// Skip over a literal string starting at `i` and return the index after it.
+fn skip_literal_string(body: &[u8], mut i: usize) -> usize {
+    let mut nesting = 0;
+    while i < body.len() {
+        match body[i] {
+            b'\\' => i += 1,
+            b'(' => nesting += 1,
+            b')' => {
+                nesting -= 1;
+                if nesting == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Whether a byte ends a regular token.
+fn is_delimiter(c: u8) -> bool {
+    c.is_ascii_whitespace()
+        || matches!(
+            c,
+            b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+        )
+}
+
+/// Write an indirect object.
+fn encode_object(id: i32, body: &[u8]) -> Vec<u8> {
+    let mut buf = format!("{id} 0 obj\n").into_bytes();
+    buf.extend(body);
+    buf.extend(b"\nendobj\n\n");
+    buf
+}
+
+/// Write the hint stream object.
+fn encode_stream(id: i32, data: &[u8], shared_offset: usize) -> Vec<u8> {
+    let mut body = format!(
+        "<<\n  /Length {}\n  /Filter /FlateDecode\n  /S {shared_offset}\n>>\nstream\n",
+        data.len(),
+    )
+    .into_bytes();
+    body.extend(data);
+    body.extend(b"\nendstream");
+    encode_object(id, &body)
+}
+
+/// Write a cross-reference entry for an object at `offset`.
+fn xref_entry(buf: &mut Vec<u8>, offset: usize) {
+    buf.extend(format!("{offset:010} 00000 n\r\n").as_bytes());
+}
+
+/// The values of the linearization parameter dictionary.
+#[derive(Default)]
+struct LinearizationParams {
+    /// The length of the whole file.
+    file_len: usize,
+    /// The offset of the primary hint stream.
+    hint_offset: usize,
+    /// The length of the primary hint stream.
+    hint_len: usize,
+    /// The object number of the first page's page object.
+    first_page: i32,
+    /// The offset of the end of the first page.
+    first_page_end: usize,
+    /// The number of pages in the document.
+    page_count: usize,
+    /// The offset of the white-space before the first entry of the main
+    /// cross-reference table.
+    main_xref_entry: usize,
+}
+
+/// Write the linearization parameter dictionary.
+fn linearization_dict(id: i32, params: &LinearizationParams) -> Vec<u8> {
+    const W: usize = PLACEHOLDER_WIDTH;
+    encode_object(
+        id,
+        format!(
+            "<<\n  /Linearized 1\n  /L {:<W$}\n  /H [{:<W$} {:<W$}]\n  /O {:<W$}\n  \
+             /E {:<W$}\n  /N {:<W$}\n  /T {:<W$}\n>>",
+            params.file_len,
+            params.hint_offset,
+            params.hint_len,
+            params.first_page,
+            params.first_page_end,
+            params.page_count,
+            params.main_xref_entry,
+        )
+        .as_bytes(),
+    )
+}
+
+/// Write the first-page cross-reference section and its trailer.
+fn first_xref(
+    first_id: i32,
+    offsets: &[usize],
+    total: i32,
+    prev: usize,
+    catalog: i32,
+    info: i32,
+    file_id: &(Vec<u8>, Vec<u8>),
+) -> Vec<u8> {
+    const W: usize = PLACEHOLDER_WIDTH;
+    let mut buf = format!("xref\n{first_id} {}\n", offsets.len()).into_bytes();
+    for &offset in offsets {
+        xref_entry(&mut buf, offset);
+    }
+    buf.extend(
+        format!(
+            "trailer\n<<\n  /Size {}\n  /Prev {prev:<W$}\n  /Root {catalog} 0 R\n  \
+             /Info {info} 0 R\n  /ID [<{}> <{}>]\n>>\nstartxref\n0\n%%EOF\n",
+            total + 1,
+            hex(&file_id.0),
+            hex(&file_id.1),
+        )
+        .as_bytes(),
+    );
+    buf
+}
+
+/// Encode bytes as a hexadecimal string.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+/// The hints for a single page.
+struct PageEntry {
+    /// The number of objects in the page's section.
+    objects: u32,
+    /// The length of the page's section in bytes.
+    length: u32,
+    /// The indices of the shared object groups the page uses.
+    shared: Vec<u32>,
+}
+
+/// Write the page offset hint table.
+fn page_offset_hint_table(entries: &[PageEntry], first_page_offset: u32) -> Vec<u8> {
+    let least_objects = entries.iter().map(|e| e.objects).min().unwrap_or(0);
+    let most_objects = entries.iter().map(|e| e.objects).max().unwrap_or(0);
+    let least_length = entries.iter().map(|e| e.length).min().unwrap_or(0);
+    let most_length = entries.iter().map(|e| e.length).max().unwrap_or(0);
+    let most_shared = entries.iter().map(|e| e.shared.len() as u32).max().unwrap_or(0);
+    let greatest_id = entries.iter().flat_map(|e| &e.shared).copied().max().unwrap_or(0);
+
+    let objects_bits = bits_needed(most_objects - least_objects);
+    let length_bits = bits_needed(most_length - least_length);
+    let shared_bits = bits_needed(most_shared);
+    let id_bits = bits_needed(greatest_id);
+
+    let mut w = BitWriter::default();
+    w.write(least_objects, 32);
+    w.write(first_page_offset, 32);
+    w.write(objects_bits, 16);
+    w.write(least_length, 32);
+    w.write(length_bits, 16);
+
+    // Like Acrobat, we don't provide real content stream hints and instead
+    // claim that the content stream spans the whole page.
+    w.write(0, 32);
+    w.write(0, 16);
+    w.write(least_length, 32);
+    w.write(length_bits, 16);
+
+    w.write(shared_bits, 16);
+    w.write(id_bits, 16);
+    w.write(0, 16);
+    w.write(1, 16);
+
+    for entry in entries {
+        w.write(entry.objects - least_objects, objects_bits);
+    }
+    w.flush();
+
+    for entry in entries {
+        w.write(entry.length - least_length, length_bits);
+    }
+    w.flush();
+
+    for entry in entries {
+        w.write(entry.shared.len() as u32, shared_bits);
+    }
+    w.flush();
+
+    for entry in entries {
+        for &id in &entry.shared {
+            w.write(id, id_bits);
+        }
+    }
+    w.flush();
+
+    // The numerators of the fractional positions have zero bits and the
+    // content stream offsets are all zero, so there is nothing to write.
+
+    for entry in entries {
+        w.write(entry.length - least_length, length_bits);
+    }
+    w.flush();
+
+    w.finish()
+}
+
+/// Write the shared object hint table.
+///
+/// Every shared object forms its own group. The first `first_page_count`
+/// groups are the objects of the first page.
+fn shared_object_hint_table(
+    first_shared_id: u32,
+    first_shared_offset: u32,
+    first_page_count: u32,
+    groups: &[u32],
+) -> Vec<u8> {
+    let least_length = groups.iter().copied().min().unwrap_or(0);
+    let most_length = groups.iter().copied().max().unwrap_or(0);
+    let length_bits = bits_needed(most_length - least_length);
+
+    let mut w = BitWriter::default();
+    w.write(first_shared_id, 32);
+    w.write(first_shared_offset, 32);
+    w.write(first_page_count, 32);
+    w.write(groups.len() as u32, 32);
+    w.write(0, 16);
+    w.write(least_length, 32);
+    w.write(length_bits, 16);
+
+    for &length in groups {
+        w.write(length - least_length, length_bits);
+    }
+    w.flush();
+
+    // No group has an MD5 signature.
+    for _ in groups {
+        w.write(0, 1);
+    }
+    w.flush();
+
+    w.finish()
+}
+
+/// The number of bits needed to represent all numbers up to `max`.
+fn bits_needed(max: u32) -> u32 {
+    u32::BITS - max.leading_zeros()
+}
+
+/// Writes integers of arbitrary bit width, most significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    buf: Vec<u8>,
+    current: u8,
+    filled: u32,
+}
+
+impl BitWriter {
+    /// Write the `width` lowest bits of `value`.
+    fn write(&mut self, value: u32, width: u32) {
+        for i in (0..width).rev() {
+            self.current = (self.current << 1) | ((value >> i) & 1) as u8;
+            self.filled += 1;
+            if self.filled == 8 {
+                self.buf.push(self.current);
+                self.current = 0;
+                self.filled = 0;
+            }
+        }
+    }
+
+    /// Pad the current byte with zeros.
+    fn flush(&mut self) {
+        if self.filled > 0 {
+            self.buf.push(self.current << (8 - self.filled));
+            self.current = 0;
+            self.filled = 0;
+        }
+    }
+
+    /// Return the written bytes.
+    fn finish(mut self) -> Vec<u8> {
+        self.flush();
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pdf_writer::{Finish, Name, Pdf, Rect, Ref, TextStr};
+
+    use super::*;
+
+    /// Write a PDF with three pages that all use one font and of which the
+    /// last two share another. The content streams contain things that look
+    /// like object headers and references.
+    fn sample() -> (Pdf, Ref, Ref, Vec<Ref>) {
+        let catalog = Ref::new(1);
+        let tree = Ref::new(2);
+        let info = Ref::new(3);
+        let fonts = [Ref::new(4), Ref::new(5)];
+        let pages = [Ref::new(6), Ref::new(7), Ref::new(8)];
+        let contents = [Ref::new(9), Ref::new(10), Ref::new(11)];
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog).pages(tree);
+        pdf.pages(tree).kids(pages).count(pages.len() as i32);
+        pdf.document_info(info).title(TextStr("Test (1 0 R)"));
+        for font in fonts {
+            pdf.type1_font(font).base_font(Name(b"Helvetica"));
+        }
+
+        for (i, (&page, &content)) in pages.iter().zip(&contents).enumerate() {
+            let mut writer = pdf.page(page);
+            writer.parent(tree).media_box(Rect::new(0.0, 0.0, 100.0, 100.0));
+            writer.contents(content);
+            let mut resources = writer.resources();
+            let mut dict = resources.fonts();
+            dict.pair(Name(b"F1"), fonts[0]);
+            if i > 0 {
+                dict.pair(Name(b"F2"), fonts[1]);
+            }
+            dict.finish();
+            resources.finish();
+            writer.finish();
+            let data = format!("% {i}\n9 0 obj\n<< /Length 0 >>\nendobj\n7 0 R\n");
+            pdf.stream(content, data.as_bytes());
+        }
+
+        (pdf, catalog, info, pages.to_vec())
+    }
+
+    /// Find the first occurrence of `needle` in `bytes`.
+    fn find(bytes: &[u8], needle: &[u8]) -> usize {
+        bytes.windows(needle.len()).position(|w| w == needle).unwrap()
+    }
+
+    /// Parse the integers after the first occurrence of `key` in `bytes`.
+    fn ints_after<const N: usize>(bytes: &[u8], key: &str) -> [usize; N] {
+        let mut i = find(bytes, key.as_bytes()) + key.len();
+        std::array::from_fn(|_| {
+            let (token, range) = next_token(bytes, i).unwrap();
+            assert_eq!(token, Token::Int);
+            i = range.end;
+            std::str::from_utf8(&bytes[range]).unwrap().parse().unwrap()
+        })
+    }
+
+    /// Parse the number of the object that starts at `offset`.
+    fn object_at(bytes: &[u8], offset: usize) -> usize {
+        let (token, range) = next_token(bytes, offset).unwrap();
+        assert_eq!((token, range.start), (Token::Int, offset));
+        assert!(bytes[range.end..].starts_with(b" 0 obj"));
+        std::str::from_utf8(&bytes[range]).unwrap().parse().unwrap()
+    }
+
+    /// Parse the cross-reference section at `offset` into its first object
+    /// number and the offsets of its in-use entries.
+    fn xref_at(bytes: &[u8], offset: usize) -> (usize, Vec<usize>) {
+        assert!(bytes[offset..].starts_with(b"xref\n"));
+        let [first, count] = ints_after(&bytes[offset..], "xref");
+        let entries = bytes[offset..]
+            .split(|&b| b == b'\n')
+            .skip(2)
+            .take(count)
+            .filter(|line| line.ends_with(b"n\r"))
+            .map(|line| std::str::from_utf8(&line[..10]).unwrap().parse().unwrap())
+            .collect();
+        (first, entries)
+    }
+
+    /// Read `width` bits at bit position `pos` of `data`.
+    fn read_bits(data: &[u8], pos: usize, width: usize) -> usize {
+        (pos..pos + width)
+            .fold(0, |acc, i| (acc << 1) | usize::from((data[i / 8] >> (7 - i % 8)) & 1))
+    }
+
+    #[test]
+    fn test_split_objects_skips_stream_data() {
+        let (pdf, ..) = sample();
+        let (header, objects) = split_objects(&pdf).unwrap();
+        assert!(header.starts_with(b"%PDF-"));
+        let ids: Vec<i32> = objects.iter().map(|obj| obj.id).collect();
+        assert_eq!(ids, pdf.refs().map(Ref::get).collect::<Vec<_>>());
+
+        let content = objects.iter().find(|obj| obj.id == 9).unwrap();
+        assert!(content.body.starts_with(b"<<"));
+        assert!(content.body.ends_with(b"endstream"));
+    }
+
+    #[test]
+    fn test_map_refs_ignores_strings_and_streams() {
+        let body = b"<< /A 1 0 R /B (2 0 R) /C [3 0 R] >>\nstream\n4 0 R\nendstream";
+        assert_eq!(
+            map_refs(body, |id| id * 10),
+            b"<< /A 10 0 R /B (2 0 R) /C [30 0 R] >>\nstream\n4 0 R\nendstream",
+        );
+    }
+
+    #[test]
+    fn test_linearize_missing_object() {
+        let (pdf, catalog, info, _) = sample();
+        let file_id = (vec![0; 16], vec![0; 16]);
+        let result = linearize(&pdf, catalog, info, &[Ref::new(42)], &file_id);
+        assert_eq!(result.unwrap_err(), "object 42 is missing from the PDF");
+    }
+
+    #[test]
+    fn test_linearize_roundtrip() {
+        let (pdf, catalog, info, pages) = sample();
+        let file_id = (vec![1; 16], vec![2; 16]);
+        let out = linearize(&pdf, catalog, info, &pages, &file_id).unwrap();
+
+        // The linearization dictionary comes first.
+        let lin_offset = out[..find(&out, b" 0 obj")]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .unwrap()
+            + 1;
+        let lin_id = object_at(&out, lin_offset);
+        let [file_len] = ints_after(&out, "/L ");
+        let [hint_offset, hint_len] = ints_after(&out, "/H [");
+        let [first_page] = ints_after(&out, "/O ");
+        let [first_page_end] = ints_after(&out, "/E ");
+        let [page_count] = ints_after(&out, "/N ");
+        let [main_entry] = ints_after(&out, "/T ");
+        assert_eq!(file_len, out.len());
+        assert_eq!(page_count, pages.len());
+
+        // The first-page cross-reference section points at the linearization
+        // dictionary, the catalog, the hint stream, and the first page, whose
+        // page object comes first.
+        let (first, offsets) = xref_at(&out, find(&out, b"xref\n"));
+        assert_eq!(first, lin_id);
+        assert_eq!(offsets.len(), 3 + 4);
+        for (i, &offset) in offsets.iter().enumerate() {
+            assert_eq!(object_at(&out, offset), first + i);
+        }
+        assert_eq!(offsets[0], lin_offset);
+        assert_eq!(offsets[2], hint_offset);
+        assert_eq!(offsets[3], hint_offset + hint_len);
+        assert_eq!(object_at(&out, offsets[3]), first_page);
+        assert!(out[first_page_end..].starts_with(b"1 0 obj"));
+
+        // The main cross-reference section covers all other objects.
+        let main_xref = out.windows(7).rposition(|w| w == b"xref\n0 ").unwrap();
+        let (main_first, main_offsets) = xref_at(&out, main_xref);
+        assert_eq!(main_first, 0);
+        assert_eq!(main_offsets.len(), lin_id - 1);
+        for (i, &offset) in main_offsets.iter().enumerate() {
+            assert_eq!(object_at(&out, offset), i + 1);
+        }
+        assert_eq!(out[main_entry], b'\n');
+        assert!(out[main_entry + 1..].starts_with(b"0000000000 65535 f"));
+
+        // The hint stream's offsets are to be read as if it weren't there.
+        let hint = &out[hint_offset..hint_offset + hint_len];
+        let [length] = ints_after(hint, "/Length");
+        let [shared_offset] = ints_after(hint, "/S");
+        let start = find(hint, b"stream\n") + 7;
+        let data =
+            miniz_oxide::inflate::decompress_to_vec_zlib(&hint[start..start + length])
+                .unwrap();
+
+        // Page offset hint table: the location of the first page and the
+        // length of each page.
+        assert_eq!(read_bits(&data, 32, 32) + hint_len, offsets[3]);
+        let least_objects = read_bits(&data, 0, 32);
+        let objects_bits = read_bits(&data, 64, 16);
+        let least_length = read_bits(&data, 80, 32);
+        let length_bits = read_bits(&data, 112, 16);
+        assert_eq!(least_objects + read_bits(&data, 288, objects_bits), 4);
+        let lengths = 288 + (3 * objects_bits).div_ceil(8) * 8;
+        assert_eq!(
+            least_length + read_bits(&data, lengths, length_bits),
+            first_page_end - offsets[3],
+        );
+
+        // Shared object hint table: the shared font of the last two pages
+        // comes right after their own objects.
+        let table = &data[shared_offset..];
+        let shared_id = read_bits(table, 0, 32);
+        let shared_location = read_bits(table, 32, 32);
+        assert_eq!(read_bits(table, 64, 32), 4);
+        assert_eq!(read_bits(table, 96, 32), 4 + 1);
+        assert_eq!(object_at(&out, shared_location + hint_len), shared_id);
+    }
+}
@@ -7,9 +7,13 @@ use pdf_writer::{Filter, Finish, Name, Rect, Ref, Str};
 use typst_library::diag::SourceResult;
 use typst_library::foundations::Label;
 use typst_library::introspection::Location;
-use typst_library::layout::{Abs, Page};
+use typst_library::layout::{Abs, Frame, FrameItem, Page, Point, Size};
 use typst_library::model::{Destination, Numbering};
+use typst_library::visualize::{Cmyk, Color, FixedStroke, Geometry};
+use typst_syntax::Span;
+use typst_utils::Numeric;
 
+use crate::cache::CacheSession;
 use crate::{
     content, AbsExt, PdfChunk, PdfOptions, Resources, WithDocument, WithRefs,
     WithResources,
@@ -24,6 +28,7 @@ pub fn traverse_pages(
     let mut resources = Resources::default();
     let mut pages = Vec::with_capacity(state.document.pages.len());
     let mut skipped_pages = 0;
+    let mut cache = state.options.cache.map(CacheSession::new);
     for (i, page) in state.document.pages.iter().enumerate() {
         if state
             .options
@@ -35,7 +40,15 @@ pub fn traverse_pages(
             pages.push(None);
             skipped_pages += 1;
         } else {
-            let mut encoded = construct_page(state.options, &mut resources, page)?;
+            let mut encoded = match &mut cache {
+                Some(cache) => EncodedPage {
+                    content: cache.page(state.options, &mut resources, page, |out| {
+                        Ok(construct_page(state.options, out, page)?.content)
+                    })?,
+                    label: None,
+                },
+                None => construct_page(state.options, &mut resources, page)?,
+            };
             encoded.label = page
                 .numbering
                 .as_ref()
@@ -54,6 +67,10 @@ pub fn traverse_pages(
         }
     }
 
+    if let Some(cache) = cache {
+        cache.finish();
+    }
+
     Ok((PdfChunk::new(), (pages, resources)))
 }
 
@@ -64,18 +81,76 @@ fn construct_page(
     out: &mut Resources<()>,
     page: &Page,
 ) -> SourceResult<EncodedPage> {
+    let mut frame = page.frame.clone();
+    let mut fill = page.fill_or_transparent();
+
+    // The fill extends into the bleed, so we can't let the content builder
+    // draw it.
+    if !page.bleed.is_zero() {
+        if let Some(fill) = fill.take() {
+            let size = frame.size() + Size::splat(2.0 * page.bleed);
+            let shape = Geometry::Rect(size).filled(fill);
+            frame.prepend(
+                Point::splat(-page.bleed),
+                FrameItem::Shape(shape, Span::detached()),
+            );
+        }
+    }
+
+    if page.crop_marks {
+        write_crop_marks(&mut frame, page.bleed);
+    }
+
     Ok(EncodedPage {
-        content: content::build(
-            options,
-            out,
-            &page.frame,
-            page.fill_or_transparent(),
-            None,
-        )?,
+        content: content::build(options, out, &frame, fill, None)?,
         label: None,
     })
 }
 
+/// The distance of the crop marks from the bleed box, in points.
+const CROP_MARK_OFFSET: f64 = 3.0;
+/// The length of the crop marks, in points.
+const CROP_MARK_LENGTH: f64 = 12.0;
+/// The thickness of the crop marks, in points.
+const CROP_MARK_THICKNESS: f64 = 0.25;
+
+/// Add crop marks at the corners of the page, outside of the bleed.
+fn write_crop_marks(frame: &mut Frame, bleed: Abs) {
+    // Registration color, so that the marks show up on every printing plate.
+    let registration = Color::Cmyk(Cmyk { c: 1.0, m: 1.0, y: 1.0, k: 1.0 });
+    let stroke = FixedStroke::from_pair(registration, Abs::pt(CROP_MARK_THICKNESS));
+    let start = bleed + Abs::pt(CROP_MARK_OFFSET);
+    let length = Abs::pt(CROP_MARK_LENGTH);
+    let size = frame.size();
+
+    for x in [Abs::zero(), size.x] {
+        for y in [Abs::zero(), size.y] {
+            // The marks point away from the page.
+            let dx = if x.is_zero() { -1.0 } else { 1.0 };
+            let dy = if y.is_zero() { -1.0 } else { 1.0 };
+            let horizontal = Geometry::Line(Point::with_x(length * dx));
+            let vertical = Geometry::Line(Point::with_y(length * dy));
+            frame.push(
+                Point::new(x + start * dx, y),
+                FrameItem::Shape(horizontal.stroked(stroke.clone()), Span::detached()),
+            );
+            frame.push(
+                Point::new(x, y + start * dy),
+                FrameItem::Shape(vertical.stroked(stroke.clone()), Span::detached()),
+            );
+        }
+    }
+}
+
+/// The space around the bleed box that is reserved for crop marks.
+fn slug(page: &Page) -> Abs {
+    if page.crop_marks {
+        Abs::pt(CROP_MARK_OFFSET + CROP_MARK_LENGTH)
+    } else {
+        Abs::zero()
+    }
+}
+
 /// Allocate a reference for each exported page.
 pub fn alloc_page_refs(
     context: &WithResources,
@@ -183,7 +258,14 @@ fn write_page(
 
     let w = page.content.size.x.to_f32();
     let h = page.content.size.y.to_f32();
-    page_writer.media_box(Rect::new(0.0, 0.0, w, h));
+    let doc_page = &ctx.document.pages[i];
+    let bleed = doc_page.bleed.to_f32();
+    let media = bleed + slug(doc_page).to_f32();
+    page_writer.media_box(Rect::new(-media, -media, w + media, h + media));
+    if media > 0.0 {
+        page_writer.bleed_box(Rect::new(-bleed, -bleed, w + bleed, h + bleed));
+        page_writer.trim_box(Rect::new(0.0, 0.0, w, h));
+    }
     page_writer.contents(content_id);
     page_writer.pair(Name(b"Resources"), ctx.resources.reference);
 
@@ -13,6 +13,7 @@ use ecow::{eco_format, EcoString};
 use pdf_writer::{Dict, Finish, Name, Ref};
 use subsetter::GlyphRemapper;
 use typst_library::diag::{SourceResult, StrResult};
+use typst_library::pdf::Layer;
 use typst_library::text::{Font, Lang};
 use typst_library::visualize::Image;
 use typst_syntax::Span;
@@ -72,6 +73,8 @@ pub struct Resources<R = Ref> {
     pub ext_gs: Remapper<ExtGState>,
     /// Deduplicates color glyphs.
     pub color_fonts: Option<Box<ColorFontMap<R>>>,
+    /// Deduplicates layers (optional content groups) used across the document.
+    pub layers: Remapper<Layer>,
 
     // The fields below do not correspond to actual resources that will be
     // written in a dictionary, but are more meta-data about resources that
@@ -125,6 +128,7 @@ impl Default for Resources<()> {
             patterns: None,
             ext_gs: Remapper::new("Gs"),
             color_fonts: None,
+            layers: Remapper::new("Oc"),
             languages: BTreeMap::new(),
             glyph_sets: HashMap::new(),
             color_glyph_sets: HashMap::new(),
@@ -153,6 +157,7 @@ impl Resources<()> {
                 .color_fonts
                 .zip(refs.color_fonts.as_ref())
                 .map(|(c, r)| Box::new(c.with_refs(r))),
+            layers: self.layers,
             languages: self.languages,
             glyph_sets: self.glyph_sets,
             color_glyph_sets: self.color_glyph_sets,
@@ -283,6 +288,11 @@ pub fn write_resource_dictionaries(ctx: &WithEverything) -> SourceResult<(PdfChu
         res_dict.pair(Name(b"ExtGState"), ext_gs_states_ref);
         res_dict.pair(Name(b"ColorSpace"), color_spaces_ref);
 
+        if !resources.layers.is_empty() {
+            let mut properties = res_dict.insert(Name(b"Properties")).dict();
+            resources.layers.write(&ctx.references.layers.refs, &mut properties);
+        }
+
         // TODO: can't this be an indirect reference too?
         let mut fonts_dict = res_dict.fonts();
         resources.fonts.write(&ctx.references.fonts, &mut fonts_dict);
@@ -338,6 +348,16 @@ where
         self.to_items.iter()
     }
 
+    /// The number of items in this mapping.
+    pub fn len(&self) -> usize {
+        self.to_items.len()
+    }
+
+    /// Whether this mapping is empty.
+    pub fn is_empty(&self) -> bool {
+        self.to_items.is_empty()
+    }
+
     /// Write this list of items in a Resource dictionary.
     fn write(&self, mapping: &HashMap<T, Ref>, dict: &mut Dict) {
         for (number, item) in self.items().enumerate() {
@@ -0,0 +1,236 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use ecow::{eco_format, EcoString};
+use subsetter::GlyphRemapper;
+use ttf_parser::name_id;
+use typst_library::text::Font;
+
+use crate::{write_hex, Num};
+
+/// The maximum length of a string in the `sfnts` array. PostScript strings
+/// are limited to 65535 bytes, one of which is taken by the padding byte.
+const MAX_STRING_LEN: usize = 65534;
+
+/// The length of the tag that distinguishes font subsets.
+const SUBSET_TAG_LEN: usize = 6;
+
+/// The fonts that are embedded into a document as Type 42 fonts, with the
+/// glyphs used from them.
+#[derive(Default)]
+pub struct FontSet {
+    fonts: Vec<(Font, BTreeSet<u16>)>,
+    indices: HashMap<Font, usize>,
+}
+
+impl FontSet {
+    /// Add glyphs of a font to the set. Returns the index under which the
+    /// font is available in the `TypstDict` or `None` if the font can't be
+    /// embedded as a Type 42 font, i.e. because it has CFF outlines.
+    pub fn insert(
+        &mut self,
+        font: &Font,
+        glyphs: impl IntoIterator<Item = u16>,
+    ) -> Option<usize> {
+        if font.ttf().tables().glyf.is_none() {
+            return None;
+        }
+
+        let index = match self.indices.get(font) {
+            Some(&index) => index,
+            None => {
+                let index = self.fonts.len();
+                self.fonts.push((font.clone(), BTreeSet::new()));
+                self.indices.insert(font.clone(), index);
+                index
+            }
+        };
+
+        self.fonts[index].1.extend(glyphs);
+        Some(index)
+    }
+
+    /// Write the DSC comment listing the fonts.
+    pub fn write_supplied_resources(&self, out: &mut String) {
+        for i in 0..self.fonts.len() {
+            let prefix = if i == 0 { "%%DocumentSuppliedResources:" } else { "%%+" };
+            writeln!(out, "{prefix} font {}", self.name(i)).unwrap();
+        }
+    }
+
+    /// Define the fonts and make them available in the `TypstDict`.
+    #[typst_macros::time(name = "write fonts")]
+    pub fn write_fonts(&self, out: &mut String) {
+        for (i, (font, glyphs)) in self.fonts.iter().enumerate() {
+            let name = self.name(i);
+            let (data, mapping) = subset_font(font, glyphs);
+
+            writeln!(out, "%%BeginResource: font {name}").unwrap();
+            writeln!(out, "11 dict begin\n/FontName /{name} def").unwrap();
+            out.push_str("/FontType 42 def\n/PaintType 0 def\n");
+            out.push_str("/FontMatrix [1 0 0 1 0 0] def\n");
+
+            let bbox = font.ttf().global_bounding_box();
+            let upem = font.units_per_em();
+            writeln!(
+                out,
+                "/FontBBox [{} {} {} {}] def",
+                Num(f64::from(bbox.x_min) / upem),
+                Num(f64::from(bbox.y_min) / upem),
+                Num(f64::from(bbox.x_max) / upem),
+                Num(f64::from(bbox.y_max) / upem),
+            )
+            .unwrap();
+            out.push_str(
+                "/Encoding 256 array 0 1 255 { 1 index exch /.notdef put } for def\n",
+            );
+
+            // Glyphs are only ever shown by name, so every used glyph gets
+            // one derived from its original id.
+            writeln!(out, "/CharStrings {} dict dup begin", mapping.len() + 1).unwrap();
+            out.push_str("/.notdef 0 def\n");
+            for (old, new) in mapping {
+                writeln!(out, "/g{old} {new} def").unwrap();
+            }
+            out.push_str("end def\n");
+
+            out.push_str("/sfnts [\n");
+            for chunk in split_sfnts(&data) {
+                let mut padded = chunk.to_vec();
+                padded.push(0);
+                write_hex(out, &padded);
+                out.push('\n');
+            }
+            out.push_str("] def\n");
+            out.push_str("FontName currentdict end definefont pop\n");
+            out.push_str("%%EndResource\n");
+            writeln!(out, "TypstDict /F{i} /{name} findfont put").unwrap();
+        }
+    }
+
+    /// The PostScript name of a font subset.
+    fn name(&self, index: usize) -> EcoString {
+        let (font, glyphs) = &self.fonts[index];
+        let postscript_name = font.find_name(name_id::POST_SCRIPT_NAME);
+        let name: String = postscript_name
+            .as_deref()
+            .unwrap_or("unknown")
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+            .take(64)
+            .collect();
+
+        // Hash the name and the glyphs to produce a fairly unique subset tag.
+        const BASE: u128 = 26;
+        let mut hash = typst_utils::hash128(&(&name, glyphs));
+        let mut tag = [b'A'; SUBSET_TAG_LEN];
+        for l in tag.iter_mut() {
+            *l = b'A' + (hash % BASE) as u8;
+            hash /= BASE;
+        }
+
+        eco_format!("{}+{name}", std::str::from_utf8(&tag).unwrap())
+    }
+}
+
+/// Subset a font to the given glyphs. Returns the font data and the mapping
+/// from old to new glyph ids.
+///
+/// If subsetting fails, the full font is embedded instead.
+fn subset_font(font: &Font, glyphs: &BTreeSet<u16>) -> (Vec<u8>, Vec<(u16, u16)>) {
+    let mut remapper = GlyphRemapper::new();
+    let mapping = glyphs.iter().map(|&id| (id, remapper.remap(id))).collect();
+    match subsetter::subset(font.data(), font.index(), &remapper) {
+        Ok(data) => (data, mapping),
+        Err(_) => (font.data().to_vec(), glyphs.iter().map(|&id| (id, id)).collect()),
+    }
+}
+
+/// Split the font data into strings for the `sfnts` array.
+///
+/// The strings must begin at table boundaries or, within the `glyf` table, at
+/// glyph boundaries.
+fn split_sfnts(data: &[u8]) -> Vec<&[u8]> {
+    let mut breaks = sfnts_breaks(data).unwrap_or_default();
+    breaks.retain(|&offset| offset < data.len());
+    breaks.push(data.len());
+    breaks.sort_unstable();
+    breaks.dedup();
+
+    let mut chunks = vec![];
+    let mut start = 0;
+    let mut last = 0;
+    for end in breaks {
+        if end - start > MAX_STRING_LEN {
+            // Fall back to splitting anywhere if a single table or glyph is
+            // too long, which should not happen in practice.
+            if last > start {
+                chunks.push(&data[start..last]);
+                start = last;
+            }
+            while end - start > MAX_STRING_LEN {
+                chunks.push(&data[start..start + MAX_STRING_LEN]);
+                start += MAX_STRING_LEN;
+            }
+        }
+        last = end;
+    }
+
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+
+    chunks
+}
+
+/// Find the offsets at which the font data may be split.
+fn sfnts_breaks(data: &[u8]) -> Option<Vec<usize>> {
+    let num_tables = read_u16(data, 4)?;
+    let mut breaks = vec![];
+    let mut glyf = None;
+    let mut loca = None;
+    let mut head = None;
+    let mut maxp = None;
+
+    for i in 0..usize::from(num_tables) {
+        let record = 12 + 16 * i;
+        let tag = data.get(record..record + 4)?;
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        breaks.push(offset);
+        match tag {
+            b"glyf" => glyf = Some(offset),
+            b"loca" => loca = Some(offset),
+            b"head" => head = Some(offset),
+            b"maxp" => maxp = Some(offset),
+            _ => {}
+        }
+        breaks.push(offset + length);
+    }
+
+    // Allow splitting the glyph table between glyphs.
+    if let (Some(glyf), Some(loca), Some(head), Some(maxp)) = (glyf, loca, head, maxp) {
+        let long = read_u16(data, head + 50)? != 0;
+        let num_glyphs = usize::from(read_u16(data, maxp + 4)?);
+        for i in 0..=num_glyphs {
+            let offset = if long {
+                read_u32(data, loca + 4 * i)? as usize
+            } else {
+                2 * usize::from(read_u16(data, loca + 2 * i)?)
+            };
+            breaks.push(glyf + offset);
+        }
+    }
+
+    Some(breaks)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,226 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use tiny_skia as sk;
+use typst_library::layout::Size;
+use typst_library::visualize::{Image, ImageKind};
+
+use crate::{Num, PsRenderer};
+
+/// The resolution at which SVG images are rasterized.
+const SVG_DPI: f64 = 300.0;
+
+/// The maximum number of pixels of a rasterized SVG image.
+const SVG_MAX_PIXELS: f64 = 4096.0 * 4096.0;
+
+/// The maximum length of a line of ASCII85 data.
+const LINE_LEN: usize = 80;
+
+impl PsRenderer<'_> {
+    /// Render a raster or SVG image.
+    ///
+    /// The image data is defined once per page and read from a reusable
+    /// stream whenever the image is drawn.
+    pub(super) fn render_image(&mut self, image: &Image, size: Size) {
+        let (width, height) = pixel_size(image, size);
+        let encoded = encode_image(image, width, height);
+        let key = typst_utils::hash128(&(image, width, height));
+        let count = self.images.len();
+        let index = *self.images.entry(key).or_insert_with(|| {
+            writeln!(self.defs, "/Im{count} RS\n {}~>\ndef", encoded.data).unwrap();
+            count
+        });
+
+        writeln!(
+            self.buf,
+            "Im{index} 0 setfileposition\n/DeviceRGB setcolorspace\n{} {} scale",
+            Num(size.x.to_pt()),
+            Num(size.y.to_pt()),
+        )
+        .unwrap();
+
+        let image_dict = format!(
+            "/Width {width} /Height {height} /BitsPerComponent 8 \
+             /ImageMatrix [{width} 0 0 {height} 0 0]"
+        );
+
+        if encoded.alpha {
+            writeln!(
+                self.buf,
+                "<< /ImageType 3 /InterleaveType 1\n\
+                 /DataDict << /ImageType 1 {image_dict} /Decode [0 1 0 1 0 1] \
+                 /DataSource Im{index} >>\n\
+                 /MaskDict << /ImageType 1 {image_dict} /Decode [1 0] >>\n\
+                 >> image"
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                self.buf,
+                "<< /ImageType 1 {image_dict} /Decode [0 1 0 1 0 1] \
+                 /DataSource Im{index} >> image"
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// An image prepared for embedding.
+struct EncodedImage {
+    /// Whether the samples are interleaved with a binary mask.
+    alpha: bool,
+    /// The compressed and ASCII85-encoded samples.
+    data: String,
+}
+
+/// The number of pixels with which an image is embedded.
+///
+/// Raster images keep their resolution, while SVGs are rasterized according
+/// to the size at which they are placed.
+fn pixel_size(image: &Image, size: Size) -> (u32, u32) {
+    match image.kind() {
+        ImageKind::Raster(raster) => (raster.width(), raster.height()),
+        ImageKind::Svg(_) => {
+            let mut w = size.x.to_inches() * SVG_DPI;
+            let mut h = size.y.to_inches() * SVG_DPI;
+            if w * h > SVG_MAX_PIXELS {
+                let factor = (SVG_MAX_PIXELS / (w * h)).sqrt();
+                w *= factor;
+                h *= factor;
+            }
+            (w.ceil().max(1.0) as u32, h.ceil().max(1.0) as u32)
+        }
+    }
+}
+
+/// Convert an image into its samples and encode them.
+#[comemo::memoize]
+fn encode_image(image: &Image, width: u32, height: u32) -> Arc<EncodedImage> {
+    let (alpha, samples) = match image.kind() {
+        ImageKind::Raster(raster) => {
+            let dynamic = raster.dynamic();
+            if dynamic.color().has_alpha() {
+                let rgba = dynamic.to_rgba8();
+                (true, interleave(rgba.pixels().map(|pixel| pixel.0)))
+            } else {
+                (false, dynamic.to_rgb8().into_raw())
+            }
+        }
+        ImageKind::Svg(svg) => {
+            let mut pixmap = sk::Pixmap::new(width, height).unwrap();
+            let tree = svg.tree();
+            let ts = sk::Transform::from_scale(
+                width as f32 / tree.size().width(),
+                height as f32 / tree.size().height(),
+            );
+            resvg::render(tree, ts, &mut pixmap.as_mut());
+            let pixels = pixmap.pixels().iter().map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            });
+            (true, interleave(pixels))
+        }
+    };
+
+    let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&samples, 6);
+    Arc::new(EncodedImage { alpha, data: ascii85(&compressed) })
+}
+
+/// Interleave the color samples with a mask derived from the alpha channel.
+///
+/// PostScript only supports binary masks, so partially transparent pixels
+/// are painted if they are at least half opaque.
+fn interleave(pixels: impl Iterator<Item = [u8; 4]>) -> Vec<u8> {
+    pixels
+        .flat_map(|[r, g, b, a]| [if a >= 128 { 255 } else { 0 }, r, g, b])
+        .collect()
+}
+
+/// Encode data with the ASCII base-85 encoding, without the end-of-data
+/// marker.
+fn ascii85(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() * 5 / 4 + data.len() / LINE_LEN + 1);
+    let mut line_start = 0;
+    for chunk in data.chunks(4) {
+        let mut bytes = [0; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(bytes);
+
+        if chunk.len() == 4 && value == 0 {
+            out.push('z');
+        } else {
+            let mut digits = [0; 5];
+            for digit in digits.iter_mut().rev() {
+                *digit = b'!' + (value % 85) as u8;
+                value /= 85;
+            }
+            for &digit in &digits[..chunk.len() + 1] {
+                out.push(digit as char);
+            }
+        }
+
+        if out.len() - line_start >= LINE_LEN {
+            // Lines are indented because lines starting with a percent sign
+            // could be mistaken for DSC comments.
+            out.push_str("\n ");
+            line_start = out.len();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii85() {
+        assert_eq!(ascii85(b""), "");
+        assert_eq!(ascii85(b"Man "), "9jqo^");
+        assert_eq!(ascii85(b"sure"), "F*2M7");
+        assert_eq!(ascii85(&[0xFF; 4]), "s8W-!");
+    }
+
+    #[test]
+    fn test_ascii85_partial_group() {
+        // A final group of n bytes is encoded with n + 1 digits.
+        assert_eq!(ascii85(b"Man is"), "9jqo^Bla");
+        assert_eq!(ascii85(b"M"), "9`");
+        assert_eq!(ascii85(b"Ma"), "9jn");
+        assert_eq!(ascii85(b"Man"), "9jqo");
+    }
+
+    #[test]
+    fn test_ascii85_zero_group() {
+        // Only complete groups of zeros are abbreviated.
+        assert_eq!(ascii85(&[0; 4]), "z");
+        assert_eq!(ascii85(&[0; 8]), "zz");
+        assert_eq!(ascii85(&[0; 3]), "!!!!");
+        assert_eq!(ascii85(b"a\0\0\0\0b"), "@/p9-!+G");
+    }
+
+    #[test]
+    fn test_ascii85_line_breaks() {
+        let data: Vec<u8> = (0..=255).cycle().take(1001).collect();
+        let encoded = ascii85(&data);
+        assert!(encoded.lines().count() > 10);
+        for (i, line) in encoded.lines().enumerate() {
+            assert!(line.len() < LINE_LEN + 5, "line {i} is too long");
+            assert_eq!(line.starts_with(' '), i > 0);
+        }
+
+        // Groups are encoded independently, so removing the line breaks must
+        // yield the concatenation of the groups.
+        let groups: String = data.chunks(4).map(ascii85).collect();
+        assert_eq!(encoded.replace("\n ", ""), groups);
+    }
+
+    #[test]
+    fn test_interleave() {
+        let pixels = [[1, 2, 3, 0], [4, 5, 6, 127], [7, 8, 9, 128], [10, 11, 12, 255]];
+        assert_eq!(
+            interleave(pixels.into_iter()),
+            [0, 1, 2, 3, 0, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12],
+        );
+    }
+}
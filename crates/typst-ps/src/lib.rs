@@ -0,0 +1,534 @@
+//! Exporting of Typst documents into PostScript.
+
+mod font;
+mod image;
+mod paint;
+mod shape;
+mod text;
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter, Write};
+
+use typst_library::layout::{
+    Abs, Frame, FrameItem, FrameKind, GroupItem, Page, PageRanges, Point, Size, Transform,
+};
+use typst_library::model::Document;
+use typst_library::visualize::Geometry;
+
+use crate::font::FontSet;
+
+/// Export a document into a PostScript file.
+///
+/// The file conforms to PostScript Language Level 3 and to the Document
+/// Structuring Conventions. TrueType fonts are embedded as Type 42 font
+/// subsets, all other glyphs are drawn as paths.
+///
+/// If `page_ranges` is not `None`, only the pages in these ranges are
+/// exported.
+#[typst_macros::time(name = "ps")]
+pub fn ps(document: &Document, page_ranges: Option<&PageRanges>) -> Vec<u8> {
+    let pages: Vec<&Page> = document
+        .pages
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            page_ranges.map_or(true, |ranges| ranges.includes_page_index(*i))
+        })
+        .map(|(_, page)| page)
+        .collect();
+    write_file(&pages, document.info.title.as_deref(), false)
+}
+
+/// Export a page into an Encapsulated PostScript file.
+///
+/// EPS files contain a single page and can be placed into other documents.
+#[typst_macros::time(name = "eps")]
+pub fn eps(page: &Page) -> Vec<u8> {
+    write_file(&[page], None, true)
+}
+
+/// The procedures that are available to all pages.
+const PROLOG: &str = "\
+/TypstDict 16 dict def
+TypstDict begin
+/m { moveto } bind def
+/l { lineto } bind def
+/c { curveto } bind def
+/h { closepath } bind def
+/G { 3 1 roll moveto glyphshow } bind def
+/RS {
+  currentfile /ASCII85Decode filter
+  << /Filter /FlateDecode >> /ReusableStreamDecode filter
+} bind def
+end
+";
+
+/// Write the pages into a PostScript file.
+fn write_file(pages: &[&Page], title: Option<&str>, encapsulated: bool) -> Vec<u8> {
+    // The pages are rendered first because the fonts, which are defined
+    // before the pages, are subset to the glyphs used on them.
+    let mut fonts = FontSet::default();
+    let bodies: Vec<String> = pages
+        .iter()
+        .map(|page| {
+            let mut renderer = PsRenderer::new(&mut fonts);
+            renderer.render_page(page);
+            renderer.finish()
+        })
+        .collect();
+
+    let bbox = pages
+        .iter()
+        .fold(Size::zero(), |bbox, page| bbox.max(page.frame.size()));
+
+    let mut out = String::new();
+    if encapsulated {
+        out.push_str("%!PS-Adobe-3.0 EPSF-3.0\n");
+    } else {
+        out.push_str("%!PS-Adobe-3.0\n");
+    }
+    writeln!(out, "%%Creator: Typst {}", env!("CARGO_PKG_VERSION")).unwrap();
+    if let Some(title) = title {
+        writeln!(out, "%%Title: {}", PsString(title)).unwrap();
+    }
+    out.push_str("%%LanguageLevel: 3\n");
+    writeln!(
+        out,
+        "%%BoundingBox: 0 0 {} {}",
+        bbox.x.to_pt().ceil(),
+        bbox.y.to_pt().ceil()
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "%%HiResBoundingBox: 0 0 {} {}",
+        Num(bbox.x.to_pt()),
+        Num(bbox.y.to_pt())
+    )
+    .unwrap();
+    if !encapsulated {
+        writeln!(out, "%%Pages: {}", pages.len()).unwrap();
+    }
+    out.push_str("%%DocumentData: Clean7Bit\n");
+    fonts.write_supplied_resources(&mut out);
+    out.push_str("%%EndComments\n");
+
+    out.push_str("%%BeginProlog\n");
+    out.push_str(PROLOG);
+    out.push_str("%%EndProlog\n");
+
+    out.push_str("%%BeginSetup\n");
+    fonts.write_fonts(&mut out);
+    out.push_str("%%EndSetup\n");
+
+    for (i, (page, body)) in pages.iter().zip(bodies).enumerate() {
+        let size = page.frame.size();
+        if !encapsulated {
+            writeln!(out, "%%Page: {0} {0}", i + 1).unwrap();
+            writeln!(
+                out,
+                "%%PageBoundingBox: 0 0 {} {}",
+                size.x.to_pt().ceil(),
+                size.y.to_pt().ceil()
+            )
+            .unwrap();
+            out.push_str("%%BeginPageSetup\n");
+            writeln!(
+                out,
+                "<< /PageSize [{} {}] >> setpagedevice",
+                Num(size.x.to_pt()),
+                Num(size.y.to_pt())
+            )
+            .unwrap();
+            out.push_str("%%EndPageSetup\n");
+        }
+
+        // Typst's y-axis points downwards.
+        out.push_str("save\nTypstDict begin\n");
+        writeln!(out, "0 {} translate 1 -1 scale", Num(size.y.to_pt())).unwrap();
+        out.push_str(&body);
+        out.push_str("end\nrestore\nshowpage\n");
+
+        if !encapsulated {
+            out.push_str("%%PageTrailer\n");
+        }
+    }
+
+    out.push_str("%%Trailer\n%%EOF\n");
+    out.into_bytes()
+}
+
+/// Renders frames into PostScript code.
+struct PsRenderer<'a> {
+    /// The content of the page.
+    buf: String,
+    /// Definitions that must precede the content, i.e. the page's images.
+    defs: String,
+    /// The fonts of the document, which collect the glyphs used on the page.
+    fonts: &'a mut FontSet,
+    /// The names of the images defined in `defs`, by the hash of the image
+    /// and its pixel size.
+    images: HashMap<u128, usize>,
+}
+
+/// Contextual information for rendering.
+#[derive(Clone, Copy)]
+struct State {
+    /// The transform of the current item, relative to the first hard frame in
+    /// the hierarchy.
+    transform: Transform,
+    /// The size of the first hard frame in the hierarchy.
+    size: Size,
+}
+
+impl State {
+    fn new(size: Size) -> Self {
+        Self { transform: Transform::identity(), size }
+    }
+
+    /// Pre translate the current item's transform.
+    fn pre_translate(self, pos: Point) -> Self {
+        self.pre_concat(Transform::translate(pos.x, pos.y))
+    }
+
+    /// Pre concat the current item's transform.
+    fn pre_concat(self, transform: Transform) -> Self {
+        Self {
+            transform: self.transform.pre_concat(transform),
+            ..self
+        }
+    }
+}
+
+impl<'a> PsRenderer<'a> {
+    fn new(fonts: &'a mut FontSet) -> Self {
+        Self {
+            buf: String::new(),
+            defs: String::new(),
+            fonts,
+            images: HashMap::new(),
+        }
+    }
+
+    /// Render a page including its background.
+    fn render_page(&mut self, page: &Page) {
+        let state = State::new(page.frame.size());
+        if let Some(fill) = page.fill_or_white() {
+            let shape = Geometry::Rect(page.frame.size()).filled(fill);
+            self.render_shape(state, &shape);
+        }
+
+        self.render_frame(state, &page.frame);
+    }
+
+    /// Render a frame.
+    fn render_frame(&mut self, state: State, frame: &Frame) {
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    // Layers that are hidden by default in PDF viewers are
+                    // omitted.
+                    if group.layer.as_ref().is_some_and(|layer| !layer.visible) {
+                        continue;
+                    }
+                }
+                FrameItem::Link(_, _) | FrameItem::Tag(_) => continue,
+                _ => {}
+            }
+
+            self.buf.push_str("gsave\n");
+            if *pos != Point::zero() {
+                writeln!(
+                    self.buf,
+                    "{} {} translate",
+                    Num(pos.x.to_pt()),
+                    Num(pos.y.to_pt())
+                )
+                .unwrap();
+            }
+
+            let state = state.pre_translate(*pos);
+            match item {
+                FrameItem::Group(group) => self.render_group(state, group),
+                FrameItem::Text(text) => self.render_text(state, text),
+                FrameItem::Shape(shape, _) => self.render_shape(state, shape),
+                FrameItem::Image(image, size, _) => self.render_image(image, *size),
+                FrameItem::Link(_, _) | FrameItem::Tag(_) => {}
+            }
+
+            self.buf.push_str("grestore\n");
+        }
+    }
+
+    /// Render a group with its transform and clip path.
+    fn render_group(&mut self, state: State, group: &GroupItem) {
+        let state = match group.frame.kind() {
+            FrameKind::Soft => state.pre_concat(group.transform),
+            FrameKind::Hard => State::new(group.frame.size()),
+        };
+
+        if !group.transform.is_identity() {
+            writeln!(self.buf, "{} concat", PsMatrix(group.transform)).unwrap();
+        }
+
+        if let Some(clip_path) = &group.clip_path {
+            shape::write_path(&mut self.buf, clip_path);
+            self.buf.push_str("clip newpath\n");
+        }
+
+        self.render_frame(state, &group.frame);
+    }
+
+    /// Render a frame into a separate buffer, e.g. for the procedure of a
+    /// tiling pattern.
+    fn render_detached(&mut self, state: State, frame: &Frame) -> String {
+        let outer = std::mem::take(&mut self.buf);
+        self.render_frame(state, frame);
+        std::mem::replace(&mut self.buf, outer)
+    }
+
+    /// Combine the definitions and the content.
+    fn finish(mut self) -> String {
+        self.defs.push_str(&self.buf);
+        self.defs
+    }
+}
+
+/// Displays a number with limited precision as PostScript does not need more
+/// and short numbers keep files small.
+struct Num(f64);
+
+impl Display for Num {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let value = if self.0.is_finite() { self.0 } else { 0.0 };
+        let rounded = (value * 10000.0).round() / 10000.0;
+        if rounded == rounded.trunc() && rounded.abs() < 1e9 {
+            write!(f, "{}", rounded as i64)
+        } else {
+            write!(f, "{rounded}")
+        }
+    }
+}
+
+/// Displays as a PostScript matrix.
+struct PsMatrix(Transform);
+
+impl Display for PsMatrix {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Transform { sx, ky, kx, sy, tx, ty } = self.0;
+        write!(
+            f,
+            "[{} {} {} {} {} {}]",
+            Num(sx.get()),
+            Num(ky.get()),
+            Num(kx.get()),
+            Num(sy.get()),
+            Num(tx.to_pt()),
+            Num(ty.to_pt())
+        )
+    }
+}
+
+/// Displays as a PostScript string literal, escaping all characters outside
+/// of printable ASCII.
+struct PsString<'a>(&'a str);
+
+impl Display for PsString<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_char('(')?;
+        for byte in self.0.bytes() {
+            match byte {
+                b'(' | b')' | b'\\' => write!(f, "\\{}", byte as char)?,
+                0x20..=0x7E => f.write_char(byte as char)?,
+                _ => write!(f, "\\{byte:03o}")?,
+            }
+        }
+        f.write_char(')')
+    }
+}
+
+/// Write binary data as a hexadecimal string, broken into lines.
+fn write_hex(buf: &mut String, data: &[u8]) {
+    buf.push('<');
+    for (i, byte) in data.iter().enumerate() {
+        if i > 0 && i % 32 == 0 {
+            buf.push('\n');
+        }
+        write!(buf, "{byte:02X}").unwrap();
+    }
+    buf.push('>');
+}
+
+/// Replace zero extents with one point, so that paint transforms stay
+/// invertible.
+fn nonzero(mut size: Size) -> Size {
+    if size.x.to_pt() == 0.0 {
+        size.x = Abs::pt(1.0);
+    }
+    if size.y.to_pt() == 0.0 {
+        size.y = Abs::pt(1.0);
+    }
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_library::foundations::{Bytes, Content, Smart};
+    use typst_library::introspection::Introspector;
+    use typst_library::layout::Em;
+    use typst_library::model::DocumentInfo;
+    use typst_library::text::{Font, Glyph, Lang, TextItem};
+    use typst_library::visualize::{Color, Image, ImageFormat, VectorFormat};
+    use typst_syntax::Span;
+
+    use super::*;
+
+    /// A page with a line of text and an SVG image, which is embedded with a
+    /// mask.
+    fn page() -> Page {
+        let font = typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .find(|font| font.info().family == "DejaVu Sans Mono")
+            .unwrap();
+        let id = font.ttf().glyph_index('H').unwrap().0;
+        let text = TextItem {
+            font,
+            size: Abs::pt(12.0),
+            fill: Color::BLACK.into(),
+            stroke: None,
+            lang: Lang::ENGLISH,
+            region: None,
+            text: "H".into(),
+            glyphs: vec![Glyph {
+                id,
+                x_advance: Em::new(0.6),
+                x_offset: Em::zero(),
+                range: 0..1,
+                span: (Span::detached(), 0),
+            }],
+        };
+
+        let svg = b"<svg xmlns='http://www.w3.org/2000/svg' width='4' height='4'>\
+                    <rect width='2' height='4' fill='red'/></svg>";
+        let image = Image::new(
+            Bytes::from_static(svg),
+            ImageFormat::Vector(VectorFormat::Svg),
+            None,
+        )
+        .unwrap();
+
+        let mut frame = Frame::hard(Size::new(Abs::pt(100.5), Abs::pt(50.0)));
+        frame.push(Point::with_y(Abs::pt(20.0)), FrameItem::Text(text));
+        frame.push(
+            Point::with_x(Abs::pt(50.0)),
+            FrameItem::Image(image, Size::splat(Abs::pt(20.0)), Span::detached()),
+        );
+        Page {
+            frame,
+            fill: Smart::Auto,
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+            bleed: Abs::zero(),
+            crop_marks: false,
+        }
+    }
+
+    /// Check that the given lines appear in the file in this order.
+    #[track_caller]
+    fn assert_in_order(file: &str, lines: &[&str]) {
+        let mut rest = file;
+        for line in lines {
+            let Some(i) = rest.find(line) else {
+                panic!("missing or out of order: {line:?}");
+            };
+            rest = &rest[i + line.len()..];
+        }
+    }
+
+    /// Check the parts shared by PostScript and EPS files.
+    #[track_caller]
+    fn assert_common(file: &str) {
+        // The file promises to be clean 7-bit data and DSC lines are limited
+        // to 255 characters.
+        assert!(file.is_ascii());
+        assert!(file.lines().all(|line| line.len() <= 255));
+        assert!(file.ends_with("%%Trailer\n%%EOF\n"));
+
+        assert_in_order(
+            file,
+            &[
+                "%%Creator: Typst ",
+                "%%LanguageLevel: 3\n",
+                "%%BoundingBox: 0 0 101 50\n",
+                "%%HiResBoundingBox: 0 0 100.5 50\n",
+                "%%DocumentSuppliedResources: font ",
+                "%%EndComments\n",
+                "%%BeginProlog\n",
+                "%%EndProlog\n",
+                "%%BeginSetup\n",
+                "%%BeginResource: font ",
+                "/FontType 42 def\n",
+                "/sfnts [\n",
+                "%%EndResource\n",
+                "TypstDict /F0 ",
+                "%%EndSetup\n",
+                "/Im0 RS\n",
+                "F0 [12 0 0 -12 0 0] makefont setfont\n",
+                "Im0 0 setfileposition\n",
+                "<< /ImageType 3 /InterleaveType 1\n",
+                "/DataSource Im0 >>\n/MaskDict ",
+                "showpage\n",
+            ],
+        );
+
+        // The subset is named after the font.
+        let name = file.split("%%BeginResource: font ").nth(1).unwrap();
+        assert!(name.starts_with(char::is_uppercase));
+        assert_eq!(&name[6..22], "+DejaVuSansMono\n");
+    }
+
+    #[test]
+    fn test_ps_structure() {
+        let document = Document {
+            pages: vec![page()],
+            info: DocumentInfo {
+                title: Some("Report (Draft)".into()),
+                ..DocumentInfo::default()
+            },
+            introspector: Introspector::default(),
+        };
+
+        let file = String::from_utf8(ps(&document, None)).unwrap();
+        assert!(file.starts_with("%!PS-Adobe-3.0\n"));
+        assert_common(&file);
+        assert_in_order(
+            &file,
+            &[
+                "%%Title: (Report \\(Draft\\))\n",
+                "%%Pages: 1\n",
+                "%%EndComments\n",
+                "%%Page: 1 1\n",
+                "%%PageBoundingBox: 0 0 101 50\n",
+                "%%BeginPageSetup\n",
+                "<< /PageSize [100.5 50] >> setpagedevice\n",
+                "%%EndPageSetup\n",
+                "0 50 translate 1 -1 scale\n",
+                "showpage\n%%PageTrailer\n",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_eps_structure() {
+        let file = String::from_utf8(eps(&page())).unwrap();
+        assert!(file.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n"));
+        assert_common(&file);
+
+        // EPS files must not change the page device and have no page
+        // comments as they are placed into other documents.
+        assert!(!file.contains("setpagedevice"));
+        assert!(!file.contains("%%Page"));
+        assert!(!file.contains("%%Title"));
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt::Write;
+
+use typst_library::layout::{Point, Quadrant, Ratio, Size, Transform};
+use typst_library::visualize::{
+    Color, ColorSpace, Gradient, Paint, Pattern, RatioOrAngle, RelativeTo,
+};
+
+use crate::{nonzero, write_hex, Num, PsMatrix, PsRenderer, State};
+
+/// The number of samples per dimension for conic gradients.
+const CONIC_SAMPLES: usize = 64;
+
+impl PsRenderer<'_> {
+    /// Make a paint the current color. Returns `false` if the paint is fully
+    /// transparent, in which case nothing needs to be painted.
+    ///
+    /// The `origin` and `size` describe the bounding box of the painted item,
+    /// relative to which gradients and patterns are placed by default.
+    pub(super) fn write_paint(
+        &mut self,
+        state: State,
+        paint: &Paint,
+        origin: Point,
+        size: Size,
+        on_text: bool,
+    ) -> bool {
+        match paint {
+            Paint::Solid(color) => write_color(&mut self.buf, *color),
+            Paint::Gradient(gradient) => {
+                let (ts, size) = match gradient.unwrap_relative(on_text) {
+                    RelativeTo::Self_ => {
+                        let size = nonzero(size);
+                        (Transform::translate(origin.x, origin.y), size)
+                    }
+                    RelativeTo::Parent => (invert(state.transform), nonzero(state.size)),
+                };
+                let ts = ts.pre_concat(Transform::scale(
+                    Ratio::new(size.x.to_pt()),
+                    Ratio::new(size.y.to_pt()),
+                ));
+                self.write_gradient(gradient, ts, size);
+                true
+            }
+            Paint::Pattern(pattern) => {
+                let ts = match pattern.unwrap_relative(on_text) {
+                    RelativeTo::Self_ => Transform::translate(origin.x, origin.y),
+                    RelativeTo::Parent => invert(state.transform),
+                };
+                self.write_pattern(pattern, ts);
+                true
+            }
+        }
+    }
+
+    /// Set a gradient as a shading pattern.
+    ///
+    /// The transform maps the unit square onto the gradient's bounding box,
+    /// whose size is needed for aspect ratio correction.
+    fn write_gradient(&mut self, gradient: &Gradient, ts: Transform, size: Size) {
+        self.buf
+            .push_str("<< /PatternType 2 /Shading << /ColorSpace /DeviceRGB ");
+        match gradient {
+            Gradient::Linear(linear) => {
+                let angle =
+                    Gradient::correct_aspect_ratio(linear.angle, size.aspect_ratio());
+                let (mut sin, mut cos) = (angle.sin(), angle.cos());
+
+                // Scale to edges of unit square.
+                let factor = cos.abs() + sin.abs();
+                sin *= factor;
+                cos *= factor;
+
+                let (x1, y1, x2, y2) = match angle.quadrant() {
+                    Quadrant::First => (0.0, 0.0, cos, sin),
+                    Quadrant::Second => (1.0, 0.0, cos + 1.0, sin),
+                    Quadrant::Third => (1.0, 1.0, cos + 1.0, sin + 1.0),
+                    Quadrant::Fourth => (0.0, 1.0, cos, sin + 1.0),
+                };
+
+                write!(
+                    self.buf,
+                    "/ShadingType 2 /Coords [{} {} {} {}] /Extend [true true]",
+                    Num(x1),
+                    Num(y1),
+                    Num(x2),
+                    Num(y2)
+                )
+                .unwrap();
+                self.buf.push_str("\n/Function ");
+                write_sampled_function(&mut self.buf, gradient);
+            }
+            Gradient::Radial(radial) => {
+                write!(
+                    self.buf,
+                    "/ShadingType 3 /Coords [{} {} {} {} {} {}] /Extend [true true]",
+                    Num(radial.focal_center.x.get()),
+                    Num(radial.focal_center.y.get()),
+                    Num(radial.focal_radius.get()),
+                    Num(radial.center.x.get()),
+                    Num(radial.center.y.get()),
+                    Num(radial.radius.get()),
+                )
+                .unwrap();
+                self.buf.push_str("\n/Function ");
+                write_sampled_function(&mut self.buf, gradient);
+            }
+            Gradient::Conic(_) => {
+                // PostScript has no conic shadings, so the gradient is sampled
+                // on a grid over the unit square.
+                let (w, h) = (size.x.to_pt() as f32, size.y.to_pt() as f32);
+                let max = (CONIC_SAMPLES - 1) as f32;
+                let mut samples = Vec::with_capacity(3 * CONIC_SAMPLES * CONIC_SAMPLES);
+                for y in 0..CONIC_SAMPLES {
+                    for x in 0..CONIC_SAMPLES {
+                        let point = (x as f32 / max * w, y as f32 / max * h);
+                        let [r, g, b, _] = gradient
+                            .sample_at(point, (w, h))
+                            .to_space(ColorSpace::Srgb)
+                            .to_vec4_u8();
+                        samples.extend([r, g, b]);
+                    }
+                }
+
+                write!(
+                    self.buf,
+                    "/ShadingType 1 /Domain [0 1 0 1]\n\
+                     /Function << /FunctionType 0 /Domain [0 1 0 1] \
+                     /Range [0 1 0 1 0 1] /Size [{CONIC_SAMPLES} {CONIC_SAMPLES}] \
+                     /BitsPerSample 8 /DataSource "
+                )
+                .unwrap();
+                write_hex(&mut self.buf, &samples);
+                self.buf.push_str(" >>");
+            }
+        }
+
+        writeln!(
+            self.buf,
+            " /AntiAlias {} >> >> {} makepattern setpattern",
+            gradient.anti_alias(),
+            PsMatrix(ts)
+        )
+        .unwrap();
+    }
+
+    /// Set a pattern as a tiling pattern whose procedure draws the pattern's
+    /// frame.
+    fn write_pattern(&mut self, pattern: &Pattern, ts: Transform) {
+        let size = pattern.size();
+        let step = size + pattern.spacing();
+        let body = self.render_detached(State::new(size), pattern.frame());
+        writeln!(
+            self.buf,
+            "<< /PatternType 1 /PaintType 1 /TilingType 1 /BBox [0 0 {} {}] \
+             /XStep {} /YStep {}\n/PaintProc {{ pop\n{body}}} >> {} makepattern setpattern",
+            Num(size.x.to_pt()),
+            Num(size.y.to_pt()),
+            Num(step.x.to_pt()),
+            Num(step.y.to_pt()),
+            PsMatrix(ts)
+        )
+        .unwrap();
+    }
+}
+
+/// Set a solid color. Returns `false` if the color is fully transparent.
+///
+/// PostScript has no notion of transparency, so any other alpha value is
+/// ignored.
+fn write_color(buf: &mut String, color: Color) -> bool {
+    if color.alpha().is_some_and(|alpha| alpha == 0.0) {
+        return false;
+    }
+
+    match color.space() {
+        ColorSpace::D65Gray => {
+            let [l, ..] = color.to_vec4();
+            writeln!(buf, "{} setgray", Num(l.into())).unwrap();
+        }
+        ColorSpace::Cmyk => {
+            let [c, m, y, k] = color.to_vec4();
+            writeln!(
+                buf,
+                "{} {} {} {} setcmykcolor",
+                Num(c.into()),
+                Num(m.into()),
+                Num(y.into()),
+                Num(k.into())
+            )
+            .unwrap();
+        }
+        _ => {
+            let [r, g, b, _] = color.to_space(ColorSpace::Srgb).to_vec4();
+            writeln!(
+                buf,
+                "{} {} {} setrgbcolor",
+                Num(r.into()),
+                Num(g.into()),
+                Num(b.into())
+            )
+            .unwrap();
+        }
+    }
+
+    true
+}
+
+/// Write a function that samples a gradient along its axis.
+fn write_sampled_function(buf: &mut String, gradient: &Gradient) {
+    let samples: Vec<u8> = (0..256)
+        .flat_map(|i| {
+            let t = RatioOrAngle::Ratio(Ratio::new(f64::from(i) / 255.0));
+            let [r, g, b, _] = gradient.sample(t).to_space(ColorSpace::Srgb).to_vec4_u8();
+            [r, g, b]
+        })
+        .collect();
+
+    buf.push_str(
+        "<< /FunctionType 0 /Domain [0 1] /Range [0 1 0 1 0 1] /Size [256] \
+         /BitsPerSample 8 /DataSource ",
+    );
+    write_hex(buf, &samples);
+    buf.push_str(" >>");
+}
+
+/// Invert a transform, falling back to the identity if it is singular.
+fn invert(ts: Transform) -> Transform {
+    ts.invert().unwrap_or(Transform::identity())
+}
@@ -0,0 +1,198 @@
+use std::fmt::Write;
+
+use typst_library::layout::{Point, Size};
+use typst_library::visualize::{
+    FillRule, FixedStroke, Geometry, LineCap, LineJoin, Paint, Path, PathItem, Shape,
+};
+
+use crate::{Num, PsRenderer, State};
+
+impl PsRenderer<'_> {
+    /// Render a geometrical shape.
+    pub(super) fn render_shape(&mut self, state: State, shape: &Shape) {
+        write_geometry(&mut self.buf, &shape.geometry);
+        let size = shape.geometry.bbox_size();
+
+        if let Some(paint) = &shape.fill {
+            let op = match shape.fill_rule {
+                FillRule::NonZero => "fill",
+                FillRule::EvenOdd => "eofill",
+            };
+            self.fill_current_path(state, paint, Point::zero(), size, false, op);
+        }
+
+        match &shape.stroke {
+            Some(stroke) if stroke.thickness.to_pt() > 0.0 => {
+                self.stroke_current_path(state, stroke, Point::zero(), size, false);
+            }
+            _ => self.buf.push_str("newpath\n"),
+        }
+    }
+
+    /// Fill the current path with a paint, but keep the path.
+    ///
+    /// The `origin`, `size`, and `on_text` are forwarded to
+    /// [`write_paint`](Self::write_paint).
+    pub(super) fn fill_current_path(
+        &mut self,
+        state: State,
+        paint: &Paint,
+        origin: Point,
+        size: Size,
+        on_text: bool,
+        op: &str,
+    ) {
+        self.buf.push_str("gsave\n");
+        if self.write_paint(state, paint, origin, size, on_text) {
+            writeln!(self.buf, "{op}").unwrap();
+        }
+        self.buf.push_str("grestore\n");
+    }
+
+    /// Stroke and consume the current path.
+    pub(super) fn stroke_current_path(
+        &mut self,
+        state: State,
+        stroke: &FixedStroke,
+        origin: Point,
+        size: Size,
+        on_text: bool,
+    ) {
+        if !self.write_paint(state, &stroke.paint, origin, size, on_text) {
+            self.buf.push_str("newpath\n");
+            return;
+        }
+
+        let cap = match stroke.cap {
+            LineCap::Butt => 0,
+            LineCap::Round => 1,
+            LineCap::Square => 2,
+        };
+        let join = match stroke.join {
+            LineJoin::Miter => 0,
+            LineJoin::Round => 1,
+            LineJoin::Bevel => 2,
+        };
+        writeln!(
+            self.buf,
+            "{} setlinewidth {cap} setlinecap {join} setlinejoin {} setmiterlimit",
+            Num(stroke.thickness.to_pt()),
+            Num(stroke.miter_limit.get().max(1.0)),
+        )
+        .unwrap();
+
+        if let Some(dash) = &stroke.dash {
+            self.buf.push('[');
+            for (i, length) in dash.array.iter().enumerate() {
+                if i > 0 {
+                    self.buf.push(' ');
+                }
+                write!(self.buf, "{}", Num(length.to_pt())).unwrap();
+            }
+            writeln!(self.buf, "] {} setdash", Num(dash.phase.to_pt())).unwrap();
+        }
+
+        self.buf.push_str("stroke\n");
+    }
+}
+
+/// Write the path of a geometry.
+fn write_geometry(buf: &mut String, geometry: &Geometry) {
+    match geometry {
+        Geometry::Line(target) => {
+            writeln!(buf, "0 0 m {} {} l", Num(target.x.to_pt()), Num(target.y.to_pt()))
+                .unwrap();
+        }
+        Geometry::Rect(size) => {
+            let (w, h) = (Num(size.x.to_pt()), Num(size.y.to_pt()));
+            writeln!(buf, "0 0 m {w} 0 l {w} {h} l 0 {h} l h").unwrap();
+        }
+        Geometry::Path(path) => write_path(buf, path),
+    }
+}
+
+/// Write a path.
+pub fn write_path(buf: &mut String, path: &Path) {
+    for item in &path.0 {
+        match item {
+            PathItem::MoveTo(p) => {
+                write!(buf, "{} {} m ", Num(p.x.to_pt()), Num(p.y.to_pt()))
+            }
+            PathItem::LineTo(p) => {
+                write!(buf, "{} {} l ", Num(p.x.to_pt()), Num(p.y.to_pt()))
+            }
+            PathItem::CubicTo(c1, c2, p) => write!(
+                buf,
+                "{} {} {} {} {} {} c ",
+                Num(c1.x.to_pt()),
+                Num(c1.y.to_pt()),
+                Num(c2.x.to_pt()),
+                Num(c2.y.to_pt()),
+                Num(p.x.to_pt()),
+                Num(p.y.to_pt()),
+            ),
+            PathItem::ClosePath => write!(buf, "h "),
+        }
+        .unwrap();
+    }
+    buf.push('\n');
+}
+
+/// Writes glyph outlines as a path.
+///
+/// Font units are scaled to points and flipped, as fonts have an upwards
+/// pointing y-axis.
+pub struct OutlineWriter<'a> {
+    pub buf: &'a mut String,
+    /// The horizontal offset of the glyph in points.
+    pub offset: f64,
+    /// The scaling from font units to points.
+    pub scale: f64,
+    /// The current point in font units, for converting quadratic curves.
+    pub last: (f32, f32),
+}
+
+impl OutlineWriter<'_> {
+    fn point(&self, x: f32, y: f32) -> (Num, Num) {
+        (Num(self.offset + f64::from(x) * self.scale), Num(-f64::from(y) * self.scale))
+    }
+}
+
+impl ttf_parser::OutlineBuilder for OutlineWriter<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (px, py) = self.point(x, y);
+        write!(self.buf, "{px} {py} m ").unwrap();
+        self.last = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (px, py) = self.point(x, y);
+        write!(self.buf, "{px} {py} l ").unwrap();
+        self.last = (x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        // Raise the degree of the curve, as PostScript only knows cubic ones.
+        let (x0, y0) = self.last;
+        self.curve_to(
+            x0 + 2.0 / 3.0 * (x1 - x0),
+            y0 + 2.0 / 3.0 * (y1 - y0),
+            x + 2.0 / 3.0 * (x1 - x),
+            y + 2.0 / 3.0 * (y1 - y),
+            x,
+            y,
+        );
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (ax, ay) = self.point(x1, y1);
+        let (bx, by) = self.point(x2, y2);
+        let (px, py) = self.point(x, y);
+        write!(self.buf, "{ax} {ay} {bx} {by} {px} {py} c ").unwrap();
+        self.last = (x, y);
+    }
+
+    fn close(&mut self) {
+        self.buf.push_str("h ");
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::Write;
+
+use ttf_parser::GlyphId;
+use typst_library::layout::{Abs, Point, Ratio, Size, Transform};
+use typst_library::text::color::{glyph_frame, should_outline};
+use typst_library::text::TextItem;
+
+use crate::shape::OutlineWriter;
+use crate::{Num, PsRenderer, State};
+
+impl PsRenderer<'_> {
+    /// Render a text run.
+    ///
+    /// Outline glyphs are shown with an embedded Type 42 font if possible and
+    /// drawn as paths otherwise. Color glyphs are rendered as frames.
+    pub(super) fn render_text(&mut self, state: State, text: &TextItem) {
+        let origin = Point::with_y(-text.size);
+        let size = Size::new(text.width(), text.size);
+
+        let type42 = match text.stroke {
+            None => self.fonts.insert(
+                &text.font,
+                text.glyphs
+                    .iter()
+                    .filter(|glyph| should_outline(&text.font, glyph))
+                    .map(|glyph| glyph.id),
+            ),
+            Some(_) => None,
+        };
+
+        if let Some(index) = type42 {
+            self.buf.push_str("gsave\n");
+            if self.write_paint(state, &text.fill, origin, size, true) {
+                let s = Num(text.size.to_pt());
+                writeln!(self.buf, "F{index} [{s} 0 0 -{s} 0 0] makefont setfont")
+                    .unwrap();
+                let mut x = Abs::zero();
+                for glyph in &text.glyphs {
+                    if should_outline(&text.font, glyph) {
+                        let offset = x + glyph.x_offset.at(text.size);
+                        writeln!(self.buf, "{} 0 /g{} G", Num(offset.to_pt()), glyph.id)
+                            .unwrap();
+                    }
+                    x += glyph.x_advance.at(text.size);
+                }
+            }
+            self.buf.push_str("grestore\n");
+        } else {
+            let scale = text.size.to_pt() / text.font.units_per_em();
+            let mut x = Abs::zero();
+            let mut empty = true;
+            for glyph in &text.glyphs {
+                if should_outline(&text.font, glyph) {
+                    let mut writer = OutlineWriter {
+                        buf: &mut self.buf,
+                        offset: (x + glyph.x_offset.at(text.size)).to_pt(),
+                        scale,
+                        last: (0.0, 0.0),
+                    };
+                    empty &= text
+                        .font
+                        .ttf()
+                        .outline_glyph(GlyphId(glyph.id), &mut writer)
+                        .is_none();
+                }
+                x += glyph.x_advance.at(text.size);
+            }
+
+            if !empty {
+                self.buf.push('\n');
+                self.fill_current_path(state, &text.fill, origin, size, true, "fill");
+                match &text.stroke {
+                    Some(stroke) if stroke.thickness.to_pt() > 0.0 => {
+                        self.stroke_current_path(state, stroke, origin, size, true);
+                    }
+                    _ => self.buf.push_str("newpath\n"),
+                }
+            }
+        }
+
+        self.render_color_glyphs(state, text);
+    }
+
+    /// Render the glyphs of a text run that are not plain outlines, e.g.
+    /// emoji, as frames.
+    fn render_color_glyphs(&mut self, state: State, text: &TextItem) {
+        let scale = text.size.to_pt() / text.font.units_per_em();
+        let mut x = Abs::zero();
+        for glyph in &text.glyphs {
+            if !should_outline(&text.font, glyph) {
+                let offset = x + glyph.x_offset.at(text.size);
+                let pos = Point::new(offset, -text.size);
+                writeln!(
+                    self.buf,
+                    "gsave\n{} {} translate {} {} scale",
+                    Num(pos.x.to_pt()),
+                    Num(pos.y.to_pt()),
+                    Num(scale),
+                    Num(scale)
+                )
+                .unwrap();
+
+                let (frame, _) = glyph_frame(&text.font, glyph.id);
+                let state = state
+                    .pre_translate(pos)
+                    .pre_concat(Transform::scale(Ratio::new(scale), Ratio::new(scale)));
+                self.render_frame(state, &frame);
+                self.buf.push_str("grestore\n");
+            }
+            x += glyph.x_advance.at(text.size);
+        }
+    }
+}
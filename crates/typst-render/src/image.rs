@@ -19,6 +19,11 @@ pub fn render_image(
     let view_width = size.x.to_f32();
     let view_height = size.y.to_f32();
 
+    let bounds = sk::Rect::from_xywh(0.0, 0.0, view_width, view_height)?;
+    if crate::is_offscreen(canvas, ts, bounds) {
+        return Some(());
+    }
+
     // For better-looking output, resize `image` to its final size before
     // painting it to `canvas`. For the math, see:
     // https://github.com/typst/typst/issues/1404#issuecomment-1598374652
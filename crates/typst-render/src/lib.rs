@@ -12,18 +12,85 @@ use typst_library::layout::{
 use typst_library::model::Document;
 use typst_library::visualize::{Color, Geometry, Paint};
 
+/// Settings for raster export.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// The number of pixels per point.
+    pub pixel_per_pt: f32,
+    /// Whether to smooth the edges of text and shapes.
+    ///
+    /// Without anti-aliasing, every pixel is either fully covered or not at
+    /// all, which is what e-ink displays and some printers need.
+    pub anti_alias: bool,
+    /// Whether to round the font size and the baseline of text to whole
+    /// pixels. This makes small text crisper, but, unlike real hinting, it
+    /// doesn't change the shape of the glyphs.
+    pub snap_text: bool,
+    /// Whether to snap the origin of each glyph to the pixel grid.
+    pub snap_to_pixels: bool,
+    /// The gamma with which text coverage is blended. Values above `1.0`
+    /// make text appear bolder, values below make it appear lighter.
+    pub gamma: f32,
+}
+
+impl RenderOptions {
+    /// Create default options with the given number of pixels per point.
+    pub fn new(pixel_per_pt: f32) -> Self {
+        Self { pixel_per_pt, ..Self::default() }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            pixel_per_pt: 1.0,
+            anti_alias: true,
+            snap_text: false,
+            snap_to_pixels: false,
+            gamma: 1.0,
+        }
+    }
+}
+
 /// Export a page into a raster image.
 ///
-/// This renders the page at the given number of pixels per point and returns
-/// the resulting `tiny-skia` pixel buffer.
+/// This renders the page with the given options and returns the resulting
+/// `tiny-skia` pixel buffer.
 #[typst_macros::time(name = "render")]
-pub fn render(page: &Page, pixel_per_pt: f32) -> sk::Pixmap {
-    let size = page.frame.size();
+pub fn render(page: &Page, options: &RenderOptions) -> sk::Pixmap {
+    render_page(page, Point::zero(), page.frame.size(), options)
+}
+
+/// Export a region of a page into a raster image.
+///
+/// The region is given by its top-left corner and its size in the page's
+/// coordinate system. Items that lie completely outside of the region are
+/// skipped, so this is much faster than rendering the full page when only a
+/// small part of a large page is needed.
+#[typst_macros::time(name = "render region")]
+pub fn render_region(
+    page: &Page,
+    origin: Point,
+    size: Size,
+    options: &RenderOptions,
+) -> sk::Pixmap {
+    render_page(page, origin, size, options)
+}
+
+/// Render the region of a page starting at `origin` with the given size.
+fn render_page(
+    page: &Page,
+    origin: Point,
+    size: Size,
+    options: &RenderOptions,
+) -> sk::Pixmap {
+    let pixel_per_pt = options.pixel_per_pt;
     let pxw = (pixel_per_pt * size.x.to_f32()).round().max(1.0) as u32;
     let pxh = (pixel_per_pt * size.y.to_f32()).round().max(1.0) as u32;
 
-    let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt);
-    let state = State::new(size, ts, pixel_per_pt);
+    let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt)
+        .pre_translate(-origin.x.to_f32(), -origin.y.to_f32());
+    let state = State::new(page.frame.size(), ts, *options);
 
     let mut canvas = sk::Pixmap::new(pxw, pxh).unwrap();
 
@@ -44,14 +111,14 @@ pub fn render(page: &Page, pixel_per_pt: f32) -> sk::Pixmap {
 /// Export a document with potentially multiple pages into a single raster image.
 pub fn render_merged(
     document: &Document,
-    pixel_per_pt: f32,
+    options: &RenderOptions,
     gap: Abs,
     fill: Option<Color>,
 ) -> sk::Pixmap {
     let pixmaps: Vec<_> =
-        document.pages.iter().map(|page| render(page, pixel_per_pt)).collect();
+        document.pages.iter().map(|page| render(page, options)).collect();
 
-    let gap = (pixel_per_pt * gap.to_f32()).round() as u32;
+    let gap = (options.pixel_per_pt * gap.to_f32()).round() as u32;
     let pxw = pixmaps.iter().map(sk::Pixmap::width).max().unwrap_or_default();
     let pxh = pixmaps.iter().map(|pixmap| pixmap.height()).sum::<u32>()
         + gap * pixmaps.len().saturating_sub(1) as u32;
@@ -87,19 +154,19 @@ struct State<'a> {
     container_transform: sk::Transform,
     /// The mask of the current item.
     mask: Option<&'a sk::Mask>,
-    /// The pixel per point ratio.
-    pixel_per_pt: f32,
+    /// The settings for the export, including the pixel per point ratio.
+    options: RenderOptions,
     /// The size of the first hard frame in the hierarchy.
     size: Size,
 }
 
 impl<'a> State<'a> {
-    fn new(size: Size, transform: sk::Transform, pixel_per_pt: f32) -> Self {
+    fn new(size: Size, transform: sk::Transform, options: RenderOptions) -> Self {
         Self {
             size,
             transform,
             container_transform: transform,
-            pixel_per_pt,
+            options,
             ..Default::default()
         }
     }
@@ -156,6 +223,10 @@ fn render_frame(canvas: &mut sk::Pixmap, state: State, frame: &Frame) {
     for (pos, item) in frame.items() {
         match item {
             FrameItem::Group(group) => {
+                // Layers that are hidden by default in PDF viewers are omitted.
+                if group.layer.as_ref().is_some_and(|layer| !layer.visible) {
+                    continue;
+                }
                 render_group(canvas, state, *pos, group);
             }
             FrameItem::Text(text) => {
@@ -191,18 +262,35 @@ fn render_group(canvas: &mut sk::Pixmap, state: State, pos: Point, group: &Group
             .with_size(group.frame.size()),
     };
 
+    // Contents may extend beyond the frame, so an unclipped group is culled by
+    // the bounds of its contents.
+    if group.clip_path.is_none()
+        && content_bounds(&group.frame)
+            .map_or(true, |bounds| is_offscreen(canvas, state.transform, bounds))
+    {
+        return;
+    }
+
     let mut mask = state.mask;
     let storage;
     if let Some(clip_path) = group.clip_path.as_ref() {
         if let Some(path) = shape::convert_path(clip_path)
             .and_then(|path| path.transform(state.transform))
         {
+            // Nothing in the group is visible if the clip path is off-screen.
+            if is_offscreen(canvas, sk::Transform::identity(), path.bounds()) {
+                return;
+            }
+
+            // Clip masks are smoothed like everything else, unless
+            // anti-aliasing is turned off.
+            let anti_alias = state.options.anti_alias;
             if let Some(mask) = mask {
                 let mut mask = mask.clone();
                 mask.intersect_path(
                     &path,
                     sk::FillRule::default(),
-                    false,
+                    anti_alias,
                     sk::Transform::default(),
                 );
                 storage = mask;
@@ -218,7 +306,7 @@ fn render_group(canvas: &mut sk::Pixmap, state: State, pos: Point, group: &Group
                 mask.fill_path(
                     &path,
                     sk::FillRule::default(),
-                    false,
+                    anti_alias,
                     sk::Transform::default(),
                 );
                 storage = mask;
@@ -231,6 +319,63 @@ fn render_group(canvas: &mut sk::Pixmap, state: State, pos: Point, group: &Group
     render_frame(canvas, state.with_mask(mask), &group.frame);
 }
 
+/// Whether a rectangle in the coordinate system given by the transform lies
+/// entirely outside of the canvas. Such items don't need to be rendered.
+fn is_offscreen(canvas: &sk::Pixmap, ts: sk::Transform, rect: sk::Rect) -> bool {
+    let Some(rect) = rect.transform(ts) else { return false };
+
+    // Leave a pixel of room for anti-aliasing.
+    rect.right() < -1.0
+        || rect.bottom() < -1.0
+        || rect.left() > canvas.width() as f32 + 1.0
+        || rect.top() > canvas.height() as f32 + 1.0
+}
+
+/// The bounds of everything that is rendered from a frame, in the frame's
+/// coordinate system. Returns `None` if nothing is rendered.
+#[comemo::memoize]
+fn content_bounds(frame: &Frame) -> Option<sk::Rect> {
+    let mut bounds = None;
+    for (pos, item) in frame.items() {
+        let item_bounds = match item {
+            FrameItem::Group(group) => {
+                if group.layer.as_ref().is_some_and(|layer| !layer.visible) {
+                    continue;
+                }
+                let inner = match &group.clip_path {
+                    Some(clip_path) => {
+                        shape::convert_path(clip_path).map(|path| path.bounds())
+                    }
+                    None => content_bounds(&group.frame),
+                };
+                inner.and_then(|rect| rect.transform(to_sk_transform(&group.transform)))
+            }
+            FrameItem::Text(text) => text::bounds(text),
+            FrameItem::Shape(shape, _) => shape::bounds(shape),
+            FrameItem::Image(_, size, _) => {
+                sk::Rect::from_xywh(0.0, 0.0, size.x.to_f32(), size.y.to_f32())
+            }
+            FrameItem::Link(_, _) | FrameItem::Tag(_) => None,
+        };
+        let translate = sk::Transform::from_translate(pos.x.to_f32(), pos.y.to_f32());
+        bounds = union(bounds, item_bounds.and_then(|rect| rect.transform(translate)));
+    }
+    bounds
+}
+
+/// The smallest rectangle containing both rectangles.
+fn union(a: Option<sk::Rect>, b: Option<sk::Rect>) -> Option<sk::Rect> {
+    match (a, b) {
+        (Some(a), Some(b)) => sk::Rect::from_ltrb(
+            a.left().min(b.left()),
+            a.top().min(b.top()),
+            a.right().max(b.right()),
+            a.bottom().max(b.bottom()),
+        ),
+        (a, b) => a.or(b),
+    }
+}
+
 fn to_sk_transform(transform: &Transform) -> sk::Transform {
     let Transform { sx, ky, kx, sy, tx, ty } = *transform;
     sk::Transform::from_row(
@@ -254,3 +399,231 @@ impl AbsExt for Abs {
         self.to_pt() as f32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use typst_library::foundations::{Bytes, Content, Smart};
+    use typst_library::layout::{Em, Ratio};
+    use typst_library::text::{Font, Glyph, Lang, TextItem};
+    use typst_library::visualize::{Color, Path};
+    use typst_syntax::Span;
+
+    use super::*;
+
+    /// A filled rectangle.
+    fn rect(x: f64, y: f64, color: Color) -> FrameItem {
+        let size = Size::new(Abs::pt(x), Abs::pt(y));
+        FrameItem::Shape(Geometry::Rect(size).filled(color), Span::detached())
+    }
+
+    /// A group with a small hard frame that contains the given items.
+    fn group(items: Vec<(Point, FrameItem)>) -> GroupItem {
+        let mut frame = Frame::hard(Size::splat(Abs::pt(10.0)));
+        for (pos, item) in items {
+            frame.push(pos, item);
+        }
+        GroupItem::new(frame)
+    }
+
+    fn point(x: f64, y: f64) -> Point {
+        Point::new(Abs::pt(x), Abs::pt(y))
+    }
+
+    /// A black "H" of the given size.
+    fn text(size: f64) -> FrameItem {
+        let font = typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .find(|font| font.info().family == "DejaVu Sans Mono")
+            .unwrap();
+        let id = font.ttf().glyph_index('H').unwrap().0;
+        FrameItem::Text(TextItem {
+            font,
+            size: Abs::pt(size),
+            fill: Color::BLACK.into(),
+            stroke: None,
+            lang: Lang::ENGLISH,
+            region: None,
+            text: "H".into(),
+            glyphs: vec![Glyph {
+                id,
+                x_advance: Em::new(0.6),
+                x_offset: Em::zero(),
+                range: 0..1,
+                span: (Span::detached(), 0),
+            }],
+        })
+    }
+
+    /// A page with the given items.
+    fn page(size: f64, items: Vec<(Point, FrameItem)>) -> Page {
+        let mut frame = Frame::hard(Size::splat(Abs::pt(size)));
+        for (pos, item) in items {
+            frame.push(pos, item);
+        }
+        Page {
+            frame,
+            fill: Smart::Auto,
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+            bleed: Abs::zero(),
+            crop_marks: false,
+        }
+    }
+
+    /// Render a page with default options except for the given changes.
+    fn render_with(page: &Page, f: impl FnOnce(&mut RenderOptions)) -> sk::Pixmap {
+        let mut options = RenderOptions::new(2.0);
+        f(&mut options);
+        render(page, &options)
+    }
+
+    /// How dark the pixels of a rendering are in total.
+    fn darkness(pixmap: &sk::Pixmap) -> u32 {
+        pixmap.pixels().iter().map(|pixel| 255 - u32::from(pixel.red())).sum()
+    }
+
+    /// A page with all kinds of groups in and around the region that is
+    /// rendered by the test. All edges are on the pixel grid.
+    fn culling_page() -> Page {
+        let mut frame = Frame::hard(Size::splat(Abs::pt(200.0)));
+        frame.push(point(10.0, 10.0), rect(30.0, 30.0, Color::RED));
+
+        // Contents beyond the frame of an unclipped group are visible.
+        let overflowing =
+            group(vec![(point(-100.0, -100.0), rect(40.0, 40.0, Color::GREEN))]);
+        frame.push(point(150.0, 150.0), FrameItem::Group(overflowing));
+
+        // Transformed contents are culled by where they end up.
+        let mut scaled = group(vec![(point(100.0, 20.0), rect(40.0, 20.0, Color::BLUE))]);
+        scaled.transform = Transform::scale(Ratio::new(0.5), Ratio::new(1.5));
+        frame.push(Point::zero(), FrameItem::Group(scaled));
+
+        // Clipped contents are only visible within the clip path.
+        let mut clipped = group(vec![(Point::zero(), rect(80.0, 80.0, Color::BLACK))]);
+        clipped.clip_path = Some(Path::rect(Size::splat(Abs::pt(10.0))));
+        frame.push(point(110.0, 110.0), FrameItem::Group(clipped));
+
+        // Off-screen contents.
+        let offscreen = group(vec![(Point::zero(), rect(10.0, 10.0, Color::RED))]);
+        frame.push(point(180.0, 180.0), FrameItem::Group(offscreen));
+
+        let mut page = page(200.0, vec![]);
+        page.frame = frame;
+        page
+    }
+
+    #[test]
+    fn test_content_bounds() {
+        let page = culling_page();
+        let bounds = |i: usize| {
+            let FrameItem::Group(group) = &page.frame.items().nth(i).unwrap().1 else {
+                panic!("expected a group");
+            };
+            content_bounds(&group.frame)
+        };
+        assert_eq!(bounds(1), sk::Rect::from_xywh(-100.0, -100.0, 40.0, 40.0));
+        assert_eq!(bounds(2), sk::Rect::from_xywh(100.0, 20.0, 40.0, 20.0));
+        assert_eq!(content_bounds(&Frame::soft(Size::zero())), None);
+        assert_eq!(
+            content_bounds(&page.frame),
+            sk::Rect::from_xywh(10.0, 10.0, 180.0, 180.0),
+        );
+    }
+
+    #[test]
+    fn test_render_region_is_crop_of_render() {
+        let page = culling_page();
+        let options = RenderOptions::new(2.0);
+        let full = render(&page, &options);
+        let region = render_region(
+            &page,
+            point(25.0, 20.0),
+            Size::splat(Abs::pt(100.0)),
+            &options,
+        );
+        assert_eq!((region.width(), region.height()), (200, 200));
+
+        for y in 0..region.height() {
+            for x in 0..region.width() {
+                assert_eq!(full.pixel(x + 50, y + 40), region.pixel(x, y), "at {x}, {y}");
+            }
+        }
+
+        // The overflowing and the scaled group are visible in the region.
+        let green = region.pixel(70, 80).unwrap();
+        assert!(green.green() > green.red() && green.green() > green.blue());
+        let blue = region.pixel(60, 40).unwrap();
+        assert!(blue.blue() > blue.red() && blue.blue() > blue.green());
+    }
+
+    #[test]
+    fn test_render_without_anti_alias() {
+        // A triangle, a group with a round clip path, and text.
+        let mut triangle = Path::new();
+        triangle.move_to(point(0.0, 0.0));
+        triangle.line_to(point(30.0, 7.0));
+        triangle.line_to(point(5.0, 25.0));
+        triangle.close_path();
+        let shape = Geometry::Path(triangle).filled(Color::BLACK);
+
+        let mut clipped = group(vec![(Point::zero(), rect(20.0, 20.0, Color::BLACK))]);
+        clipped.clip_path = Some(Path::ellipse(Size::splat(Abs::pt(20.0))));
+
+        let page = page(
+            60.0,
+            vec![
+                (point(2.0, 2.0), FrameItem::Shape(shape, Span::detached())),
+                (point(35.3, 3.3), FrameItem::Group(clipped)),
+                (point(4.2, 50.0), text(13.0)),
+            ],
+        );
+
+        // With anti-aliasing, edges are partially covered. Without it, all
+        // pixels are either black or white.
+        let is_gray = |pixel: &sk::PremultipliedColorU8| !matches!(pixel.red(), 0 | 255);
+        let smooth = render_with(&page, |_| {});
+        assert!(smooth.pixels().iter().any(is_gray));
+        let sharp = render_with(&page, |options| options.anti_alias = false);
+        assert!(!sharp.pixels().iter().any(is_gray));
+    }
+
+    #[test]
+    fn test_render_snap_text() {
+        // Text is rendered as if it had a whole-pixel size and baseline.
+        let snapped = page(40.0, vec![(point(5.0, 20.2), text(10.3))]);
+        let exact = page(40.0, vec![(point(5.0, 20.0), text(10.0))]);
+        let at_1x = |options: &mut RenderOptions| options.pixel_per_pt = 1.0;
+        let reference = render_with(&exact, at_1x);
+        assert_ne!(render_with(&snapped, at_1x), reference);
+        let with_snapping = render_with(&snapped, |options| {
+            at_1x(options);
+            options.snap_text = true;
+        });
+        assert_eq!(with_snapping, reference);
+    }
+
+    #[test]
+    fn test_render_snap_to_pixels() {
+        // Glyphs are moved to the closest pixel.
+        let snapped = page(40.0, vec![(point(5.2, 20.2), text(10.0))]);
+        let exact = page(40.0, vec![(point(5.0, 20.0), text(10.0))]);
+        let reference = render_with(&exact, |_| {});
+        assert_ne!(render_with(&snapped, |_| {}), reference);
+        let with_snapping =
+            render_with(&snapped, |options| options.snap_to_pixels = true);
+        assert_eq!(with_snapping, reference);
+    }
+
+    #[test]
+    fn test_render_gamma() {
+        // A higher gamma darkens the partially covered pixels of text.
+        let page = page(40.0, vec![(point(5.3, 20.3), text(12.0))]);
+        let normal = render_with(&page, |_| {});
+        let bold = render_with(&page, |options| options.gamma = 2.2);
+        assert!(darkness(&bold) > darkness(&normal));
+        for (a, b) in normal.pixels().iter().zip(bold.pixels()) {
+            assert!(b.red() <= a.red());
+        }
+    }
+}
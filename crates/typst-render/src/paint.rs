@@ -99,9 +99,10 @@ impl<'a> PatternSampler<'a> {
 
         Self {
             pixmap,
-            size: (pattern.size() + pattern.spacing()) * state.pixel_per_pt as f64,
+            size: (pattern.size() + pattern.spacing())
+                * state.options.pixel_per_pt as f64,
             transform_to_parent: fill_transform,
-            pixel_per_pt: state.pixel_per_pt,
+            pixel_per_pt: state.options.pixel_per_pt,
         }
     }
 }
@@ -191,15 +192,15 @@ pub fn to_sk_paint<'a>(
                 RelativeTo::Parent => None,
             };
 
-            let width =
-                (container_size.x.to_f32().abs() * state.pixel_per_pt).ceil() as u32;
-            let height =
-                (container_size.y.to_f32().abs() * state.pixel_per_pt).ceil() as u32;
+            let width = (container_size.x.to_f32().abs() * state.options.pixel_per_pt)
+                .ceil() as u32;
+            let height = (container_size.y.to_f32().abs() * state.options.pixel_per_pt)
+                .ceil() as u32;
 
             *pixmap = Some(cached(
                 gradient,
-                width.max(state.pixel_per_pt.ceil() as u32),
-                height.max(state.pixel_per_pt.ceil() as u32),
+                width.max(state.options.pixel_per_pt.ceil() as u32),
+                height.max(state.options.pixel_per_pt.ceil() as u32),
                 gradient_map,
             ));
 
@@ -211,8 +212,8 @@ pub fn to_sk_paint<'a>(
                 sk::FilterQuality::Nearest,
                 1.0,
                 fill_transform.pre_scale(
-                    container_size.x.signum() as f32 / state.pixel_per_pt,
-                    container_size.y.signum() as f32 / state.pixel_per_pt,
+                    container_size.x.signum() as f32 / state.options.pixel_per_pt,
+                    container_size.y.signum() as f32 / state.options.pixel_per_pt,
                 ),
             );
 
@@ -245,12 +246,19 @@ pub fn to_sk_paint<'a>(
                 sk::FilterQuality::Nearest,
                 1.0,
                 fill_transform
-                    .pre_scale(1.0 / state.pixel_per_pt, 1.0 / state.pixel_per_pt)
+                    .pre_scale(
+                        1.0 / state.options.pixel_per_pt,
+                        1.0 / state.options.pixel_per_pt,
+                    )
                     .pre_translate(offset.x.to_f32(), offset.y.to_f32()),
             );
         }
     }
 
+    if !state.options.anti_alias {
+        sk_paint.anti_alias = false;
+    }
+
     sk_paint
 }
 
@@ -266,16 +274,17 @@ pub fn to_sk_color_u8(color: Color) -> sk::ColorU8 {
 }
 
 pub fn render_pattern_frame(state: &State, pattern: &Pattern) -> sk::Pixmap {
+    let pixel_per_pt = state.options.pixel_per_pt;
     let size = pattern.size() + pattern.spacing();
     let mut canvas = sk::Pixmap::new(
-        (size.x.to_f32() * state.pixel_per_pt).round() as u32,
-        (size.y.to_f32() * state.pixel_per_pt).round() as u32,
+        (size.x.to_f32() * pixel_per_pt).round() as u32,
+        (size.y.to_f32() * pixel_per_pt).round() as u32,
     )
     .unwrap();
 
     // Render the pattern into a new canvas.
-    let ts = sk::Transform::from_scale(state.pixel_per_pt, state.pixel_per_pt);
-    let temp_state = State::new(pattern.size(), ts, state.pixel_per_pt);
+    let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt);
+    let temp_state = State::new(pattern.size(), ts, state.options);
     crate::render_frame(&mut canvas, temp_state, pattern.frame());
     canvas
 }
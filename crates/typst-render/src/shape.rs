@@ -10,31 +10,15 @@ use crate::{paint, AbsExt, State};
 /// Render a geometrical shape into the canvas.
 pub fn render_shape(canvas: &mut sk::Pixmap, state: State, shape: &Shape) -> Option<()> {
     let ts = state.transform;
-    let path = match shape.geometry {
-        Geometry::Line(target) => {
-            let mut builder = sk::PathBuilder::new();
-            builder.line_to(target.x.to_f32(), target.y.to_f32());
-            builder.finish()?
-        }
-        Geometry::Rect(size) => {
-            let w = size.x.to_f32();
-            let h = size.y.to_f32();
-            let rect = if w < 0.0 || h < 0.0 {
-                // Skia doesn't normally allow for negative dimensions, but
-                // Typst supports them, so we apply a transform if needed
-                // Because this operation is expensive according to tiny-skia's
-                // docs, we prefer to not apply it if not needed
-                let transform = sk::Transform::from_scale(w.signum(), h.signum());
-                let rect = sk::Rect::from_xywh(0.0, 0.0, w.abs(), h.abs())?;
-                rect.transform(transform)?
-            } else {
-                sk::Rect::from_xywh(0.0, 0.0, w, h)?
-            };
-
-            sk::PathBuilder::from_rect(rect)
-        }
-        Geometry::Path(ref path) => convert_path(path)?,
-    };
+    let path = convert_geometry(&shape.geometry)?;
+    let margin = stroke_margin(shape);
+    if path
+        .bounds()
+        .outset(margin, margin)
+        .is_some_and(|bounds| crate::is_offscreen(canvas, ts, bounds))
+    {
+        return Some(());
+    }
 
     if let Some(fill) = &shape.fill {
         let mut pixmap = None;
@@ -85,8 +69,8 @@ pub fn render_shape(canvas: &mut sk::Pixmap, state: State, shape: &Shape) -> Opt
                 (!matches!(shape.geometry, Geometry::Line(..))).then(|| {
                     (
                         Point::new(
-                            -*thickness * state.pixel_per_pt as f64,
-                            -*thickness * state.pixel_per_pt as f64,
+                            -*thickness * state.options.pixel_per_pt as f64,
+                            -*thickness * state.options.pixel_per_pt as f64,
                         ),
                         Axes::new(
                             Ratio::new(offset_bbox.x / bbox.x),
@@ -119,6 +103,50 @@ pub fn render_shape(canvas: &mut sk::Pixmap, state: State, shape: &Shape) -> Opt
     Some(())
 }
 
+/// The bounds of a shape, including its stroke.
+pub fn bounds(shape: &Shape) -> Option<sk::Rect> {
+    let margin = stroke_margin(shape);
+    convert_geometry(&shape.geometry)?.bounds().outset(margin, margin)
+}
+
+/// How far the stroke of a shape extends beyond its path, at most.
+fn stroke_margin(shape: &Shape) -> f32 {
+    // Strokes extend beyond the path, at most by the miter length.
+    shape.stroke.as_ref().map_or(0.0, |stroke| {
+        stroke.thickness.to_f32() * (stroke.miter_limit.get() as f32).max(1.0)
+    })
+}
+
+/// Convert a Typst geometry into a tiny-skia path.
+fn convert_geometry(geometry: &Geometry) -> Option<sk::Path> {
+    let path = match *geometry {
+        Geometry::Line(target) => {
+            let mut builder = sk::PathBuilder::new();
+            builder.line_to(target.x.to_f32(), target.y.to_f32());
+            builder.finish()?
+        }
+        Geometry::Rect(size) => {
+            let w = size.x.to_f32();
+            let h = size.y.to_f32();
+            let rect = if w < 0.0 || h < 0.0 {
+                // Skia doesn't normally allow for negative dimensions, but
+                // Typst supports them, so we apply a transform if needed
+                // Because this operation is expensive according to tiny-skia's
+                // docs, we prefer to not apply it if not needed
+                let transform = sk::Transform::from_scale(w.signum(), h.signum());
+                let rect = sk::Rect::from_xywh(0.0, 0.0, w.abs(), h.abs())?;
+                rect.transform(transform)?
+            } else {
+                sk::Rect::from_xywh(0.0, 0.0, w, h)?
+            };
+
+            sk::PathBuilder::from_rect(rect)
+        }
+        Geometry::Path(ref path) => convert_path(path)?,
+    };
+    Some(path)
+}
+
 /// Convert a Typst path into a tiny-skia path.
 pub fn convert_path(path: &Path) -> Option<sk::Path> {
     let mut builder = sk::PathBuilder::new();
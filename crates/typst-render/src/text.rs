@@ -9,7 +9,7 @@ use typst_library::text::{Font, TextItem};
 use typst_library::visualize::{FixedStroke, Paint};
 
 use crate::paint::{self, GradientSampler, PaintSampler, PatternSampler};
-use crate::{shape, AbsExt, State};
+use crate::{shape, AbsExt, RenderOptions, State};
 
 /// Render a text run into the canvas.
 pub fn render_text(canvas: &mut sk::Pixmap, state: State, text: &TextItem) {
@@ -19,8 +19,15 @@ pub fn render_text(canvas: &mut sk::Pixmap, state: State, text: &TextItem) {
         let offset = x + glyph.x_offset.at(text.size);
 
         if should_outline(&text.font, glyph) {
-            let state = state.pre_translate(Point::with_x(offset));
-            render_outline_glyph(canvas, state, text, id);
+            // Skip glyphs that are off-screen.
+            let offscreen = outline_bounds(text, offset).is_some_and(|bounds| {
+                crate::is_offscreen(canvas, state.transform, bounds)
+            });
+
+            if !offscreen {
+                let state = state.pre_translate(Point::with_x(offset));
+                render_outline_glyph(canvas, state, text, id);
+            }
         } else {
             let upem = text.font.units_per_em();
             let text_scale = text.size / upem;
@@ -36,6 +43,48 @@ pub fn render_text(canvas: &mut sk::Pixmap, state: State, text: &TextItem) {
     }
 }
 
+/// The bounds of a text run, including its stroke.
+pub fn bounds(text: &TextItem) -> Option<sk::Rect> {
+    let mut bounds = None;
+    let mut x = Abs::zero();
+    for glyph in &text.glyphs {
+        let offset = x + glyph.x_offset.at(text.size);
+        let glyph_bounds = if should_outline(&text.font, glyph) {
+            outline_bounds(text, offset)
+        } else {
+            let (glyph_frame, _) = glyph_frame(&text.font, glyph.id);
+            crate::content_bounds(&glyph_frame)
+                .and_then(|rect| rect.transform(frame_glyph_transform(text, offset)))
+        };
+        bounds = crate::union(bounds, glyph_bounds);
+        x += glyph.x_advance.at(text.size);
+    }
+    bounds
+}
+
+/// The bounds of an outline glyph at the given offset.
+///
+/// Every outline glyph fits into the font's global bounding box, extended by
+/// the stroke.
+fn outline_bounds(text: &TextItem, offset: Abs) -> Option<sk::Rect> {
+    let scale = text.size.to_f32() / text.font.units_per_em() as f32;
+    let margin = text.stroke.as_ref().map_or(0.0, |stroke| stroke.thickness.to_f32());
+    let bbox = text.font.ttf().global_bounding_box();
+    sk::Rect::from_ltrb(
+        offset.to_f32() + f32::from(bbox.x_min) * scale - margin,
+        -f32::from(bbox.y_max) * scale - margin,
+        offset.to_f32() + f32::from(bbox.x_max) * scale + margin,
+        -f32::from(bbox.y_min) * scale + margin,
+    )
+}
+
+/// The transform from the frame of a bitmap or SVG glyph at the given offset
+/// to the text run, like in [`render_text`].
+fn frame_glyph_transform(text: &TextItem, offset: Abs) -> sk::Transform {
+    let scale = (text.size / text.font.units_per_em()).to_f32();
+    sk::Transform::from_row(scale, 0.0, 0.0, scale, offset.to_f32(), -text.size.to_f32())
+}
+
 /// Render an outline glyph into the canvas. This is the "normal" case.
 fn render_outline_glyph(
     canvas: &mut sk::Pixmap,
@@ -43,8 +92,16 @@ fn render_outline_glyph(
     text: &TextItem,
     id: GlyphId,
 ) -> Option<()> {
-    let ts = &state.transform;
-    let ppem = text.size.to_f32() * ts.sy;
+    let options = &state.options;
+    let mut ts = state.transform;
+    if options.snap_to_pixels {
+        ts.tx = ts.tx.round();
+        ts.ty = ts.ty.round();
+    } else if options.snap_text {
+        ts.ty = ts.ty.round();
+    }
+
+    let mut ppem = text.size.to_f32() * ts.sy;
 
     // Render a glyph directly as a path. This only happens when the fast glyph
     // rasterization can't be used due to very large text size or weird
@@ -111,6 +168,10 @@ fn render_outline_glyph(
         return Some(());
     }
 
+    if options.snap_text {
+        ppem = ppem.round().max(1.0);
+    }
+
     // Rasterize the glyph with `pixglyph`.
     #[comemo::memoize]
     fn rasterize(
@@ -161,6 +222,9 @@ fn write_bitmap<S: PaintSampler>(
     state: &State,
     sampler: S,
 ) -> Option<()> {
+    let map = coverage_map(&state.options);
+    let adjust = |cov: u8| map.as_ref().map_or(cov, |map| map[usize::from(cov)]);
+
     // If we have a clip mask we first render to a pixmap that we then blend
     // with our canvas
     if state.mask.is_some() {
@@ -172,7 +236,7 @@ fn write_bitmap<S: PaintSampler>(
         let mut pixmap = sk::Pixmap::new(mw + 2, mh + 2)?;
         for x in 0..mw {
             for y in 0..mh {
-                let alpha = bitmap.coverage[(y * mw + x) as usize];
+                let alpha = adjust(bitmap.coverage[(y * mw + x) as usize]);
                 let color = sampler.sample((x, y));
                 pixmap.pixels_mut()[((y + 1) * (mw + 2) + (x + 1)) as usize] =
                     sk::ColorU8::from_rgba(
@@ -213,7 +277,7 @@ fn write_bitmap<S: PaintSampler>(
         for x in left.clamp(0, cw)..right.clamp(0, cw) {
             for y in top.clamp(0, ch)..bottom.clamp(0, ch) {
                 let ai = ((y - top) * mw + (x - left)) as usize;
-                let cov = bitmap.coverage[ai];
+                let cov = adjust(bitmap.coverage[ai]);
                 if cov == 0 {
                     continue;
                 }
@@ -236,6 +300,32 @@ fn write_bitmap<S: PaintSampler>(
     Some(())
 }
 
+/// Build a lookup table that adjusts glyph coverage according to the
+/// anti-aliasing and gamma settings, or `None` if it stays unchanged.
+fn coverage_map(options: &RenderOptions) -> Option<[u8; 256]> {
+    if options.anti_alias && options.gamma == 1.0 {
+        return None;
+    }
+
+    let mut map = [0; 256];
+    for (cov, mapped) in map.iter_mut().enumerate() {
+        *mapped = if !options.anti_alias {
+            // Without anti-aliasing, a pixel is drawn if it is at least half
+            // covered.
+            if cov >= 128 {
+                u8::MAX
+            } else {
+                0
+            }
+        } else {
+            let linear = cov as f32 / 255.0;
+            (255.0 * linear.powf(1.0 / options.gamma)).round() as u8
+        };
+    }
+
+    Some(map)
+}
+
 /// Allows to build tiny-skia paths from glyph outlines.
 struct WrappedPathBuilder(sk::PathBuilder);
 
@@ -0,0 +1,482 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::io::Write;
+
+use base64::Engine;
+use ecow::EcoString;
+use subsetter::GlyphRemapper;
+use ttf_parser::{RawFace, Tag};
+use typst_library::text::Font;
+
+use crate::SVGRenderer;
+
+const CMAP: Tag = Tag::from_bytes(b"cmap");
+const GLYF: Tag = Tag::from_bytes(b"glyf");
+const LOCA: Tag = Tag::from_bytes(b"loca");
+const NAME: Tag = Tag::from_bytes(b"name");
+const OS2: Tag = Tag::from_bytes(b"OS/2");
+const POST: Tag = Tag::from_bytes(b"post");
+
+/// The tags that WOFF2 can encode with a single byte, in order.
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ",
+    b"fpgm", b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp",
+    b"hdmx", b"kern", b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF",
+    b"GPOS", b"GSUB", b"EBSC", b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL",
+    b"SVG ", b"sbix", b"acnt", b"avar", b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc",
+    b"feat", b"fmtx", b"fvar", b"gvar", b"hsty", b"just", b"lcar", b"mort", b"morx",
+    b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat", b"Gloc", b"Feat", b"Sill",
+];
+
+impl SVGRenderer {
+    /// Build the `@font-face` rules for the embedded fonts.
+    pub(super) fn write_font_defs(&mut self) {
+        if self.fonts.is_empty() {
+            return;
+        }
+
+        let mut css = String::new();
+        for (id, font) in self.fonts.iter() {
+            let Some(chars) = self.font_chars.get(&id) else { continue };
+            let Some(url) = convert_font_to_woff2_url(font, chars) else { continue };
+            writeln!(
+                css,
+                "@font-face {{ font-family: '{}{id}'; src: url(\"{url}\") format(\"woff2\"); }}",
+                self.id_prefix,
+            )
+            .unwrap();
+        }
+
+        self.xml.start_element("defs");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}font", self.id_prefix));
+        self.xml.start_element("style");
+        self.xml.write_text(&css);
+        self.xml.end_element();
+        self.xml.end_element();
+    }
+}
+
+/// Subset a font to the given characters and encode it as a WOFF2 data URL.
+#[comemo::memoize]
+#[typst_macros::time(name = "embed font")]
+fn convert_font_to_woff2_url(font: &Font, chars: &BTreeSet<char>) -> Option<EcoString> {
+    let mut remapper = GlyphRemapper::new();
+    let mut mapping = vec![];
+    for &c in chars {
+        if let Some(id) = font.ttf().glyph_index(c) {
+            mapping.push((c, remapper.remap(id.0)));
+        }
+    }
+
+    let subset = subsetter::subset(font.data(), font.index(), &remapper).ok()?;
+    let mut tables = parse_tables(&subset)?;
+
+    // The subsetter drops the character map because PDF doesn't need it, but
+    // browsers do. The tables that don't depend on the glyph set are taken
+    // over from the original font if they are missing.
+    let original = RawFace::parse(font.data(), font.index()).ok()?;
+    tables.retain(|(tag, _)| *tag != CMAP);
+    tables.push((CMAP, write_cmap(&mapping)));
+    for tag in [NAME, OS2] {
+        if !tables.iter().any(|(t, _)| *t == tag) {
+            tables.push((tag, original.table(tag)?.to_vec()));
+        }
+    }
+    if !tables.iter().any(|(t, _)| *t == POST) {
+        tables.push((POST, write_post(original.table(POST)?)?));
+    }
+
+    let flavor = u32::from_be_bytes(subset.get(..4)?.try_into().ok()?);
+    let woff2 = write_woff2(flavor, tables)?;
+
+    let mut url: EcoString = "data:font/woff2;base64,".into();
+    url.push_str(&base64::engine::general_purpose::STANDARD.encode(woff2));
+    Some(url)
+}
+
+/// Extract the tables from an OpenType font.
+fn parse_tables(data: &[u8]) -> Option<Vec<(Tag, Vec<u8>)>> {
+    let count = read_u16(data, 4)?;
+    (0..usize::from(count))
+        .map(|i| {
+            let record = 12 + 16 * i;
+            let tag = Tag::from_bytes(data.get(record..record + 4)?.try_into().ok()?);
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            Some((tag, data.get(offset..offset + length)?.to_vec()))
+        })
+        .collect()
+}
+
+/// Write a character map with a format 4 subtable for the Basic Multilingual
+/// Plane and a format 12 subtable for all of Unicode.
+fn write_cmap(mapping: &[(char, u16)]) -> Vec<u8> {
+    let bmp: Vec<(u16, u16)> = mapping
+        .iter()
+        .filter_map(|&(c, id)| Some((u16::try_from(u32::from(c)).ok()?, id)))
+        .filter(|&(c, _)| c != 0xFFFF)
+        // Keep the subtable's length within 16 bits.
+        .take(8000)
+        .collect();
+
+    // Format 4 with one segment per character, plus the required final
+    // segment.
+    let seg_count = bmp.len() as u16 + 1;
+    let search_range = 2 * 2u16.pow(seg_count.ilog2());
+    let mut format4 = vec![];
+    let length = 16 + 8 * seg_count;
+    push_u16(&mut format4, 4);
+    push_u16(&mut format4, length);
+    push_u16(&mut format4, 0);
+    push_u16(&mut format4, 2 * seg_count);
+    push_u16(&mut format4, search_range);
+    push_u16(&mut format4, seg_count.ilog2() as u16);
+    push_u16(&mut format4, 2 * seg_count - search_range);
+    bmp.iter().for_each(|&(c, _)| push_u16(&mut format4, c));
+    push_u16(&mut format4, 0xFFFF);
+    push_u16(&mut format4, 0);
+    bmp.iter().for_each(|&(c, _)| push_u16(&mut format4, c));
+    push_u16(&mut format4, 0xFFFF);
+    bmp.iter()
+        .for_each(|&(c, id)| push_u16(&mut format4, id.wrapping_sub(c)));
+    push_u16(&mut format4, 1);
+    (0..seg_count).for_each(|_| push_u16(&mut format4, 0));
+
+    // Format 12 with one group per character.
+    let mut format12 = vec![];
+    push_u16(&mut format12, 12);
+    push_u16(&mut format12, 0);
+    push_u32(&mut format12, 16 + 12 * mapping.len() as u32);
+    push_u32(&mut format12, 0);
+    push_u32(&mut format12, mapping.len() as u32);
+    for &(c, id) in mapping {
+        push_u32(&mut format12, c.into());
+        push_u32(&mut format12, c.into());
+        push_u32(&mut format12, id.into());
+    }
+
+    let mut cmap = vec![];
+    push_u16(&mut cmap, 0);
+    push_u16(&mut cmap, 2);
+    push_u16(&mut cmap, 3);
+    push_u16(&mut cmap, 1);
+    push_u32(&mut cmap, 20);
+    push_u16(&mut cmap, 3);
+    push_u16(&mut cmap, 10);
+    push_u32(&mut cmap, 20 + format4.len() as u32);
+    cmap.extend(format4);
+    cmap.extend(format12);
+    cmap
+}
+
+/// Write a version 3 PostScript table, which doesn't contain glyph names and
+/// thus stays valid for a subset.
+fn write_post(original: &[u8]) -> Option<Vec<u8>> {
+    let mut post = original.get(..32)?.to_vec();
+    post[..4].copy_from_slice(&0x00030000u32.to_be_bytes());
+    Some(post)
+}
+
+/// Encode the tables of a font as WOFF2.
+///
+/// The tables are not transformed, but just compressed with Brotli.
+fn write_woff2(flavor: u32, mut tables: Vec<(Tag, Vec<u8>)>) -> Option<Vec<u8>> {
+    // The `loca` table must directly follow the `glyf` table.
+    tables.sort_by_key(|&(tag, _)| (if tag == LOCA { GLYF } else { tag }, tag == LOCA));
+
+    let mut directory = vec![];
+    let mut stream = vec![];
+    let mut sfnt_size = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        let known = KNOWN_TAGS.iter().position(|known| **known == tag.to_bytes());
+        // Transform version 3 is the null transform for `glyf` and `loca`,
+        // for all other tables it is version 0.
+        let version = if *tag == GLYF || *tag == LOCA { 3 << 6 } else { 0 };
+        match known {
+            Some(index) => directory.push(index as u8 | version),
+            None => {
+                directory.push(63 | version);
+                directory.extend(tag.to_bytes());
+            }
+        }
+        push_base128(&mut directory, data.len() as u32);
+        stream.extend_from_slice(data);
+        sfnt_size += data.len().next_multiple_of(4);
+    }
+
+    let mut compressor = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+    compressor.write_all(&stream).ok()?;
+    let compressed = compressor.into_inner();
+
+    let length = (48 + directory.len() + compressed.len()).next_multiple_of(4);
+    let mut woff2 = Vec::with_capacity(length);
+    woff2.extend(b"wOF2");
+    push_u32(&mut woff2, flavor);
+    push_u32(&mut woff2, length as u32);
+    push_u16(&mut woff2, tables.len() as u16);
+    push_u16(&mut woff2, 0);
+    push_u32(&mut woff2, sfnt_size as u32);
+    push_u32(&mut woff2, compressed.len() as u32);
+    push_u16(&mut woff2, 1);
+    push_u16(&mut woff2, 0);
+    // No metadata and private data blocks.
+    (0..5).for_each(|_| push_u32(&mut woff2, 0));
+    woff2.extend(directory);
+    woff2.extend(compressed);
+    woff2.resize(length, 0);
+    Some(woff2)
+}
+
+/// Write a variable-length `UIntBase128` as defined by WOFF2.
+fn push_base128(buf: &mut Vec<u8>, value: u32) {
+    let bytes = (1..5).take_while(|&i| value >> (7 * i) != 0).count() + 1;
+    for i in (0..bytes).rev() {
+        let continuation = if i > 0 { 0x80 } else { 0 };
+        buf.push(((value >> (7 * i)) & 0x7F) as u8 | continuation);
+    }
+}
+
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend(value.to_be_bytes());
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend(value.to_be_bytes());
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use ttf_parser::cmap::{self, Format};
+    use ttf_parser::Face;
+    use typst_library::foundations::Bytes;
+    use typst_library::layout::Ratio;
+
+    use super::*;
+    use crate::SvgPathBuilder;
+
+    /// Decode a WOFF2 file without transformed tables into its flavor and
+    /// tables.
+    fn decode_woff2(data: &[u8]) -> (u32, Vec<(Tag, Vec<u8>)>) {
+        assert_eq!(&data[..4], b"wOF2");
+        assert_eq!(read_u32(data, 8).unwrap() as usize, data.len());
+        let flavor = read_u32(data, 4).unwrap();
+        let count = read_u16(data, 12).unwrap();
+        let compressed = read_u32(data, 20).unwrap() as usize;
+
+        let mut i = 48;
+        let mut entries = vec![];
+        for _ in 0..count {
+            let flags = data[i];
+            i += 1;
+            let tag = match flags & 63 {
+                63 => {
+                    i += 4;
+                    Tag::from_bytes(data[i - 4..i].try_into().unwrap())
+                }
+                index => Tag::from_bytes(KNOWN_TAGS[usize::from(index)]),
+            };
+
+            // Only the null transforms are used.
+            let version = flags >> 6;
+            assert_eq!(version, if tag == GLYF || tag == LOCA { 3 } else { 0 });
+
+            let mut length = 0;
+            loop {
+                let byte = data[i];
+                i += 1;
+                length = (length << 7) | usize::from(byte & 0x7F);
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            entries.push((tag, length));
+        }
+
+        let mut stream = vec![];
+        brotli::Decompressor::new(&data[i..i + compressed], 4096)
+            .read_to_end(&mut stream)
+            .unwrap();
+
+        let mut offset = 0;
+        let tables = entries
+            .into_iter()
+            .map(|(tag, length)| {
+                offset += length;
+                (tag, stream[offset - length..offset].to_vec())
+            })
+            .collect();
+        assert_eq!(offset, stream.len());
+        (flavor, tables)
+    }
+
+    /// Assemble an OpenType font from its tables.
+    fn write_sfnt(flavor: u32, tables: &[(Tag, Vec<u8>)]) -> Vec<u8> {
+        let mut sfnt = vec![];
+        push_u32(&mut sfnt, flavor);
+        push_u16(&mut sfnt, tables.len() as u16);
+        (0..3).for_each(|_| push_u16(&mut sfnt, 0));
+
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, data) in tables {
+            sfnt.extend(tag.to_bytes());
+            push_u32(&mut sfnt, 0);
+            push_u32(&mut sfnt, offset as u32);
+            push_u32(&mut sfnt, data.len() as u32);
+            offset += data.len().next_multiple_of(4);
+        }
+
+        for (_, data) in tables {
+            sfnt.extend(data);
+            sfnt.resize(sfnt.len().next_multiple_of(4), 0);
+        }
+        sfnt
+    }
+
+    /// A TrueType font from the assets.
+    fn font() -> Font {
+        typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .find(|font| font.info().family == "DejaVu Sans Mono")
+            .unwrap()
+    }
+
+    /// Look up a character in the format 4 and format 12 subtables of a
+    /// character map. Mapping to `.notdef` counts as unmapped.
+    fn lookup(cmap: &[u8], c: char) -> (Option<u16>, Option<u16>) {
+        let table = cmap::Table::parse(cmap).unwrap();
+        let mut result = (None, None);
+        for subtable in table.subtables {
+            match subtable.format {
+                Format::SegmentMappingToDeltaValues(format4) => {
+                    result.0 = format4
+                        .glyph_index(c.into())
+                        .map(|id| id.0)
+                        .filter(|&id| id != 0);
+                }
+                Format::SegmentedCoverage(format12) => {
+                    result.1 = format12
+                        .glyph_index(c.into())
+                        .map(|id| id.0)
+                        .filter(|&id| id != 0);
+                }
+                _ => panic!("unexpected subtable"),
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_push_base128() {
+        let encode = |value| {
+            let mut buf = vec![];
+            push_base128(&mut buf, value);
+            buf
+        };
+        assert_eq!(encode(0), [0x00]);
+        assert_eq!(encode(127), [0x7F]);
+        assert_eq!(encode(128), [0x81, 0x00]);
+        assert_eq!(encode(16384), [0x81, 0x80, 0x00]);
+        assert_eq!(encode(u32::MAX), [0x8F, 0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn test_write_cmap_boundaries() {
+        let mapping = [
+            ('\0', 1),
+            ('A', 7),
+            ('B', 3),
+            ('\u{FFFE}', 4),
+            ('\u{FFFF}', 5),
+            ('\u{10000}', 6),
+            ('\u{10FFFF}', 2),
+        ];
+        let cmap = write_cmap(&mapping);
+
+        // Format 4 covers the Basic Multilingual Plane except for the code
+        // point that ends its last segment.
+        assert_eq!(lookup(&cmap, '\0'), (Some(1), Some(1)));
+        assert_eq!(lookup(&cmap, 'A'), (Some(7), Some(7)));
+        assert_eq!(lookup(&cmap, 'B'), (Some(3), Some(3)));
+        assert_eq!(lookup(&cmap, 'C'), (None, None));
+        assert_eq!(lookup(&cmap, '\u{FFFE}'), (Some(4), Some(4)));
+        assert_eq!(lookup(&cmap, '\u{FFFF}'), (None, Some(5)));
+        assert_eq!(lookup(&cmap, '\u{10000}'), (None, Some(6)));
+        assert_eq!(lookup(&cmap, '\u{10FFFF}'), (None, Some(2)));
+
+        // The segments are sorted, so a binary search finds all of them.
+        let segments = read_u16(&cmap, 20 + 6).unwrap() / 2;
+        let ends: Vec<u16> = (0..segments)
+            .map(|i| read_u16(&cmap, 20 + 14 + 2 * i as usize).unwrap())
+            .collect();
+        assert!(ends.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(ends.last(), Some(&0xFFFF));
+    }
+
+    #[test]
+    fn test_write_cmap_limits_format4() {
+        // More characters than fit into a format 4 subtable.
+        let mapping: Vec<(char, u16)> = ('\u{4E00}'..'\u{7000}').zip(1..).collect();
+        let cmap = write_cmap(&mapping);
+        assert_eq!(lookup(&cmap, '\u{4E00}'), (Some(1), Some(1)));
+        assert_eq!(lookup(&cmap, '\u{6D3F}'), (Some(8000), Some(8000)));
+        assert_eq!(lookup(&cmap, '\u{6D40}'), (None, Some(8001)));
+    }
+
+    #[test]
+    fn test_woff2_roundtrip() {
+        let font = font();
+        let tables = parse_tables(font.data()).unwrap();
+        let flavor = read_u32(font.data(), 0).unwrap();
+        let woff2 = write_woff2(flavor, tables.clone()).unwrap();
+        assert_eq!(woff2.len() % 4, 0);
+
+        let (decoded_flavor, mut decoded) = decode_woff2(&woff2);
+        let mut expected = tables;
+        expected.sort_by_key(|&(tag, _)| tag);
+        decoded.sort_by_key(|&(tag, _)| tag);
+        assert_eq!(decoded_flavor, flavor);
+        assert!(decoded == expected);
+    }
+
+    #[test]
+    fn test_embedded_font_roundtrip() {
+        let font = font();
+        let chars: BTreeSet<char> = "Typst ∑".chars().collect();
+        let url = convert_font_to_woff2_url(&font, &chars).unwrap();
+        let base64 = url.strip_prefix("data:font/woff2;base64,").unwrap();
+        let woff2 = base64::engine::general_purpose::STANDARD.decode(base64).unwrap();
+
+        let (flavor, tables) = decode_woff2(&woff2);
+        let sfnt = write_sfnt(flavor, &tables);
+        let face = Face::parse(&sfnt, 0).unwrap();
+
+        // Each character maps to its own glyph in the subset.
+        let mut ids: Vec<u16> =
+            chars.iter().map(|&c| face.glyph_index(c).unwrap().0).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), chars.len());
+        assert!(ids.iter().all(|&id| id > 0 && id < face.number_of_glyphs()));
+        assert_eq!(face.glyph_index('x'), None);
+
+        // The glyphs keep their outlines and the tables that browsers need
+        // are present.
+        let glyph = face.glyph_index('T').unwrap();
+        let mut builder = SvgPathBuilder::with_scale(Ratio::one());
+        assert!(face.outline_glyph(glyph, &mut builder).is_some());
+        assert!(!face.names().is_empty());
+        let post = &tables.iter().find(|&&(tag, _)| tag == POST).unwrap().1;
+        assert_eq!(read_u32(post, 0), Some(0x00030000));
+    }
+}
@@ -1,14 +1,18 @@
 use base64::Engine;
 use ecow::{eco_format, EcoString};
-use typst_library::layout::{Abs, Axes};
-use typst_library::visualize::{Image, ImageFormat, RasterFormat, VectorFormat};
+use typst_library::layout::{Abs, Axes, Frame, FrameItem, Page};
+use typst_library::visualize::{Image, ImageFormat, Paint, RasterFormat, VectorFormat};
+use typst_utils::hash128;
 
-use crate::SVGRenderer;
+use crate::{SVGRenderer, SvgImages};
 
 impl SVGRenderer {
     /// Render an image element.
     pub(super) fn render_image(&mut self, image: &Image, size: &Axes<Abs>) {
-        let url = convert_image_to_base64_url(image);
+        let url = match &self.images {
+            SvgImages::Embed => convert_image_to_base64_url(image),
+            SvgImages::External(base) => eco_format!("{base}{}", image_file_name(image)),
+        };
         self.xml.start_element("image");
         self.xml.write_attribute("xlink:href", &url);
         self.xml.write_attribute("width", &size.x.to_pt());
@@ -18,6 +22,69 @@ impl SVGRenderer {
     }
 }
 
+/// Collect the images on a page alongside the file names under which they are
+/// referenced when exporting with [`SvgImages::External`].
+pub fn external_images(page: &Page) -> Vec<(EcoString, Image)> {
+    let mut images = vec![];
+    if let Some(fill) = page.fill_or_white() {
+        collect_paint_images(&fill, &mut images);
+    }
+    collect_images(&page.frame, &mut images);
+    images
+}
+
+/// Collect the images in a frame, including those in pattern fills.
+fn collect_images(frame: &Frame, images: &mut Vec<(EcoString, Image)>) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect_images(&group.frame, images),
+            FrameItem::Text(text) => {
+                collect_paint_images(&text.fill, images);
+                if let Some(stroke) = &text.stroke {
+                    collect_paint_images(&stroke.paint, images);
+                }
+            }
+            FrameItem::Shape(shape, _) => {
+                if let Some(fill) = &shape.fill {
+                    collect_paint_images(fill, images);
+                }
+                if let Some(stroke) = &shape.stroke {
+                    collect_paint_images(&stroke.paint, images);
+                }
+            }
+            FrameItem::Image(image, _, _) => {
+                let name = image_file_name(image);
+                if !images.iter().any(|(other, _)| *other == name) {
+                    images.push((name, image.clone()));
+                }
+            }
+            FrameItem::Link(_, _) | FrameItem::Tag(_) => {}
+        }
+    }
+}
+
+/// Collect the images in a paint's pattern frame.
+fn collect_paint_images(paint: &Paint, images: &mut Vec<(EcoString, Image)>) {
+    if let Paint::Pattern(pattern) = paint {
+        collect_images(pattern.frame(), images);
+    }
+}
+
+/// The file name under which an image is referenced when it is not embedded.
+fn image_file_name(image: &Image) -> EcoString {
+    let extension = match image.format() {
+        ImageFormat::Raster(f) => match f {
+            RasterFormat::Png => "png",
+            RasterFormat::Jpg => "jpg",
+            RasterFormat::Gif => "gif",
+        },
+        ImageFormat::Vector(f) => match f {
+            VectorFormat::Svg => "svg",
+        },
+    };
+    eco_format!("{:032x}.{extension}", hash128(image))
+}
+
 /// Encode an image into a data URL. The format of the URL is
 /// `data:image/{format};base64,`.
 #[comemo::memoize]
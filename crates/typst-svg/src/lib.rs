@@ -1,19 +1,24 @@
 //! Rendering of Typst documents into SVG images.
 
+mod font;
 mod image;
 mod paint;
 mod shape;
 mod text;
 
-use std::collections::HashMap;
+pub use crate::image::external_images;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{self, Display, Formatter, Write};
 
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 use ttf_parser::OutlineBuilder;
+use typst_library::introspection::{Location, Tag};
 use typst_library::layout::{
     Abs, Frame, FrameItem, FrameKind, GroupItem, Page, Point, Ratio, Size, Transform,
 };
-use typst_library::model::Document;
+use typst_library::model::{Destination, Document};
+use typst_library::text::Font;
 use typst_library::visualize::{Geometry, Gradient, Pattern};
 use typst_utils::hash128;
 use xmlwriter::XmlWriter;
@@ -21,10 +26,103 @@ use xmlwriter::XmlWriter;
 use crate::paint::{GradientRef, PatternRef, SVGSubGradient};
 use crate::text::RenderedGlyph;
 
+/// Settings for SVG export.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SvgOptions {
+    /// How text is represented.
+    pub text: SvgText,
+    /// Whether to embed subsets of the used fonts as WOFF2 so that `<text>`
+    /// elements are displayed with the correct fonts.
+    ///
+    /// Has no effect if text is exported as paths only.
+    pub embed_fonts: bool,
+    /// How images are included.
+    pub images: SvgImages,
+    /// A prefix for all ids in the SVG.
+    ///
+    /// This way, multiple SVGs can be inlined into the same HTML page without
+    /// their ids colliding. Must be [valid](is_valid_id_prefix) for the ids to
+    /// be well-formed.
+    pub id_prefix: EcoString,
+    /// Whether to emit the page background.
+    pub background: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            text: SvgText::default(),
+            embed_fonts: false,
+            images: SvgImages::default(),
+            id_prefix: EcoString::new(),
+            background: true,
+        }
+    }
+}
+
+/// Whether a string can be used as an [id prefix](SvgOptions::id_prefix).
+///
+/// The ids in the SVG are XML names without colons (`NCName`s), so the prefix
+/// must not start with a digit, `-`, or `.` and may only contain letters,
+/// digits, `-`, `_`, and `.`. The empty prefix is valid.
+pub fn is_valid_id_prefix(prefix: &str) -> bool {
+    let mut chars = prefix.chars();
+    chars.next().map_or(true, is_name_start_char) && chars.all(is_name_char)
+}
+
+/// Whether a character can start an XML name without colons.
+fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        'A'..='Z' | '_' | 'a'..='z' | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{2FF}' | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}' | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}' | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}'
+    )
+}
+
+/// Whether a character can appear in an XML name without colons.
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c)
+        || matches!(c,
+            '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}'
+        )
+}
+
+/// How text is represented in an SVG.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SvgText {
+    /// Glyphs are drawn as paths. The text can't be selected or searched.
+    #[default]
+    Paths,
+    /// Glyphs are drawn as paths and overlaid with invisible `<text>`
+    /// elements, making the text selectable and searchable.
+    Invisible,
+    /// Text is emitted as real `<text>` elements.
+    ///
+    /// Text with glyphs that can only be drawn as images (e.g. color emoji)
+    /// falls back to paths with an invisible text layer.
+    Real,
+}
+
+/// How images are included in an SVG.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub enum SvgImages {
+    /// Images are embedded as base64-encoded data URLs.
+    #[default]
+    Embed,
+    /// Images are referenced as external files. The string is prepended to
+    /// each image's file name to form the reference.
+    ///
+    /// The files themselves must be written separately, see
+    /// [`external_images`].
+    External(EcoString),
+}
+
 /// Export a frame into a SVG file.
 #[typst_macros::time(name = "svg")]
-pub fn svg(page: &Page) -> String {
-    let mut renderer = SVGRenderer::new();
+pub fn svg(page: &Page, options: &SvgOptions) -> String {
+    let mut renderer = SVGRenderer::new(options);
     renderer.write_header(page.frame.size());
 
     let state = State::new(page.frame.size(), Transform::identity());
@@ -35,7 +133,7 @@ pub fn svg(page: &Page) -> String {
 /// Export a document with potentially multiple pages into a single SVG file.
 ///
 /// The padding will be added around and between the individual frames.
-pub fn svg_merged(document: &Document, padding: Abs) -> String {
+pub fn svg_merged(document: &Document, padding: Abs, options: &SvgOptions) -> String {
     let width = 2.0 * padding
         + document
             .pages
@@ -50,14 +148,28 @@ pub fn svg_merged(document: &Document, padding: Abs) -> String {
             .map(|page| page.frame.height() + padding)
             .sum::<Abs>();
 
-    let mut renderer = SVGRenderer::new();
+    // Internal links can be resolved because all pages end up in the same
+    // file.
+    let mut targets = HashSet::new();
+    for page in &document.pages {
+        collect_link_targets(&page.frame, &mut targets);
+    }
+
+    let mut renderer = SVGRenderer::new(options);
+    renderer.link_targets = Some(targets);
     renderer.write_header(Size::new(width, height));
 
     let [x, mut y] = [padding; 2];
-    for page in &document.pages {
+    for (i, page) in document.pages.iter().enumerate() {
         let ts = Transform::translate(x, y);
         let state = State::new(page.frame.size(), Transform::identity());
+        renderer.xml.start_element("g");
+        renderer.xml.write_attribute_fmt(
+            "id",
+            format_args!("{}page-{}", renderer.id_prefix, i + 1),
+        );
         renderer.render_page(state, ts, page);
+        renderer.xml.end_element();
         y += page.frame.height() + padding;
     }
 
@@ -68,6 +180,16 @@ pub fn svg_merged(document: &Document, padding: Abs) -> String {
 struct SVGRenderer {
     /// The internal XML writer.
     xml: XmlWriter,
+    /// How text is represented.
+    text: SvgText,
+    /// Whether to embed font subsets.
+    embed_fonts: bool,
+    /// How images are included.
+    images: SvgImages,
+    /// The prefix for all ids.
+    id_prefix: EcoString,
+    /// Whether to emit the page background.
+    background: bool,
     /// Prepared glyphs.
     glyphs: Deduplicator<RenderedGlyph>,
     /// Clip paths are used to clip a group. A clip path is a path that defines
@@ -102,6 +224,16 @@ struct SVGRenderer {
     patterns: Deduplicator<Pattern>,
     /// These are the gradients that compose a conic gradient.
     conic_subgradients: Deduplicator<SVGSubGradient>,
+    /// Fonts to embed for `<text>` elements.
+    fonts: Deduplicator<Font>,
+    /// The characters that were used with each font in `fonts`.
+    font_chars: HashMap<Id, BTreeSet<char>>,
+    /// The locations that internal links point to. These receive an id so
+    /// that they can be targeted by a fragment link.
+    ///
+    /// This is `None` if only a single page is exported, in which case
+    /// internal links are omitted.
+    link_targets: Option<HashSet<Location>>,
 }
 
 /// Contextual information for rendering.
@@ -144,9 +276,14 @@ impl State {
 
 impl SVGRenderer {
     /// Create a new SVG renderer with empty glyph and clip path.
-    fn new() -> Self {
+    fn new(options: &SvgOptions) -> Self {
         SVGRenderer {
             xml: XmlWriter::new(xmlwriter::Options::default()),
+            text: options.text,
+            embed_fonts: options.embed_fonts,
+            images: options.images.clone(),
+            id_prefix: options.id_prefix.clone(),
+            background: options.background,
             glyphs: Deduplicator::new('g'),
             clip_paths: Deduplicator::new('c'),
             gradient_refs: Deduplicator::new('g'),
@@ -154,6 +291,9 @@ impl SVGRenderer {
             conic_subgradients: Deduplicator::new('s'),
             pattern_refs: Deduplicator::new('p'),
             patterns: Deduplicator::new('t'),
+            fonts: Deduplicator::new('e'),
+            font_chars: HashMap::new(),
+            link_targets: None,
         }
     }
 
@@ -178,7 +318,7 @@ impl SVGRenderer {
 
     /// Render a page with the given transform.
     fn render_page(&mut self, state: State, ts: Transform, page: &Page) {
-        if let Some(fill) = page.fill_or_white() {
+        if let Some(fill) = page.fill_or_white().filter(|_| self.background) {
             let shape = Geometry::Rect(page.frame.size()).filled(fill);
             self.render_shape(state, &shape);
         }
@@ -194,9 +334,19 @@ impl SVGRenderer {
         }
 
         for (pos, item) in frame.items() {
-            // File size optimization.
-            // TODO: SVGs could contain links, couldn't they?
-            if matches!(item, FrameItem::Link(_, _) | FrameItem::Tag(_)) {
+            let skip = match item {
+                // Layers that are hidden by default in PDF viewers are
+                // omitted.
+                FrameItem::Group(group) => {
+                    group.layer.as_ref().is_some_and(|layer| !layer.visible)
+                }
+                FrameItem::Link(dest, _) => self.link_href(dest).is_none(),
+                // File size optimization: Only tags of link targets are kept.
+                FrameItem::Tag(tag) => self.link_target(tag).is_none(),
+                _ => false,
+            };
+
+            if skip {
                 continue;
             }
 
@@ -217,8 +367,8 @@ impl SVGRenderer {
                     self.render_shape(state.pre_translate(*pos), shape)
                 }
                 FrameItem::Image(image, size, _) => self.render_image(image, size),
-                FrameItem::Link(_, _) => unreachable!(),
-                FrameItem::Tag(_) => unreachable!(),
+                FrameItem::Link(dest, size) => self.render_link(dest, *size),
+                FrameItem::Tag(tag) => self.render_link_target(tag),
             };
 
             self.xml.end_element();
@@ -247,15 +397,68 @@ impl SVGRenderer {
         if let Some(clip_path) = &group.clip_path {
             let hash = hash128(&group);
             let id = self.clip_paths.insert_with(hash, || shape::convert_path(clip_path));
-            self.xml.write_attribute_fmt("clip-path", format_args!("url(#{id})"));
+            self.xml.write_attribute_fmt(
+                "clip-path",
+                format_args!("url(#{}{id})", self.id_prefix),
+            );
         }
 
         self.render_frame(state, group.transform, &group.frame);
         self.xml.end_element();
     }
 
+    /// Determine where a link should point to, if it can be represented in
+    /// the SVG.
+    fn link_href(&self, dest: &Destination) -> Option<EcoString> {
+        match dest {
+            Destination::Url(url) => Some(escape_attribute(url)),
+            Destination::Position(pos) => {
+                self.link_targets.as_ref()?;
+                Some(eco_format!("#{}page-{}", self.id_prefix, pos.page))
+            }
+            Destination::Location(loc) => {
+                self.link_targets.as_ref()?;
+                Some(eco_format!("#{}{}", self.id_prefix, LocationId(*loc)))
+            }
+        }
+    }
+
+    /// The location of a tag if it is the target of an internal link.
+    fn link_target(&self, tag: &Tag) -> Option<Location> {
+        let Tag::Start(elem) = tag else { return None };
+        let loc = elem.location()?;
+        self.link_targets.as_ref()?.contains(&loc).then_some(loc)
+    }
+
+    /// Render a link as a transparent, clickable rectangle.
+    fn render_link(&mut self, dest: &Destination, size: Size) {
+        let Some(href) = self.link_href(dest) else { return };
+        self.xml.start_element("a");
+        self.xml.write_attribute("href", &href);
+        // Also writing the xlink:href attribute for compatibility.
+        self.xml.write_attribute("xlink:href", &href);
+        self.xml.start_element("rect");
+        self.xml.write_attribute("width", &size.x.to_pt());
+        self.xml.write_attribute("height", &size.y.to_pt());
+        self.xml.write_attribute("fill", "transparent");
+        self.xml.end_element();
+        self.xml.end_element();
+    }
+
+    /// Render an empty, identifiable group at the start of a link target.
+    fn render_link_target(&mut self, tag: &Tag) {
+        let Some(loc) = self.link_target(tag) else { return };
+        self.xml.start_element("g");
+        self.xml.write_attribute_fmt(
+            "id",
+            format_args!("{}{}", self.id_prefix, LocationId(loc)),
+        );
+        self.xml.end_element();
+    }
+
     /// Finalize the SVG file. This must be called after all rendering is done.
     fn finalize(mut self) -> String {
+        self.write_font_defs();
         self.write_glyph_defs();
         self.write_clip_path_defs();
         self.write_gradients();
@@ -273,11 +476,13 @@ impl SVGRenderer {
         }
 
         self.xml.start_element("defs");
-        self.xml.write_attribute("id", "clip-path");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}clip-path", self.id_prefix));
 
         for (id, path) in self.clip_paths.iter() {
             self.xml.start_element("clipPath");
-            self.xml.write_attribute("id", &id);
+            self.xml
+                .write_attribute_fmt("id", format_args!("{}{id}", self.id_prefix));
             self.xml.start_element("path");
             self.xml.write_attribute("d", &path);
             self.xml.end_element();
@@ -334,6 +539,9 @@ impl<T> Deduplicator<T> {
 }
 
 /// Identifies a `<def>`.
+///
+/// Displays without the [id prefix](SvgOptions::id_prefix), which the
+/// renderer writes in front of it.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct Id(char, u128, usize);
 
@@ -343,6 +551,42 @@ impl Display for Id {
     }
 }
 
+/// Displays as the id of a link target.
+struct LocationId(Location);
+
+impl Display for LocationId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "loc-{:032x}", self.0.hash())
+    }
+}
+
+/// Escape the characters in an attribute value that the XML writer leaves
+/// as is.
+fn escape_attribute(value: &str) -> EcoString {
+    let mut escaped = EcoString::new();
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Collect the locations that internal links in a frame point to.
+fn collect_link_targets(frame: &Frame, targets: &mut HashSet<Location>) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect_link_targets(&group.frame, targets),
+            FrameItem::Link(Destination::Location(loc), _) => {
+                targets.insert(*loc);
+            }
+            _ => {}
+        }
+    }
+}
+
 /// Displays as an SVG matrix.
 struct SvgMatrix(Transform);
 
@@ -457,3 +701,190 @@ impl ttf_parser::OutlineBuilder for SvgPathBuilder {
         write!(&mut self.0, "Z ").unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
+    use typst_library::foundations::{Content, Smart};
+    use typst_library::introspection::Introspector;
+    use typst_library::layout::{Angle, Position};
+    use typst_library::model::{DocumentInfo, Url};
+    use typst_library::visualize::{Color, ColorSpace, LinearGradient, Path};
+    use typst_syntax::Span;
+
+    use super::*;
+
+    /// A page with the given items at the origin.
+    fn page(items: Vec<FrameItem>) -> Page {
+        let mut frame = Frame::hard(Size::splat(Abs::pt(100.0)));
+        for item in items {
+            frame.push(Point::zero(), item);
+        }
+        Page {
+            frame,
+            fill: Smart::Auto,
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+            bleed: Abs::zero(),
+            crop_marks: false,
+        }
+    }
+
+    fn document(pages: Vec<Page>) -> Document {
+        Document {
+            pages,
+            info: DocumentInfo::default(),
+            introspector: Introspector::default(),
+        }
+    }
+
+    fn link(dest: Destination) -> FrameItem {
+        FrameItem::Link(dest, Size::new(Abs::pt(20.0), Abs::pt(10.0)))
+    }
+
+    /// The start tag of an element with the given location.
+    fn target(loc: Location) -> FrameItem {
+        let mut content = Content::empty();
+        content.set_location(loc);
+        FrameItem::Tag(Tag::Start(content))
+    }
+
+    /// The values of all occurrences of an attribute.
+    fn attributes<'a>(svg: &'a str, name: &str) -> Vec<&'a str> {
+        let pattern = format!(" {name}=\"");
+        svg.match_indices(&pattern)
+            .map(|(i, _)| {
+                let value = &svg[i + pattern.len()..];
+                &value[..value.find('"').unwrap()]
+            })
+            .collect()
+    }
+
+    /// Remove the indentation and line breaks from an SVG.
+    fn flat(svg: &str) -> String {
+        svg.lines().map(str::trim).collect()
+    }
+
+    /// The clickable area of a link to `href`.
+    fn anchor(href: &str) -> String {
+        format!(
+            "<a href=\"{href}\" xlink:href=\"{href}\">\
+             <rect width=\"20\" height=\"10\" fill=\"transparent\"/></a>"
+        )
+    }
+
+    #[test]
+    fn test_svg_url_link() {
+        let url = Url::new("https://typst.app/?a=1&b=2").unwrap();
+        let page = page(vec![link(Destination::Url(url))]);
+        let svg = flat(&svg(&page, &SvgOptions::default()));
+        assert!(svg.contains(&anchor("https://typst.app/?a=1&amp;b=2")));
+    }
+
+    #[test]
+    fn test_svg_internal_links_need_merged_document() {
+        let loc = Location::new(1);
+        let pos = Position { page: NonZeroUsize::MIN, point: Point::zero() };
+        let page = page(vec![
+            link(Destination::Position(pos)),
+            link(Destination::Location(loc)),
+            target(loc),
+        ]);
+        let svg = flat(&svg(&page, &SvgOptions::default()));
+        assert!(!svg.contains("<a "));
+        assert!(!svg.contains("loc-"));
+    }
+
+    #[test]
+    fn test_svg_merged_position_link() {
+        let pos = Position {
+            page: NonZeroUsize::new(2).unwrap(),
+            point: Point::zero(),
+        };
+        let doc =
+            document(vec![page(vec![link(Destination::Position(pos))]), page(vec![])]);
+        let options = SvgOptions { id_prefix: "doc-".into(), ..SvgOptions::default() };
+        let svg = flat(&svg_merged(&doc, Abs::zero(), &options));
+        assert!(svg.contains(&anchor("#doc-page-2")));
+        assert!(svg.contains("<g id=\"doc-page-2\""));
+    }
+
+    #[test]
+    fn test_svg_merged_location_link() {
+        let loc = Location::new(0xABC);
+        let other = Location::new(0xDEF);
+        let doc = document(vec![
+            page(vec![link(Destination::Location(loc))]),
+            page(vec![target(other), target(loc)]),
+        ]);
+        let svg = flat(&svg_merged(&doc, Abs::zero(), &SvgOptions::default()));
+        let id = format!("loc-{:032x}", 0xABC);
+        assert!(svg.contains(&anchor(&format!("#{id}"))));
+        assert!(svg.contains(&format!("<g id=\"{id}\"/>")));
+
+        // Only tags of link targets are kept.
+        assert!(!svg.contains(&format!("loc-{:032x}", 0xDEF)));
+    }
+
+    #[test]
+    fn test_css_string() {
+        assert_eq!(crate::text::css_string("DejaVu Sans"), "'DejaVu Sans'");
+        assert_eq!(crate::text::css_string("It's"), "'It\\'s'");
+        assert_eq!(crate::text::css_string("a\\b\nc"), "'a\\\\b\\A c'");
+    }
+
+    #[test]
+    fn test_valid_id_prefix() {
+        for prefix in ["", "doc-", "_", "a1.-_", "été", "x\u{B7}"] {
+            assert!(is_valid_id_prefix(prefix), "{prefix:?}");
+        }
+        for prefix in ["1a", "-a", ".a", "a:b", "a b", "a\"", "a<", "\u{B7}"] {
+            assert!(!is_valid_id_prefix(prefix), "{prefix:?}");
+        }
+    }
+
+    #[test]
+    fn test_svg_id_prefix() {
+        let size = Size::splat(Abs::pt(50.0));
+        let gradient = Gradient::Linear(Arc::new(LinearGradient {
+            stops: vec![(Color::RED, Ratio::zero()), (Color::BLUE, Ratio::one())],
+            angle: Angle::zero(),
+            space: ColorSpace::Oklab,
+            relative: Smart::Auto,
+            anti_alias: true,
+        }));
+        let mut inner = Frame::hard(size);
+        inner.push(
+            Point::zero(),
+            FrameItem::Shape(Geometry::Rect(size).filled(gradient), Span::detached()),
+        );
+        let mut group = GroupItem::new(inner);
+        group.clip_path = Some(Path::rect(size));
+
+        let page = page(vec![FrameItem::Group(group)]);
+        let options = SvgOptions { id_prefix: "doc-".into(), ..SvgOptions::default() };
+        let svg = svg(&page, &options);
+
+        // Every id has the prefix and every reference points to one of them.
+        let ids = attributes(&svg, "id");
+        assert!(ids.len() >= 4);
+        assert!(ids.iter().all(|id| id.starts_with("doc-")));
+
+        let mut refs = attributes(&svg, "href");
+        for name in ["fill", "clip-path"] {
+            refs.extend(
+                attributes(&svg, name)
+                    .into_iter()
+                    .filter_map(|value| value.strip_prefix("url(")?.strip_suffix(')')),
+            );
+        }
+        assert!(refs.len() >= 3);
+        for target in refs {
+            let id = target.strip_prefix('#').unwrap();
+            assert!(ids.contains(&id), "{id} is not defined");
+        }
+    }
+}
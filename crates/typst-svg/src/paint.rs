@@ -42,11 +42,17 @@ impl SVGRenderer {
             Paint::Solid(color) => self.xml.write_attribute("fill", &color.encode()),
             Paint::Gradient(gradient) => {
                 let id = self.push_gradient(gradient, size, ts);
-                self.xml.write_attribute_fmt("fill", format_args!("url(#{id})"));
+                self.xml.write_attribute_fmt(
+                    "fill",
+                    format_args!("url(#{}{id})", self.id_prefix),
+                );
             }
             Paint::Pattern(pattern) => {
                 let id = self.push_pattern(pattern, size, ts);
-                self.xml.write_attribute_fmt("fill", format_args!("url(#{id})"));
+                self.xml.write_attribute_fmt(
+                    "fill",
+                    format_args!("url(#{}{id})", self.id_prefix),
+                );
             }
         }
         match fill_rule {
@@ -121,13 +127,17 @@ impl SVGRenderer {
         }
 
         self.xml.start_element("defs");
-        self.xml.write_attribute("id", "gradients");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}gradients", self.id_prefix));
 
         for (id, (gradient, ratio)) in self.gradients.iter() {
             match &gradient {
                 Gradient::Linear(linear) => {
                     self.xml.start_element("linearGradient");
-                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute_fmt(
+                        "id",
+                        format_args!("{}{id}", self.id_prefix),
+                    );
                     self.xml.write_attribute("spreadMethod", "pad");
                     self.xml.write_attribute("gradientUnits", "userSpaceOnUse");
 
@@ -150,7 +160,10 @@ impl SVGRenderer {
                 }
                 Gradient::Radial(radial) => {
                     self.xml.start_element("radialGradient");
-                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute_fmt(
+                        "id",
+                        format_args!("{}{id}", self.id_prefix),
+                    );
                     self.xml.write_attribute("spreadMethod", "pad");
                     self.xml.write_attribute("gradientUnits", "userSpaceOnUse");
                     self.xml.write_attribute("cx", &radial.center.x.get());
@@ -162,7 +175,10 @@ impl SVGRenderer {
                 }
                 Gradient::Conic(conic) => {
                     self.xml.start_element("pattern");
-                    self.xml.write_attribute("id", &id);
+                    self.xml.write_attribute_fmt(
+                        "id",
+                        format_args!("{}{id}", self.id_prefix),
+                    );
                     self.xml.write_attribute("viewBox", "0 0 1 1");
                     self.xml.write_attribute("preserveAspectRatio", "none");
                     self.xml.write_attribute("patternUnits", "userSpaceOnUse");
@@ -229,9 +245,14 @@ impl SVGRenderer {
                         // Add the path to the pattern.
                         self.xml.start_element("path");
                         self.xml.write_attribute("d", &builder.0);
-                        self.xml.write_attribute_fmt("fill", format_args!("url(#{id})"));
-                        self.xml
-                            .write_attribute_fmt("stroke", format_args!("url(#{id})"));
+                        self.xml.write_attribute_fmt(
+                            "fill",
+                            format_args!("url(#{}{id})", self.id_prefix),
+                        );
+                        self.xml.write_attribute_fmt(
+                            "stroke",
+                            format_args!("url(#{}{id})", self.id_prefix),
+                        );
                         self.xml.write_attribute("stroke-width", "0");
                         self.xml.write_attribute("shape-rendering", "optimizeSpeed");
                         self.xml.end_element();
@@ -293,7 +314,8 @@ impl SVGRenderer {
         }
 
         self.xml.start_element("defs");
-        self.xml.write_attribute("id", "subgradients");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}subgradients", self.id_prefix));
         for (id, gradient) in self.conic_subgradients.iter() {
             let x1 = 2.0 - gradient.t0.cos() as f32 + gradient.center.x.get() as f32;
             let y1 = gradient.t0.sin() as f32 + gradient.center.y.get() as f32;
@@ -301,7 +323,8 @@ impl SVGRenderer {
             let y2 = gradient.t1.sin() as f32 + gradient.center.y.get() as f32;
 
             self.xml.start_element("linearGradient");
-            self.xml.write_attribute("id", &id);
+            self.xml
+                .write_attribute_fmt("id", format_args!("{}{id}", self.id_prefix));
             self.xml.write_attribute("gradientUnits", "objectBoundingBox");
             self.xml.write_attribute("x1", &x1);
             self.xml.write_attribute("y1", &y1);
@@ -329,7 +352,8 @@ impl SVGRenderer {
         }
 
         self.xml.start_element("defs");
-        self.xml.write_attribute("id", "gradient-refs");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}gradient-refs", self.id_prefix));
         for (id, gradient_ref) in self.gradient_refs.iter() {
             match gradient_ref.kind {
                 GradientKind::Linear => {
@@ -355,15 +379,20 @@ impl SVGRenderer {
                 }
             }
 
-            self.xml.write_attribute("id", &id);
+            self.xml
+                .write_attribute_fmt("id", format_args!("{}{id}", self.id_prefix));
 
             // Writing the href attribute to the "reference" gradient.
-            self.xml
-                .write_attribute_fmt("href", format_args!("#{}", gradient_ref.id));
+            self.xml.write_attribute_fmt(
+                "href",
+                format_args!("#{}{}", self.id_prefix, gradient_ref.id),
+            );
 
             // Also writing the xlink:href attribute for compatibility.
-            self.xml
-                .write_attribute_fmt("xlink:href", format_args!("#{}", gradient_ref.id));
+            self.xml.write_attribute_fmt(
+                "xlink:href",
+                format_args!("#{}{}", self.id_prefix, gradient_ref.id),
+            );
             self.xml.end_element();
         }
 
@@ -377,14 +406,16 @@ impl SVGRenderer {
         }
 
         self.xml.start_element("defs");
-        self.xml.write_attribute("id", "patterns");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}patterns", self.id_prefix));
 
         for (id, pattern) in
             self.patterns.iter().map(|(i, p)| (i, p.clone())).collect::<Vec<_>>()
         {
             let size = pattern.size() + pattern.spacing();
             self.xml.start_element("pattern");
-            self.xml.write_attribute("id", &id);
+            self.xml
+                .write_attribute_fmt("id", format_args!("{}{id}", self.id_prefix));
             self.xml.write_attribute("width", &size.x.to_pt());
             self.xml.write_attribute("height", &size.y.to_pt());
             self.xml.write_attribute("patternUnits", "userSpaceOnUse");
@@ -411,21 +442,27 @@ impl SVGRenderer {
         }
 
         self.xml.start_element("defs");
-        self.xml.write_attribute("id", "pattern-refs");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}pattern-refs", self.id_prefix));
         for (id, pattern_ref) in self.pattern_refs.iter() {
             self.xml.start_element("pattern");
             self.xml
                 .write_attribute("patternTransform", &SvgMatrix(pattern_ref.transform));
 
-            self.xml.write_attribute("id", &id);
+            self.xml
+                .write_attribute_fmt("id", format_args!("{}{id}", self.id_prefix));
 
             // Writing the href attribute to the "reference" pattern.
-            self.xml
-                .write_attribute_fmt("href", format_args!("#{}", pattern_ref.id));
+            self.xml.write_attribute_fmt(
+                "href",
+                format_args!("#{}{}", self.id_prefix, pattern_ref.id),
+            );
 
             // Also writing the xlink:href attribute for compatibility.
-            self.xml
-                .write_attribute_fmt("xlink:href", format_args!("#{}", pattern_ref.id));
+            self.xml.write_attribute_fmt(
+                "xlink:href",
+                format_args!("#{}{}", self.id_prefix, pattern_ref.id),
+            );
             self.xml.end_element();
         }
 
@@ -110,11 +110,17 @@ impl SVGRenderer {
             Paint::Solid(color) => self.xml.write_attribute("stroke", &color.encode()),
             Paint::Gradient(gradient) => {
                 let id = self.push_gradient(gradient, size, fill_transform);
-                self.xml.write_attribute_fmt("stroke", format_args!("url(#{id})"));
+                self.xml.write_attribute_fmt(
+                    "stroke",
+                    format_args!("url(#{}{id})", self.id_prefix),
+                );
             }
             Paint::Pattern(pattern) => {
                 let id = self.push_pattern(pattern, size, fill_transform);
-                self.xml.write_attribute_fmt("stroke", format_args!("url(#{id})"));
+                self.xml.write_attribute_fmt(
+                    "stroke",
+                    format_args!("url(#{}{id})", self.id_prefix),
+                );
             }
         }
 
@@ -1,20 +1,40 @@
+use std::fmt::Write;
 use std::io::Read;
 
 use base64::Engine;
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 use ttf_parser::GlyphId;
 use typst_library::layout::{Abs, Point, Ratio, Size, Transform};
 use typst_library::text::{Font, TextItem};
 use typst_library::visualize::{FillRule, Image, Paint, RasterFormat, RelativeTo};
 use typst_utils::hash128;
 
-use crate::{SVGRenderer, State, SvgMatrix, SvgPathBuilder};
+use crate::{SVGRenderer, State, SvgMatrix, SvgPathBuilder, SvgText};
 
 impl SVGRenderer {
-    /// Render a text item. The text is rendered as a group of glyphs. We will
-    /// try to render the text as SVG first, then bitmap, then outline. If none
-    /// of them works, we will skip the text.
+    /// Render a text item, as glyphs, as a `<text>` element, or both.
     pub(super) fn render_text(&mut self, state: State, text: &TextItem) {
+        // Glyphs that are drawn as images can't be represented by a font, so
+        // such text keeps its paths.
+        let real = self.text == SvgText::Real
+            && !text
+                .glyphs
+                .iter()
+                .any(|glyph| is_image_glyph(&text.font, GlyphId(glyph.id)));
+
+        if !real {
+            self.render_glyphs(state, text);
+        }
+
+        if self.text != SvgText::Paths {
+            self.render_text_layer(state, text, !real);
+        }
+    }
+
+    /// Render the glyphs of a text item. The text is rendered as a group of
+    /// glyphs. We will try to render the text as SVG first, then bitmap, then
+    /// outline. If none of them works, we will skip the text.
+    fn render_glyphs(&mut self, state: State, text: &TextItem) {
         let scale: f64 = text.size.to_pt() / text.font.units_per_em();
 
         self.xml.start_element("g");
@@ -46,6 +66,90 @@ impl SVGRenderer {
         self.xml.end_element();
     }
 
+    /// Render a text item as a `<text>` element, which can be selected,
+    /// searched, and read by screen readers.
+    ///
+    /// Every character is positioned explicitly, so that the text lines up
+    /// with the shaped glyphs even if the viewer lays it out differently.
+    fn render_text_layer(&mut self, state: State, text: &TextItem, invisible: bool) {
+        let mut chars = vec![];
+        let mut x: f64 = 0.0;
+        for glyph in &text.glyphs {
+            let advance = glyph.x_advance.at(text.size).to_pt();
+            let offset = x + glyph.x_offset.at(text.size).to_pt();
+
+            // The characters of a ligature share the glyph's advance.
+            let cluster = &text.text[glyph.range()];
+            let count = cluster.chars().count() as f64;
+            for (i, (j, c)) in cluster.char_indices().enumerate() {
+                let start = glyph.range().start + j;
+                chars.push((start, c, offset + advance * i as f64 / count));
+            }
+
+            x += advance;
+        }
+
+        // Emit the characters in logical order. Multiple glyphs may belong
+        // to the same character.
+        chars.sort_by_key(|&(start, ..)| start);
+        chars.dedup_by_key(|&mut (start, ..)| start);
+        if chars.is_empty() {
+            return;
+        }
+
+        let family = if self.embed_fonts {
+            let hash = hash128(&text.font);
+            let id = self.fonts.insert_with(hash, || text.font.clone());
+            self.font_chars
+                .entry(id)
+                .or_default()
+                .extend(chars.iter().map(|&(_, c, _)| c));
+            eco_format!("{}{id}", self.id_prefix)
+        } else {
+            text.font.info().family.as_str().into()
+        };
+
+        let mut positions = EcoString::new();
+        let mut content = EcoString::new();
+        for (i, &(_, c, x)) in chars.iter().enumerate() {
+            if i > 0 {
+                positions.push(' ');
+            }
+            write!(positions, "{x}").unwrap();
+            content.push(c);
+        }
+
+        self.xml.start_element("text");
+        self.xml.write_attribute("class", "typst-text-layer");
+        self.xml.write_attribute("xml:space", "preserve");
+        self.xml.write_attribute("xml:lang", text.lang.as_str());
+        self.xml.write_attribute("x", &positions);
+        self.xml.write_attribute("font-size", &text.size.to_pt());
+        self.xml.write_attribute("font-family", &css_string(&family));
+
+        if invisible {
+            self.xml.write_attribute("fill", "transparent");
+        } else {
+            let size = Size::new(text.width(), text.size);
+            self.write_fill(
+                &text.fill,
+                FillRule::default(),
+                size,
+                self.text_paint_transform(state, &text.fill),
+            );
+            if let Some(stroke) = &text.stroke {
+                self.write_stroke(
+                    stroke,
+                    size,
+                    self.text_paint_transform(state, &stroke.paint),
+                );
+            }
+        }
+
+        self.xml.write_text(&content);
+        self.xml.end_element();
+    }
+
     /// Render a glyph defined by an SVG.
     fn render_svg_glyph(
         &mut self,
@@ -68,7 +172,8 @@ impl SVGRenderer {
         });
 
         self.xml.start_element("use");
-        self.xml.write_attribute_fmt("xlink:href", format_args!("#{id}"));
+        self.xml
+            .write_attribute_fmt("xlink:href", format_args!("#{}{id}", self.id_prefix));
         self.xml.write_attribute("x", &x_offset);
         self.xml.end_element();
 
@@ -99,7 +204,8 @@ impl SVGRenderer {
 
         let target_height = text.size.to_pt();
         self.xml.start_element("use");
-        self.xml.write_attribute_fmt("xlink:href", format_args!("#{id}"));
+        self.xml
+            .write_attribute_fmt("xlink:href", format_args!("#{}{id}", self.id_prefix));
 
         // The image is stored with the height of `image.height()`, but we want
         // to render it with a height of `target_height`. So we need to scale
@@ -134,7 +240,8 @@ impl SVGRenderer {
         let height = glyph_size.height() as f64 * scale.get();
 
         self.xml.start_element("use");
-        self.xml.write_attribute_fmt("xlink:href", format_args!("#{id}"));
+        self.xml
+            .write_attribute_fmt("xlink:href", format_args!("#{}{id}", self.id_prefix));
         self.xml.write_attribute_fmt("x", format_args!("{x_offset}"));
         self.write_fill(
             &text.fill,
@@ -179,11 +286,13 @@ impl SVGRenderer {
         }
 
         self.xml.start_element("defs");
-        self.xml.write_attribute("id", "glyph");
+        self.xml
+            .write_attribute_fmt("id", format_args!("{}glyph", self.id_prefix));
 
         for (id, glyph) in self.glyphs.iter() {
             self.xml.start_element("symbol");
-            self.xml.write_attribute("id", &id);
+            self.xml
+                .write_attribute_fmt("id", format_args!("{}{id}", self.id_prefix));
             self.xml.write_attribute("overflow", "visible");
 
             match glyph {
@@ -224,6 +333,30 @@ pub enum RenderedGlyph {
     Image { url: EcoString, width: f64, height: f64, ts: Transform },
 }
 
+/// Quote text as a CSS string, escaping quotes, backslashes, and line
+/// breaks.
+pub(crate) fn css_string(text: &str) -> EcoString {
+    let mut quoted = EcoString::from("'");
+    for c in text.chars() {
+        match c {
+            '\'' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' | '\r' | '\x0C' => write!(quoted, "\\{:X} ", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Whether a glyph is drawn as an image rather than as an outline.
+fn is_image_glyph(font: &Font, id: GlyphId) -> bool {
+    convert_svg_glyph_to_base64_url(font, id).is_some()
+        || convert_bitmap_glyph_to_image(font, id).is_some()
+}
+
 /// Convert an outline glyph to an SVG path.
 #[comemo::memoize]
 fn convert_outline_glyph_to_path(
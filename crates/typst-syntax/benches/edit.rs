@@ -0,0 +1,95 @@
+//! Compares applying batches of edits one at a time with `Source::edit` to
+//! applying them at once with `Source::edit_many`.
+//!
+//! Run with `cargo bench -p typst-syntax --bench edit`.
+
+use std::hint::black_box;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use typst_syntax::Source;
+
+/// A section of markup with some code that is repeated to build the document.
+const SECTION: &str = "\
+= Section
+Some *strong* and _emphasized_ text with a #link(\"https://typst.app\")[link].
+
+#let f(x, y: 2) = {
+  let total = x + y
+  if total > 3 { [big] } else { [small] }
+}
+
+- A list item with #f(1) and $x^2 + y^2$.
+- Another item.
+
+";
+
+/// How often each batch is applied.
+const ROUNDS: u32 = 20;
+
+fn main() {
+    let text = SECTION.repeat(200);
+    let stride = SECTION.len();
+
+    // Renaming a variable in a single section.
+    let local = find_all(&text[stride * 100..stride * 101], "total")
+        .map(|range| shift(range, stride * 100))
+        .map(|range| (range, "sum"))
+        .collect::<Vec<_>>();
+
+    // Renaming a function everywhere, like an editor's rename refactoring.
+    let spread = find_all(&text, "#f(")
+        .map(|range| (range.start + 1..range.start + 2, "g"))
+        .collect::<Vec<_>>();
+
+    // Reformatting-like whitespace changes in every section.
+    let dense = find_all(&text, "  let")
+        .map(|range| (range.start..range.start + 2, "    "))
+        .collect::<Vec<_>>();
+
+    for (name, edits) in [("local", &local), ("spread", &spread), ("dense", &dense)] {
+        let single = measure(&text, |source| {
+            // Apply from back to front such that the ranges stay valid.
+            for (range, with) in edits.iter().rev() {
+                source.edit(range.clone(), with);
+            }
+        });
+
+        let many = measure(&text, |source| {
+            source.edit_many(edits.iter().cloned());
+        });
+
+        println!(
+            "{name:>6} ({:>4} edits): edit {:>10.2?}  edit_many {:>10.2?}",
+            edits.len(),
+            single,
+            many,
+        );
+    }
+}
+
+/// Measure the average time of applying edits to a fresh source.
+fn measure(text: &str, mut apply: impl FnMut(&mut Source)) -> Duration {
+    let mut total = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let mut source = Source::detached(text);
+        let start = Instant::now();
+        apply(&mut source);
+        total += start.elapsed();
+        black_box(source);
+    }
+    total / ROUNDS
+}
+
+/// Find the ranges of all occurrences of a pattern.
+fn find_all<'a>(
+    text: &'a str,
+    pattern: &'a str,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    text.match_indices(pattern).map(|(i, m)| i..i + m.len())
+}
+
+/// Shift a range by an offset.
+fn shift(range: Range<usize>, offset: usize) -> Range<usize> {
+    range.start + offset..range.end + offset
+}
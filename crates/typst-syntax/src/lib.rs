@@ -11,6 +11,7 @@ mod node;
 mod parser;
 mod path;
 mod reparser;
+mod rewrite;
 mod set;
 mod source;
 mod span;
@@ -25,6 +26,7 @@ pub use self::lexer::{
 pub use self::node::{LinkedChildren, LinkedNode, Side, SyntaxError, SyntaxNode};
 pub use self::parser::{parse, parse_code, parse_math};
 pub use self::path::VirtualPath;
+pub use self::rewrite::Rewriter;
 pub use self::source::Source;
 pub use self::span::{Span, Spanned};
 
@@ -28,6 +28,76 @@ pub fn reparse(
     })
 }
 
+/// Group replacements into clusters that can be reparsed independently.
+///
+/// Takes the sorted, non-overlapping ranges in the old text that are replaced
+/// and returns ranges of indices into them. Neighbouring replacements end up
+/// in the same cluster if the syntax covering them overlaps, since reparsing
+/// one of them would then reparse the other one, too.
+pub fn cluster(root: &SyntaxNode, replaced: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut clusters: Vec<(Range<usize>, Range<usize>)> = vec![];
+    for (i, range) in replaced.iter().enumerate() {
+        clusters.push((i..i + 1, covering(root, range)));
+
+        // Merge with the previous clusters while they overlap. Merging can
+        // make the covering node larger, so this may cascade.
+        while let [.., (prev, prev_covered), (last, last_covered)] = clusters.as_slice() {
+            if !overlaps(prev_covered, last_covered) {
+                break;
+            }
+
+            let indices = prev.start..last.end;
+            let hull = replaced[indices.start].start..replaced[indices.end - 1].end;
+            let covered = covering(root, &hull);
+            let covered =
+                covered.start.min(prev_covered.start)..covered.end.max(last_covered.end);
+            clusters.truncate(clusters.len() - 2);
+            clusters.push((indices, covered));
+        }
+    }
+
+    clusters.into_iter().map(|(indices, _)| indices).collect()
+}
+
+/// The range of the syntax that reparsing the replaced range affects at
+/// least: The children overlapping with it if the smallest node that
+/// includes it is markup and that whole node otherwise.
+fn covering(root: &SyntaxNode, replaced: &Range<usize>) -> Range<usize> {
+    let mut node = root;
+    let mut offset = 0;
+    'descend: loop {
+        let mut cursor = offset;
+        for child in node.children() {
+            let range = cursor..cursor + child.len();
+            if !child.is_leaf() && includes(&range, replaced) {
+                node = child;
+                offset = cursor;
+                continue 'descend;
+            }
+            cursor += child.len();
+        }
+        break;
+    }
+
+    let full = offset..offset + node.len();
+    if node.kind() != SyntaxKind::Markup {
+        return full;
+    }
+
+    let mut covered: Option<Range<usize>> = None;
+    let mut cursor = offset;
+    for child in node.children() {
+        let range = cursor..cursor + child.len();
+        if overlaps(&range, replaced) {
+            let start = covered.as_ref().map_or(range.start, |covered| covered.start);
+            covered = Some(start..range.end);
+        }
+        cursor += child.len();
+    }
+
+    covered.unwrap_or(full)
+}
+
 /// Try to reparse inside the given node.
 fn try_reparse(
     text: &str,
@@ -269,6 +339,18 @@ mod tests {
         }
     }
 
+    #[track_caller]
+    fn test_many(prev: &str, edits: &[(Range<usize>, &str)], incremental: bool) {
+        let mut source = Source::detached(prev);
+        let ranges = source.edit_many(edits.iter().cloned());
+        let mut found = source.root().clone();
+        let mut expected = parse(source.text());
+        found.synthesize(Span::detached());
+        expected.synthesize(Span::detached());
+        assert_eq!(found, expected, "source: {:?}", source.text());
+        assert_eq!(ranges.first() != Some(&(0..source.len_bytes())), incremental);
+    }
+
     #[test]
     fn test_reparse_markup() {
         test("abc~def~gh~", 5..6, "+", true);
@@ -317,4 +399,26 @@ mod tests {
         test("a #while x {\n g(x) \n}  b", 12..12, "//", true);
         test("a#[]b", 3..3, "[hey]", true);
     }
+
+    #[test]
+    fn test_reparse_many() {
+        test_many("#{ let x = 1; x + 2 }\n\nA", &[(7..8, "yy"), (14..15, "yy")], true);
+        test_many("= A\n#[b *c*]\n", &[(9..10, "d"), (6..7, "e")], true);
+        test_many("#{a}#{b}", &[(6..7, "c"), (2..3, "d")], true);
+        test_many("a *b* c", &[(1..1, "*"), (6..6, "*")], false);
+    }
+
+    #[test]
+    fn test_reparse_many_far_apart() {
+        let text = format!("#{{ x }}\n\n{}\n\n#{{ y }}", "Some text. ".repeat(50));
+        let end = text.len();
+        let edits = [(3..4, "xx"), (end - 3..end - 2, "yy")];
+        test_many(&text, &edits, true);
+
+        // Only the two blocks are reparsed, not the text between them.
+        let mut source = Source::detached(&text);
+        let ranges = source.edit_many(edits.iter().cloned());
+        let len = source.len_bytes();
+        assert_eq!(ranges, [1..7, len - 6..len]);
+    }
 }
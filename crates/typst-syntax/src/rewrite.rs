@@ -0,0 +1,295 @@
+//! Changing source code through its syntax tree.
+
+use std::ops::Range;
+
+use ecow::{eco_format, EcoString};
+
+use crate::ast::AstNode;
+use crate::{ast, LinkedNode, Source, SyntaxKind, SyntaxNode};
+
+/// Collects changes to the syntax tree of a source file and applies them to
+/// its text.
+///
+/// All changes are expressed in terms of the nodes of the original tree and
+/// only touch the text of these nodes, so trivia and comments elsewhere are
+/// preserved. The changes are applied at once by [`finish`](Self::finish),
+/// which incrementally reparses the edited source.
+///
+/// The methods panic if a node is not part of the source file.
+#[derive(Debug, Clone)]
+pub struct Rewriter {
+    source: Source,
+    edits: Vec<(Range<usize>, EcoString)>,
+}
+
+impl Rewriter {
+    /// Create a rewriter for a source file.
+    pub fn new(source: &Source) -> Self {
+        Self { source: source.clone(), edits: vec![] }
+    }
+
+    /// The source file as it was before any changes.
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// The changes so far as replacements of ranges in the original text.
+    pub fn edits(&self) -> &[(Range<usize>, EcoString)] {
+        &self.edits
+    }
+
+    /// Replace a node, e.g. an expression, with new code.
+    #[track_caller]
+    pub fn replace(&mut self, node: &SyntaxNode, with: &str) {
+        let range = find(&self.source, node).range();
+        self.edits.push((range, with.into()));
+    }
+
+    /// Remove a node.
+    #[track_caller]
+    pub fn remove(&mut self, node: &SyntaxNode) {
+        self.replace(node, "");
+    }
+
+    /// Insert code directly before a node.
+    #[track_caller]
+    pub fn insert_before(&mut self, node: &SyntaxNode, text: &str) {
+        let start = find(&self.source, node).offset();
+        self.edits.push((start..start, text.into()));
+    }
+
+    /// Insert code directly after a node.
+    #[track_caller]
+    pub fn insert_after(&mut self, node: &SyntaxNode, text: &str) {
+        let end = find(&self.source, node).range().end;
+        self.edits.push((end..end, text.into()));
+    }
+
+    /// Insert an argument at an index among the arguments in parentheses.
+    ///
+    /// Adds parentheses if there are only trailing content blocks. Commas are
+    /// added as needed and arguments that are on separate lines stay on
+    /// separate lines.
+    ///
+    /// Panics if the index is larger than the number of arguments in
+    /// parentheses.
+    #[track_caller]
+    pub fn insert_arg(&mut self, args: ast::Args<'_>, index: usize, arg: &str) {
+        let node = find(&self.source, args.to_untyped());
+        let children: Vec<LinkedNode> = node.children().collect();
+        let find = |kind| children.iter().find(|child| child.kind() == kind);
+
+        let (Some(open), Some(close)) =
+            (find(SyntaxKind::LeftParen), find(SyntaxKind::RightParen))
+        else {
+            // Only trailing content blocks.
+            assert_eq!(index, 0, "argument index is out of bounds");
+            let start = node.offset();
+            let edit = (start..start, eco_format!("({arg})"));
+            self.edits.push(edit);
+            return;
+        };
+
+        let items: Vec<&LinkedNode> = children
+            .iter()
+            .filter(|child| {
+                child.offset() < close.offset()
+                    && !child.kind().is_trivia()
+                    && child.is::<ast::Arg>()
+            })
+            .collect();
+        assert!(index <= items.len(), "argument index is out of bounds");
+
+        let Some(first) = items.first() else {
+            let end = open.range().end;
+            self.edits.push((end..end, arg.into()));
+            return;
+        };
+
+        // Arguments are on separate lines if the first one is on a new line.
+        let lead = &self.source.text()[open.range().end..first.offset()];
+        let indent = lead.rfind('\n').map(|i| &lead[i + 1..]);
+
+        let edit = if let Some(item) = items.get(index) {
+            let start = item.offset();
+            match indent {
+                Some(indent) => (start..start, eco_format!("{arg},\n{indent}")),
+                None => (start..start, eco_format!("{arg}, ")),
+            }
+        } else {
+            let last = items[items.len() - 1];
+            match (last.next_sibling(), indent) {
+                (Some(comma), Some(indent)) if comma.kind() == SyntaxKind::Comma => {
+                    let end = comma.range().end;
+                    (end..end, eco_format!("\n{indent}{arg},"))
+                }
+                (Some(comma), None) if comma.kind() == SyntaxKind::Comma => {
+                    let end = comma.range().end;
+                    (end..end, eco_format!(" {arg},"))
+                }
+                (_, Some(indent)) => {
+                    let end = last.range().end;
+                    (end..end, eco_format!(",\n{indent}{arg}"))
+                }
+                (_, None) => {
+                    let end = last.range().end;
+                    (end..end, eco_format!(", {arg}"))
+                }
+            }
+        };
+
+        self.edits.push(edit);
+    }
+
+    /// Wrap a run of sibling nodes, from `first` to `last`, in a call to the
+    /// given callee, e.g. `emph` or `text(red)`.
+    ///
+    /// In markup, the nodes are passed as a trailing content block:
+    /// `[#emph[..]]`. An embedded expression is wrapped together with its
+    /// hash. In code, the nodes are passed in parentheses: `{emph(..)}`.
+    ///
+    /// Panics if the nodes are not siblings or are out of order, or if they
+    /// contain more than one expression in code.
+    #[track_caller]
+    pub fn wrap(&mut self, first: &SyntaxNode, last: &SyntaxNode, callee: &str) {
+        let first = find(&self.source, first);
+        let last = find(&self.source, last);
+        assert!(
+            first.parent().map(|parent| parent.span())
+                == last.parent().map(|parent| parent.span())
+                && first.offset() <= last.offset(),
+            "nodes must be siblings in order",
+        );
+
+        let markup = first.parent_kind() == Some(SyntaxKind::Markup);
+        if !markup {
+            let mut exprs = 0;
+            let mut node = Some(first.clone());
+            while let Some(sibling) = node.filter(|node| node.offset() <= last.offset()) {
+                exprs += usize::from(sibling.is::<ast::Expr>());
+                node = sibling.next_sibling();
+            }
+            assert!(exprs <= 1, "cannot wrap multiple expressions in code");
+        }
+
+        let mut start = first.offset();
+        if let Some(hash) = first.prev_leaf().filter(|prev| {
+            markup && prev.kind() == SyntaxKind::Hash && prev.range().end == start
+        }) {
+            start = hash.offset();
+        }
+
+        let end = last.range().end;
+        let (open, close) = if markup {
+            (eco_format!("#{callee}["), "]")
+        } else {
+            (eco_format!("{callee}("), ")")
+        };
+
+        self.edits.push((start..start, open));
+        self.edits.push((end..end, close.into()));
+    }
+
+    /// Apply the changes and return the edited source file.
+    ///
+    /// Its text is the printed result of the changes. Panics if any of the
+    /// changed ranges overlap.
+    #[track_caller]
+    pub fn finish(self) -> Source {
+        let mut source = self.source;
+        source.edit_many(
+            self.edits.iter().map(|(range, with)| (range.clone(), with.as_str())),
+        );
+        source
+    }
+}
+
+/// Find a node in a source file.
+#[track_caller]
+fn find<'a>(source: &'a Source, node: &SyntaxNode) -> LinkedNode<'a> {
+    source.find(node.span()).expect("node is not part of the source file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rewriter;
+    use crate::{ast, LinkedNode, Source, SyntaxKind, SyntaxNode};
+
+    /// Find the first node of a kind.
+    fn find(source: &Source, kind: SyntaxKind) -> SyntaxNode {
+        fn walk(node: LinkedNode, kind: SyntaxKind) -> Option<SyntaxNode> {
+            if node.kind() == kind {
+                return Some(node.get().clone());
+            }
+            node.children().find_map(|child| walk(child, kind))
+        }
+        walk(LinkedNode::new(source.root()), kind).unwrap()
+    }
+
+    #[track_caller]
+    fn test_arg(text: &str, index: usize, expected: &str) {
+        let source = Source::detached(text);
+        let args = find(&source, SyntaxKind::Args);
+        let mut rewriter = Rewriter::new(&source);
+        rewriter.insert_arg(args.cast::<ast::Args>().unwrap(), index, "x");
+        assert_eq!(rewriter.finish().text(), expected);
+    }
+
+    #[test]
+    fn test_rewrite_replace() {
+        let source = Source::detached("#let y = 1 + 2 // Sum.\n#y");
+        let binary = find(&source, SyntaxKind::Binary);
+        let mut rewriter = Rewriter::new(&source);
+        rewriter.replace(&binary, "3");
+        let edited = rewriter.finish();
+        assert_eq!(edited.text(), "#let y = 3 // Sum.\n#y");
+        assert!(edited.root().spanless_eq(Source::detached(edited.text()).root()));
+    }
+
+    #[test]
+    fn test_rewrite_insert_arg() {
+        test_arg("#f(a, b)", 1, "#f(a, x, b)");
+        test_arg("#f(a, b)", 2, "#f(a, b, x)");
+        test_arg("#f(a,)", 1, "#f(a, x,)");
+        test_arg("#f()", 0, "#f(x)");
+        test_arg("#f[c]", 0, "#f(x)[c]");
+        test_arg("#f(a)[c]", 1, "#f(a, x)[c]");
+        test_arg("#f(\n  a, // A.\n)", 1, "#f(\n  a,\n  x, // A.\n)");
+        test_arg("#f(\n  a\n)", 0, "#f(\n  x,\n  a\n)");
+    }
+
+    #[test]
+    fn test_rewrite_wrap() {
+        let source = Source::detached("A *b* c");
+        let strong = find(&source, SyntaxKind::Strong);
+        let mut rewriter = Rewriter::new(&source);
+        rewriter.wrap(&strong, &strong, "emph");
+        assert_eq!(rewriter.finish().text(), "A #emph[*b*] c");
+
+        let source = Source::detached("#{ 1 + 2 }");
+        let binary = find(&source, SyntaxKind::Binary);
+        let mut rewriter = Rewriter::new(&source);
+        rewriter.wrap(&binary, &binary, "str");
+        assert_eq!(rewriter.finish().text(), "#{ str(1 + 2) }");
+    }
+
+    #[test]
+    fn test_rewrite_wrap_embedded() {
+        let source = Source::detached("A #f() c");
+        let call = find(&source, SyntaxKind::FuncCall);
+        let mut rewriter = Rewriter::new(&source);
+        rewriter.wrap(&call, &call, "emph");
+        assert_eq!(rewriter.finish().text(), "A #emph[#f()] c");
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot wrap multiple expressions in code")]
+    fn test_rewrite_wrap_multiple_exprs() {
+        let source = Source::detached("#{ a; b }");
+        let code = find(&source, SyntaxKind::Code);
+        let mut rewriter = Rewriter::new(&source);
+        let exprs: Vec<_> =
+            code.children().filter(|child| child.is::<ast::Expr>()).collect();
+        rewriter.wrap(exprs[0], exprs[1], "emph");
+    }
+}
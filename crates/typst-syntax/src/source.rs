@@ -8,7 +8,7 @@ use std::sync::Arc;
 
 use typst_utils::LazyHash;
 
-use crate::reparser::reparse;
+use crate::reparser::{cluster, reparse};
 use crate::{is_newline, parse, FileId, LinkedNode, Span, SyntaxNode, VirtualPath};
 
 /// A source file.
@@ -140,6 +140,122 @@ impl Source {
         reparse(&mut inner.root, &inner.text, replace, with.len())
     }
 
+    /// Edit the source file by replacing multiple ranges at once.
+    ///
+    /// The ranges refer to the text before any of the edits and must not
+    /// overlap. Insertions at the same position are applied in the given
+    /// order. Compared to calling [`edit`](Self::edit) for each replacement,
+    /// the line starts are only rebuilt once and replacements that are close
+    /// to each other are reparsed together. Replacements that are far apart
+    /// are reparsed separately, such that the text between them isn't
+    /// reparsed.
+    ///
+    /// Returns the ranges in the new source that were ultimately reparsed,
+    /// sorted and without overlaps.
+    ///
+    /// The method panics if a range is out of bounds or if two ranges
+    /// overlap.
+    #[track_caller]
+    pub fn edit_many<'a>(
+        &mut self,
+        edits: impl IntoIterator<Item = (Range<usize>, &'a str)>,
+    ) -> Vec<Range<usize>> {
+        let mut edits: Vec<_> = edits.into_iter().collect();
+        edits.sort_by_key(|(replace, _)| (replace.start, replace.end));
+
+        let mut cursor = 0;
+        for (replace, _) in &edits {
+            assert!(cursor <= replace.start, "edits overlap");
+            assert!(replace.start <= replace.end, "edit range is reversed");
+            cursor = replace.end;
+        }
+        assert!(cursor <= self.len_bytes(), "edit is out of bounds");
+
+        let Some((first, _)) = edits.first() else { return vec![] };
+        let start_byte = first.start;
+        let start_utf16 = self.byte_to_utf16(start_byte).unwrap();
+        let line = self.byte_to_line(start_byte).unwrap();
+
+        let replaced: Vec<_> = edits.iter().map(|(replace, _)| replace.clone()).collect();
+        let clusters = cluster(self.root(), &replaced);
+
+        // The range that each cluster covers in the old and the new text.
+        let (mut old_end, mut new_end) = (0, 0);
+        let hulls: Vec<_> = clusters
+            .iter()
+            .map(|indices| {
+                let cluster = &edits[indices.clone()];
+                let old = cluster[0].0.start..cluster[cluster.len() - 1].0.end;
+                let len = old.len()
+                    + cluster.iter().map(|(_, with)| with.len()).sum::<usize>()
+                    - cluster.iter().map(|(replace, _)| replace.len()).sum::<usize>();
+                let start = new_end + (old.start - old_end);
+                (old_end, new_end) = (old.end, start + len);
+                (old, start..start + len)
+            })
+            .collect();
+
+        let inner = Arc::make_mut(&mut self.0);
+        let mut text = std::mem::take(&mut *inner.text);
+
+        // Apply and reparse the clusters from back to front, such that the
+        // ranges of the clusters that are still to be applied stay valid and
+        // the tree always matches the text.
+        let mut reparsed = vec![];
+        for (indices, (old, new)) in clusters.iter().zip(&hulls).rev() {
+            for (replace, with) in edits[indices.clone()].iter().rev() {
+                text.replace_range(replace.clone(), with);
+            }
+
+            let range = reparse(&mut inner.root, &text, old.clone(), new.len());
+
+            // Map the range to the new text. The parts before the cluster are
+            // not yet edited, the parts after it are already.
+            let start = match hulls.iter().rev().find(|(old, _)| old.start < range.start)
+            {
+                Some((old, new)) if old.end <= range.start => {
+                    range.start - old.end + new.end
+                }
+                Some((_, new)) => new.start,
+                None => range.start,
+            };
+            let end = range.end - old.start + new.start;
+            reparsed.push(start..end);
+        }
+
+        *inner.text = text;
+
+        // Recalculate the line starts after the first edit, handling the
+        // adjoining of \r and \n.
+        inner.lines.truncate(line + 1);
+        if inner.text[..start_byte].ends_with('\r')
+            && inner.text[start_byte..].starts_with('\n')
+            && inner.lines.last().is_some_and(|last| last.byte_idx == start_byte)
+        {
+            inner.lines.pop();
+        }
+
+        inner.lines.extend(lines_from(
+            start_byte,
+            start_utf16,
+            &inner.text[start_byte..],
+        ));
+
+        // Merge the reparsed ranges of clusters that grew into each other.
+        reparsed.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<usize>> = vec![];
+        for range in reparsed {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => {
+                    last.end = last.end.max(range.end);
+                }
+                _ => merged.push(range),
+            }
+        }
+
+        merged
+    }
+
     /// Get the length of the file in UTF-8 encoded bytes.
     pub fn len_bytes(&self) -> usize {
         self.text().len()
@@ -443,4 +559,26 @@ mod tests {
         // Test removing everything.
         test(TEST, 0..21, "", "");
     }
+
+    #[test]
+    fn test_source_file_edit_many() {
+        #[track_caller]
+        fn test(prev: &str, edits: &[(Range<usize>, &str)], after: &str) {
+            let reference = Source::detached(after);
+            let mut edited = Source::detached(prev);
+            edited.edit_many(edits.iter().cloned());
+            assert_eq!(edited.text(), reference.text());
+            assert_eq!(edited.0.lines, reference.0.lines);
+        }
+
+        // Test edits in any order.
+        test("abc\ndef\nghi", &[(8..9, "G"), (0..1, "A\n")], "A\nbc\ndef\nGhi");
+
+        // Test insertions at the same position.
+        test("abc", &[(1..1, "x"), (1..1, "y"), (1..2, "")], "axyc");
+
+        // Test adjoining \r and \n.
+        test("ab\rcd", &[(3..4, "\n"), (4..5, "e")], "ab\r\ne");
+        test(TEST, &[], TEST);
+    }
 }
@@ -0,0 +1,776 @@
+//! Exporting of Typst documents into plain text and Markdown.
+//!
+//! The text is extracted from the realized content, i.e. after show rules were
+//! applied, instead of from the glyphs in the frames. This keeps reading order
+//! and paragraph boundaries, and includes all text produced by templates,
+//! numberings, and `context` expressions.
+
+mod math;
+
+use comemo::{Track, Tracked};
+use ecow::{eco_format, EcoString};
+use typst::diag::{At, SourceResult, Warned};
+use typst::engine::{Engine, Route, Sink, Traced};
+use typst::foundations::{Content, NativeElement, Packed, StyleChain, Value};
+use typst::introspection::{Counter, Location, Locator, LocatorLink, Tag, TagElem};
+use typst::layout::{
+    BlockBody, BlockElem, BoxElem, ColbreakElem, HElem, InlineElem, PagebreakElem,
+    PlaceElem, RepeatElem,
+};
+use typst::math::EquationElem;
+use typst::model::{
+    Destination, Document, DocumentInfo, EnumElem, FootnoteBody, FootnoteElem,
+    HeadingElem, LinkElem, ListElem, Numbering, ParElem, QuoteElem, TableChild,
+    TableElem, TableItem,
+};
+use typst::routines::{Arenas, Pair, RealizationKind};
+use typst::syntax::Span;
+use typst::text::{
+    LinebreakElem, SmartQuoteElem, SmartQuoter, SmartQuotes, SpaceElem, TextElem,
+};
+use typst::visualize::ImageElem;
+use typst::World;
+
+/// The format of exported text.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TextFormat {
+    /// Plain text.
+    #[default]
+    Plain,
+    /// Markdown, which also marks up headings, emphasis, links, and tables.
+    Markdown,
+}
+
+/// Export a compiled document into plain text or Markdown.
+///
+/// The main file is evaluated again to obtain the document's content, which
+/// is cheap because evaluation is memoized. The content is then realized with
+/// the document's introspector, so that counters, states, and queries resolve
+/// just like in the laid-out document.
+pub fn text(
+    world: &dyn World,
+    document: &Document,
+    format: TextFormat,
+) -> Warned<SourceResult<String>> {
+    let mut sink = Sink::new();
+    let output = text_impl(world.track(), document, format, &mut sink);
+    Warned { output, warnings: sink.warnings() }
+}
+
+/// The internal implementation of [`text`].
+fn text_impl(
+    world: Tracked<dyn World + '_>,
+    document: &Document,
+    format: TextFormat,
+    sink: &mut Sink,
+) -> SourceResult<String> {
+    let main = world.source(world.main()).at(Span::detached())?;
+    let traced = Traced::default();
+    let content = typst_eval::eval(
+        &typst::ROUTINES,
+        world,
+        traced.track(),
+        sink.track_mut(),
+        Route::default().track(),
+        &main,
+    )?
+    .content();
+
+    let mut engine = Engine {
+        routines: &typst::ROUTINES,
+        world,
+        introspector: document.introspector.track(),
+        traced: traced.track(),
+        sink: sink.track_mut(),
+        route: Route::default(),
+    };
+
+    // Realize the content the same way as the document's layout does, so that
+    // the elements receive the same locations.
+    let styles = StyleChain::new(&world.library().styles).to_map().outside();
+    let styles = StyleChain::new(&styles);
+    let arenas = Arenas::default();
+    let mut info = DocumentInfo::default();
+    let children = (engine.routines.realize)(
+        RealizationKind::Root(&mut info),
+        &mut engine,
+        &mut Locator::root().split(),
+        &arenas,
+        &content,
+        styles,
+    )?;
+
+    let mut writer = Writer {
+        engine: &mut engine,
+        markdown: format == TextFormat::Markdown,
+        buf: String::new(),
+        footnotes: vec![],
+        anchor: None,
+        skip: None,
+        in_list: false,
+        format: Format::default(),
+    };
+
+    writer.flow(&children)?;
+    Ok(writer.finish())
+}
+
+/// Writes realized content into a text buffer.
+struct Writer<'w, 'e> {
+    engine: &'w mut Engine<'e>,
+    markdown: bool,
+    buf: String,
+    /// The markers and texts of the footnotes, which are written at the end.
+    footnotes: Vec<(EcoString, String)>,
+    /// The location of the most recently started element. Nested content is
+    /// realized relative to it, like when it is measured during layout.
+    anchor: Option<Location>,
+    /// The location of a footnote whose realized marker is skipped up to its
+    /// end tag, and the marker to write instead.
+    skip: Option<(Location, EcoString)>,
+    /// Whether the last block was a list item, in which case inline content
+    /// starts a new paragraph.
+    in_list: bool,
+    /// The inline formatting that is currently open.
+    format: Format,
+}
+
+/// Inline formatting that spans multiple pieces of text.
+#[derive(Default)]
+struct Format {
+    strong: bool,
+    emph: bool,
+    link: Option<Link>,
+}
+
+/// An open link to a URL.
+struct Link {
+    url: EcoString,
+    /// The position in the buffer at which the link's body starts.
+    start: usize,
+    /// The unescaped text of the link's body.
+    text: String,
+}
+
+/// Writes the content between the start and end tag of an element.
+type Walk<'w, 'e> = fn(&mut Writer<'w, 'e>, &[Pair<'_>]) -> SourceResult<()>;
+
+impl<'w, 'e> Writer<'w, 'e> {
+    /// Write the block-level children of a realized document or container.
+    fn flow(&mut self, children: &[Pair<'_>]) -> SourceResult<()> {
+        let mut i = 0;
+        while let Some(&(child, styles)) = children.get(i) {
+            i += 1;
+            if let Some(elem) = child.to_packed::<ParElem>() {
+                self.start_block();
+                let children: Vec<_> = elem.children.iter(&styles).collect();
+                self.inline(&children)?;
+                self.parbreak();
+            } else if self.skipped(child) {
+                continue;
+            } else if let Some(elem) = child.to_packed::<TagElem>() {
+                i = self.tag(&elem.tag, styles, children, i, Self::flow)?;
+            } else if let Some(elem) = child.to_packed::<BlockElem>() {
+                self.block(elem, styles)?;
+            } else if let Some(elem) = child.to_packed::<PlaceElem>() {
+                self.container(elem.body(), styles)?;
+            } else if child.is::<PagebreakElem>() || child.is::<ColbreakElem>() {
+                self.parbreak();
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the inline children of a paragraph.
+    fn inline(&mut self, children: &[Pair<'_>]) -> SourceResult<()> {
+        let mut quoter = SmartQuoter::new();
+        let mut i = 0;
+        while let Some(&(child, styles)) = children.get(i) {
+            i += 1;
+            if self.skipped(child) {
+                continue;
+            } else if let Some(elem) = child.to_packed::<TagElem>() {
+                i = self.tag(&elem.tag, styles, children, i, Self::inline)?;
+            } else if let Some(elem) = child.to_packed::<TextElem>() {
+                self.apply_format(styles);
+                match TextElem::case_in(styles) {
+                    Some(case) => self.push_text(&case.apply(elem.text())),
+                    None => self.push_text(elem.text()),
+                }
+            } else if child.is::<SpaceElem>() {
+                self.space();
+            } else if let Some(elem) = child.to_packed::<HElem>() {
+                if !elem.amount().is_zero() {
+                    self.space();
+                }
+            } else if child.is::<LinebreakElem>() {
+                self.end_list();
+                self.trim_end();
+                self.buf.push_str(if self.markdown { "\\\n" } else { "\n" });
+            } else if let Some(elem) = child.to_packed::<SmartQuoteElem>() {
+                self.apply_format(styles);
+                self.smart_quote(elem, styles, &mut quoter);
+            } else if let Some(elem) = child.to_packed::<InlineElem>() {
+                let captured = elem.body().captured();
+                if let Some(elem) = captured.to_packed::<EquationElem>() {
+                    self.equation(elem, styles);
+                }
+            } else if let Some(elem) = child.to_packed::<BoxElem>() {
+                if let Some(body) = elem.body(styles) {
+                    let text = self.render(|w| w.container(body, styles))?;
+                    self.push_raw(&text.replace('\n', " "));
+                }
+            }
+        }
+        self.apply_format(StyleChain::default());
+        Ok(())
+    }
+
+    /// Handle an element's tag and return the index at which to continue.
+    ///
+    /// Headings and block quotes are written from their realized content in a
+    /// specific way, so the content up to their end tag is consumed here.
+    fn tag(
+        &mut self,
+        tag: &Tag,
+        styles: StyleChain,
+        children: &[Pair<'_>],
+        i: usize,
+        walk: Walk<'w, 'e>,
+    ) -> SourceResult<usize> {
+        let Tag::Start(elem) = tag else { return Ok(i) };
+        let Some(loc) = elem.location() else { return Ok(i) };
+        self.anchor = Some(loc);
+
+        // A footnote's end tag may be in the following paragraph if the
+        // footnote starts it, so its marker is written when reaching the tag.
+        if let Some(elem) = elem.to_packed::<FootnoteElem>() {
+            let marker = self.footnote(elem, styles)?;
+            self.skip = Some((loc, marker));
+            return Ok(i);
+        }
+
+        let quote = elem.to_packed::<QuoteElem>().is_some_and(|elem| elem.block(styles));
+        if !quote && !elem.is::<HeadingElem>() {
+            return Ok(i);
+        }
+
+        let Some(end) = children[i..].iter().position(|(child, _)| is_end(child, loc))
+        else {
+            return Ok(i);
+        };
+
+        let inner = &children[i..i + end];
+        match elem.to_packed::<HeadingElem>() {
+            Some(elem) => self.heading(elem, styles, inner, walk)?,
+            None => self.quote(inner, walk)?,
+        }
+
+        Ok(i + end + 1)
+    }
+
+    /// Whether the child belongs to the realized marker of a footnote. At the
+    /// footnote's end tag, the footnote's own marker is written.
+    fn skipped(&mut self, child: &Content) -> bool {
+        let Some((loc, marker)) = &self.skip else { return false };
+        if is_end(child, *loc) {
+            let marker = marker.clone();
+            self.skip = None;
+            self.push_raw(&if self.markdown {
+                format!("[^{marker}]")
+            } else {
+                format!("[{marker}]")
+            });
+        }
+        true
+    }
+
+    /// Write a block.
+    fn block(
+        &mut self,
+        elem: &Packed<BlockElem>,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        match elem.body(styles) {
+            None => {}
+            Some(BlockBody::Content(body)) => {
+                self.start_block();
+                self.container(body, styles)?;
+                self.parbreak();
+            }
+            Some(BlockBody::SingleLayouter(callback)) => {
+                self.layouter(callback.captured(), styles)?
+            }
+            Some(BlockBody::MultiLayouter(callback)) => {
+                self.layouter(callback.captured(), styles)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Write an element that is laid out by a callback, e.g. a list or table.
+    fn layouter(&mut self, elem: &Content, styles: StyleChain) -> SourceResult<()> {
+        if let Some(elem) = elem.to_packed::<ListElem>() {
+            self.start_block();
+            for item in elem.children() {
+                let body = self.render(|w| w.container(item.body(), styles))?;
+                self.push_item(if self.markdown { "- " } else { "• " }, &body);
+            }
+        } else if let Some(elem) = elem.to_packed::<EnumElem>() {
+            self.start_block();
+            self.enumeration(elem, styles)?;
+        } else if let Some(elem) = elem.to_packed::<TableElem>() {
+            self.table(elem, styles)?;
+        } else if let Some(elem) = elem.to_packed::<ImageElem>() {
+            let alt = elem.alt(styles);
+            if self.markdown {
+                let alt = alt.as_deref().unwrap_or_default();
+                self.push_raw(&format!("![{}]({})", escape(alt), elem.path()));
+            } else if let Some(alt) = alt.as_deref() {
+                self.push_text(alt);
+            }
+        } else if let Some(elem) = elem.to_packed::<EquationElem>() {
+            self.equation(elem, styles);
+        } else if elem.is::<RepeatElem>() {
+            // Repeated content only fills space.
+        } else {
+            // Write the content of other elements, e.g. the body of a `pad` or
+            // the cells of a grid.
+            for (name, value) in elem.fields() {
+                if name.as_str() != "label" {
+                    self.value(&value, styles)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the content in a field of an element.
+    fn value(&mut self, value: &Value, styles: StyleChain) -> SourceResult<()> {
+        match value {
+            Value::Content(content) => {
+                self.start_block();
+                self.container(content, styles)?;
+                self.parbreak();
+            }
+            Value::Array(array) => {
+                for value in array {
+                    self.value(value, styles)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Realize and write nested content, e.g. the body of a block.
+    fn container(&mut self, content: &Content, styles: StyleChain) -> SourceResult<()> {
+        // Nested content is realized in measurement mode, so that its elements
+        // are matched with the ones in the document.
+        let link = self.anchor.map(LocatorLink::measure);
+        let locator = match &link {
+            Some(link) => Locator::link(link),
+            None => Locator::root(),
+        };
+
+        let arenas = Arenas::default();
+        let realize = self.engine.routines.realize;
+        let children = realize(
+            RealizationKind::Container,
+            self.engine,
+            &mut locator.split(),
+            &arenas,
+            content,
+            styles,
+        )?;
+
+        self.flow(&children)
+    }
+
+    fn heading(
+        &mut self,
+        elem: &Packed<HeadingElem>,
+        styles: StyleChain,
+        inner: &[Pair<'_>],
+        walk: Walk<'w, 'e>,
+    ) -> SourceResult<()> {
+        // The realized heading already includes its numbering.
+        let text = self.render(|w| walk(w, inner))?;
+        self.start_block();
+        if self.markdown {
+            let level = elem.resolve_level(styles).get();
+            self.buf.push_str(&"#".repeat(level));
+            self.buf.push(' ');
+        }
+        self.buf.push_str(&text.replace('\n', " "));
+        self.parbreak();
+        Ok(())
+    }
+
+    /// Collect a footnote's body and return its marker.
+    fn footnote(
+        &mut self,
+        elem: &Packed<FootnoteElem>,
+        styles: StyleChain,
+    ) -> SourceResult<EcoString> {
+        // The marker is displayed without the superscript of the realized
+        // footnote, which may use Unicode superscript digits.
+        let loc = elem.declaration_location(self.engine).at(elem.span())?;
+        let marker = Counter::of(FootnoteElem::elem())
+            .display_at_loc(self.engine, loc, styles, elem.numbering(styles))?
+            .plain_text();
+
+        if let FootnoteBody::Content(body) = elem.body() {
+            let text = self.render(|w| w.container(body, styles))?;
+            self.footnotes.push((marker.clone(), text));
+        }
+
+        Ok(marker)
+    }
+
+    fn equation(&mut self, elem: &Packed<EquationElem>, styles: StyleChain) {
+        let text = math::to_math(elem.body(), styles);
+        if elem.block(styles) {
+            self.start_block();
+            if self.markdown {
+                self.buf.push_str(&format!("$$\n{text}\n$$"));
+            } else {
+                self.buf.push_str(&text);
+            }
+            self.parbreak();
+        } else if self.markdown {
+            self.push_raw(&format!("${text}$"));
+        } else {
+            self.push_raw(&text);
+        }
+    }
+
+    /// Write a block quote, whose realized content includes the attribution.
+    fn quote(&mut self, inner: &[Pair<'_>], walk: Walk<'w, 'e>) -> SourceResult<()> {
+        let text = self.render(|w| walk(w, inner))?;
+        self.start_block();
+        if self.markdown {
+            self.buf.push_str(&prefix_lines(&text, "> ", ">"));
+        } else {
+            self.buf.push_str(&text);
+        }
+        self.parbreak();
+        Ok(())
+    }
+
+    /// Write an enumeration, numbering the items like its layout does.
+    fn enumeration(
+        &mut self,
+        elem: &Packed<EnumElem>,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let numbering = elem.numbering(styles);
+        let mut number = elem.start(styles);
+        for item in elem.children() {
+            number = item.number(styles).unwrap_or(number);
+            let marker = match numbering {
+                Numbering::Pattern(pattern) if !self.markdown => {
+                    pattern.apply_kth(0, number)
+                }
+                _ => eco_format!("{number}."),
+            };
+
+            let body = self.render(|w| w.container(item.body(), styles))?;
+            self.push_item(&format!("{marker} "), &body);
+            number += 1;
+        }
+        Ok(())
+    }
+
+    /// Write a table. In Markdown, it becomes a pipe table, otherwise the
+    /// cells are separated by tabs.
+    fn table(
+        &mut self,
+        elem: &Packed<TableElem>,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let columns = elem.columns(styles).0.len().max(1);
+        let mut rows: Vec<Vec<String>> = vec![];
+        let mut cursor = 0;
+
+        for child in elem.children() {
+            let items = match child {
+                TableChild::Header(header) => header.children().as_slice(),
+                TableChild::Footer(footer) => footer.children().as_slice(),
+                TableChild::Item(item) => std::slice::from_ref(item),
+            };
+
+            for item in items {
+                let TableItem::Cell(cell) = item else { continue };
+                let text = self.render(|w| w.container(cell.body(), styles))?;
+                let mut text = text.replace('\n', " ");
+                if self.markdown {
+                    text = text.replace('|', "\\|");
+                }
+
+                if cursor % columns == 0 {
+                    rows.push(vec![]);
+                }
+
+                let row = rows.last_mut().unwrap();
+                let colspan = cell.colspan(styles).get();
+                row.push(text);
+                row.resize(row.len() + colspan - 1, String::new());
+                cursor += colspan;
+            }
+        }
+
+        self.start_block();
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.resize(columns, String::new());
+            if self.markdown {
+                self.buf.push_str("| ");
+                self.buf.push_str(&row.join(" | "));
+                self.buf.push_str(" |\n");
+                if i == 0 {
+                    self.buf.push_str(&"| --- ".repeat(columns));
+                    self.buf.push_str("|\n");
+                }
+            } else {
+                self.buf.push_str(row.join("\t").trim_end());
+                self.buf.push('\n');
+            }
+        }
+        self.parbreak();
+        Ok(())
+    }
+
+    /// Write a smart quote, resolved like during layout.
+    fn smart_quote(
+        &mut self,
+        elem: &Packed<SmartQuoteElem>,
+        styles: StyleChain,
+        quoter: &mut SmartQuoter,
+    ) {
+        let double = elem.double(styles);
+        if !elem.enabled(styles) {
+            self.push_text(if double { "\"" } else { "'" });
+            return;
+        }
+
+        let quotes = SmartQuotes::get(
+            elem.quotes(styles),
+            TextElem::lang_in(styles),
+            TextElem::region_in(styles),
+            elem.alternative(styles),
+        );
+        let before = self.buf.chars().next_back();
+        let quote = quoter.quote(before, &quotes, double);
+        self.push_text(quote);
+    }
+
+    /// Open and close inline formatting to match the styles of the following
+    /// text. Strong and emphasized text is only marked up in Markdown.
+    fn apply_format(&mut self, styles: StyleChain) {
+        let url = LinkElem::dests_in(styles).iter().find_map(|dest| match dest {
+            Destination::Url(url) => Some(EcoString::from(url.as_str())),
+            _ => None,
+        });
+        let strong = self.markdown && TextElem::delta_in(styles).0 > 0;
+        let emph = self.markdown && TextElem::emph_in(styles).0;
+
+        // Formatting is nested as link, strong, and emphasis from the outside
+        // in, so a change closes everything inside of it as well.
+        let keep_link = self.format.link.as_ref().map(|link| &link.url) == url.as_ref();
+        let keep_strong = keep_link && self.format.strong == strong;
+        let keep_emph = keep_strong && self.format.emph == emph;
+
+        if !keep_emph && self.format.emph {
+            self.close("*");
+        }
+        if !keep_strong && self.format.strong {
+            self.close("**");
+        }
+        if !keep_link {
+            if let Some(link) = self.format.link.take() {
+                self.close_link(link);
+            }
+            if let Some(url) = url {
+                self.end_list();
+                let start = self.buf.len();
+                if self.markdown {
+                    self.buf.push('[');
+                }
+                self.format.link = Some(Link { url, start, text: String::new() });
+            }
+        }
+        if !keep_strong && strong {
+            self.push_raw("**");
+        }
+        if !keep_emph && emph {
+            self.push_raw("*");
+        }
+
+        self.format.strong = strong;
+        self.format.emph = emph;
+    }
+
+    /// Close a link. Links whose body is the URL itself are written as
+    /// autolinks in Markdown and as is in plain text.
+    fn close_link(&mut self, link: Link) {
+        let end = self.buf.trim_end_matches(' ').len();
+        if link.url.as_str() == link.text.trim() {
+            if self.markdown {
+                self.buf.replace_range(link.start..end, &format!("<{}>", link.url));
+            }
+        } else if self.markdown {
+            self.buf.insert_str(end, &format!("]({})", link.url));
+        } else {
+            self.buf.insert_str(end, &format!(" ({})", link.url));
+        }
+    }
+
+    /// Close inline formatting in front of trailing spaces.
+    fn close(&mut self, marker: &str) {
+        let end = self.buf.trim_end_matches(' ').len();
+        self.buf.insert_str(end, marker);
+    }
+
+    /// Write into a separate buffer, e.g. for a list item's body.
+    fn render(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> SourceResult<()>,
+    ) -> SourceResult<String> {
+        let buf = std::mem::take(&mut self.buf);
+        let in_list = std::mem::replace(&mut self.in_list, false);
+        let format = std::mem::take(&mut self.format);
+        let result = f(self);
+        self.apply_format(StyleChain::default());
+        self.format = format;
+        self.in_list = in_list;
+        let text = std::mem::replace(&mut self.buf, buf);
+        result.map(|()| text.trim().into())
+    }
+
+    /// Write inline text, escaping it in Markdown.
+    fn push_text(&mut self, text: &str) {
+        if let Some(link) = &mut self.format.link {
+            link.text.push_str(text);
+        }
+        if self.markdown {
+            self.push_raw(&escape(text));
+        } else {
+            self.push_raw(text);
+        }
+    }
+
+    /// Write inline text as is.
+    fn push_raw(&mut self, text: &str) {
+        self.end_list();
+        self.buf.push_str(text);
+    }
+
+    /// Write a list item. Continuation lines are indented to the body.
+    fn push_item(&mut self, marker: &str, body: &str) {
+        if self.in_list {
+            self.newline();
+        } else {
+            self.start_block();
+        }
+        self.buf.push_str(marker);
+        let indent = " ".repeat(marker.chars().count());
+        self.buf.push_str(prefix_lines(body, &indent, "").trim_start());
+        self.in_list = true;
+    }
+
+    /// Prepare for inline content, which ends a preceding list.
+    fn end_list(&mut self) {
+        if self.in_list {
+            self.start_block();
+        }
+    }
+
+    /// Start a block that is not a list item.
+    fn start_block(&mut self) {
+        self.in_list = false;
+        self.parbreak();
+    }
+
+    fn space(&mut self) {
+        if !self.buf.is_empty() && !self.buf.ends_with(char::is_whitespace) {
+            self.buf.push(' ');
+            if let Some(link) = &mut self.format.link {
+                link.text.push(' ');
+            }
+        }
+    }
+
+    fn newline(&mut self) {
+        self.trim_end();
+        if !self.buf.is_empty() && !self.buf.ends_with('\n') {
+            self.buf.push('\n');
+        }
+    }
+
+    fn parbreak(&mut self) {
+        self.newline();
+        if !self.buf.is_empty() && !self.buf.ends_with("\n\n") {
+            self.buf.push('\n');
+        }
+    }
+
+    /// Remove trailing spaces, but not newlines.
+    fn trim_end(&mut self) {
+        let len = self.buf.trim_end_matches([' ', '\t']).len();
+        self.buf.truncate(len);
+    }
+
+    /// Finish the text and append the footnotes.
+    fn finish(self) -> String {
+        let mut out = self.buf.trim().to_string();
+        for (marker, footnote) in &self.footnotes {
+            out.push_str("\n\n");
+            if self.markdown {
+                out.push_str(&format!("[^{marker}]: "));
+                out.push_str(prefix_lines(footnote, "    ", "").trim_start());
+            } else {
+                out.push_str(&format!("[{marker}] {footnote}"));
+            }
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Whether the content is the end tag of the element at the location.
+fn is_end(content: &Content, loc: Location) -> bool {
+    content
+        .to_packed::<TagElem>()
+        .is_some_and(|elem| matches!(elem.tag, Tag::End(end, _) if end == loc))
+}
+
+/// Escape characters with a special meaning in Markdown.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '$') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Prefix each line of the text, using a different prefix for empty lines.
+fn prefix_lines(text: &str, prefix: &str, empty: &str) -> String {
+    text.lines()
+        .map(
+            |line| {
+                if line.is_empty() {
+                    empty.to_string()
+                } else {
+                    format!("{prefix}{line}")
+                }
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests;
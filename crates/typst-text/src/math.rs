@@ -0,0 +1,133 @@
+//! The text form of math content.
+//!
+//! Math is written in a notation close to Typst's math syntax, e.g. `x^2` or
+//! `(a + b)/2`, which reads well as plain text.
+
+use typst::foundations::{Content, SequenceElem, StyleChain, StyledElem, Value};
+use typst::math::{
+    AccentElem, AlignPointElem, AttachElem, BinomElem, CasesElem, FracElem, MatElem,
+    OpElem, PrimesElem, RootElem, VecElem,
+};
+use typst::text::{LinebreakElem, SpaceElem, TextElem};
+
+/// Write the text form of math content.
+fn math(content: &Content, styles: StyleChain, out: &mut String) {
+    if let Some(sequence) = content.to_packed::<SequenceElem>() {
+        for child in sequence.children() {
+            math(child, styles, out);
+        }
+    } else if let Some(styled) = content.to_packed::<StyledElem>() {
+        math(styled.child(), styles.chain(styled.styles()), out);
+    } else if let Some(elem) = content.to_packed::<TextElem>() {
+        out.push_str(elem.text());
+    } else if content.is::<SpaceElem>() {
+        out.push(' ');
+    } else if content.is::<LinebreakElem>() {
+        out.push('\n');
+    } else if content.is::<AlignPointElem>() {
+        // Alignment points have no text form.
+    } else if let Some(elem) = content.to_packed::<AttachElem>() {
+        math(elem.base(), styles, out);
+        let scripts = [
+            ("_", elem.b(styles)),
+            ("_", elem.br(styles)),
+            ("_", elem.bl(styles)),
+            ("^", elem.t(styles)),
+            ("^", elem.tr(styles)),
+            ("^", elem.tl(styles)),
+        ];
+        for (op, script) in scripts {
+            if let Some(script) = script.as_ref() {
+                out.push_str(op);
+                out.push_str(&group(script, styles));
+            }
+        }
+    } else if let Some(elem) = content.to_packed::<PrimesElem>() {
+        out.push_str(&"′".repeat(*elem.count()));
+    } else if let Some(elem) = content.to_packed::<FracElem>() {
+        out.push_str(&group(elem.num(), styles));
+        out.push('/');
+        out.push_str(&group(elem.denom(), styles));
+    } else if let Some(elem) = content.to_packed::<BinomElem>() {
+        let args = std::iter::once(elem.upper()).chain(elem.lower());
+        function("binom", args, styles, out);
+    } else if let Some(elem) = content.to_packed::<RootElem>() {
+        match elem.index(styles).as_ref() {
+            Some(index) => function("root", [index, elem.radicand()], styles, out),
+            None => {
+                out.push('√');
+                out.push_str(&group(elem.radicand(), styles));
+            }
+        }
+    } else if let Some(elem) = content.to_packed::<MatElem>() {
+        out.push_str("mat(");
+        for (i, row) in elem.rows().iter().enumerate() {
+            if i > 0 {
+                out.push_str("; ");
+            }
+            let cells: Vec<String> =
+                row.iter().map(|cell| to_math(cell, styles)).collect();
+            out.push_str(&cells.join(", "));
+        }
+        out.push(')');
+    } else if let Some(elem) = content.to_packed::<VecElem>() {
+        function("vec", elem.children(), styles, out);
+    } else if let Some(elem) = content.to_packed::<CasesElem>() {
+        function("cases", elem.children(), styles, out);
+    } else if let Some(elem) = content.to_packed::<AccentElem>() {
+        math(elem.base(), styles, out);
+        out.push(elem.accent().0);
+    } else if let Some(elem) = content.to_packed::<OpElem>() {
+        math(elem.text(), styles, out);
+    } else {
+        for (name, value) in content.fields() {
+            if name.as_str() != "label" {
+                math_value(&value, styles, out);
+            }
+        }
+    }
+}
+
+fn math_value(value: &Value, styles: StyleChain, out: &mut String) {
+    match value {
+        Value::Content(content) => math(content, styles, out),
+        Value::Array(array) => {
+            for value in array {
+                math_value(value, styles, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The text form of math content.
+pub fn to_math(content: &Content, styles: StyleChain) -> String {
+    let mut out = String::new();
+    math(content, styles, &mut out);
+    out.trim().into()
+}
+
+/// The text form of math content, in parentheses unless it's a single word
+/// or number.
+fn group(content: &Content, styles: StyleChain) -> String {
+    let text = to_math(content, styles);
+    if text.chars().count() <= 1 || text.chars().all(char::is_alphanumeric) {
+        text
+    } else {
+        format!("({text})")
+    }
+}
+
+/// Write math content as a function call.
+fn function<'a>(
+    name: &str,
+    args: impl IntoIterator<Item = &'a Content>,
+    styles: StyleChain,
+    out: &mut String,
+) {
+    let args: Vec<String> = args.into_iter().map(|arg| to_math(arg, styles)).collect();
+    out.push_str(name);
+    out.push('(');
+    out.push_str(&args.join(", "));
+    out.push(')');
+}
@@ -0,0 +1,179 @@
+use typst::diag::{FileError, FileResult};
+use typst::foundations::{Bytes, Datetime};
+use typst::syntax::{FileId, Source, VirtualPath};
+use typst::text::{Font, FontBook};
+use typst::utils::{singleton, LazyHash};
+use typst::{Library, World};
+
+use crate::{text, TextFormat};
+
+/// A world with a single source file.
+struct TestWorld {
+    main: Source,
+    base: &'static TestBase,
+}
+
+impl TestWorld {
+    fn new(text: &str) -> Self {
+        let id = FileId::new(None, VirtualPath::new("main.typ"));
+        Self {
+            main: Source::new(id, text.into()),
+            base: singleton!(TestBase, TestBase::default()),
+        }
+    }
+}
+
+impl World for TestWorld {
+    fn library(&self) -> &LazyHash<Library> {
+        &self.base.library
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        &self.base.book
+    }
+
+    fn main(&self) -> FileId {
+        self.main.id()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if id == self.main.id() {
+            Ok(self.main.clone())
+        } else {
+            Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+        }
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        Some(self.base.fonts[index].clone())
+    }
+
+    fn today(&self, _: Option<i64>) -> Option<Datetime> {
+        None
+    }
+}
+
+/// Shared foundation of all test worlds.
+struct TestBase {
+    library: LazyHash<Library>,
+    book: LazyHash<FontBook>,
+    fonts: Vec<Font>,
+}
+
+impl Default for TestBase {
+    fn default() -> Self {
+        let fonts: Vec<_> = typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .collect();
+
+        Self {
+            library: LazyHash::new(Library::default()),
+            book: LazyHash::new(FontBook::from_fonts(&fonts)),
+            fonts,
+        }
+    }
+}
+
+#[track_caller]
+fn test(source: &str, format: TextFormat, expected: &str) {
+    let world = TestWorld::new(source);
+    let document = typst::compile(&world).output.unwrap();
+    let output = text(&world, &document, format).output.unwrap();
+    assert_eq!(output, expected);
+}
+
+#[track_caller]
+fn test_both(source: &str, plain: &str, markdown: &str) {
+    test(source, TextFormat::Plain, plain);
+    test(source, TextFormat::Markdown, markdown);
+}
+
+#[test]
+fn test_text_headings() {
+    test_both(
+        "#set heading(numbering: \"1.\")\n= Intro\nSome *strong* text.\n== Details",
+        "1. Intro\n\nSome strong text.\n\n1.1. Details\n",
+        "# 1. Intro\n\nSome **strong** text.\n\n## 1.1. Details\n",
+    );
+}
+
+#[test]
+fn test_text_template() {
+    // The title only exists in the template and the page number is generated
+    // by a context expression.
+    let source = "\
+        #let template(title, body) = {\n\
+        \x20 set heading(numbering: \"I.\")\n\
+        \x20 block(text(17pt, title))\n\
+        \x20 body\n\
+        }\n\
+        #show: template.with[Report]\n\
+        \n\
+        #context [Page #here().page()]\n\
+        \n\
+        = Intro\n";
+    test_both(
+        source,
+        "Report\n\nPage 1\n\nI. Intro\n",
+        "Report\n\nPage 1\n\n# I. Intro\n",
+    );
+}
+
+#[test]
+fn test_text_lists() {
+    test_both(
+        "- One\n- _Two_\n\n+ First\n+ Second\n\n/ Term: Description",
+        "• One\n• Two\n\n1. First\n2. Second\n\nTerm Description\n",
+        "- One\n- *Two*\n\n1. First\n2. Second\n\n**Term** Description\n",
+    );
+}
+
+#[test]
+fn test_text_tables() {
+    test_both(
+        "#table(columns: 2, [*A*], [B], [1], [a | b])",
+        "A\tB\n1\ta | b\n",
+        "| **A** | B |\n| --- | --- |\n| 1 | a \\| b |\n",
+    );
+}
+
+#[test]
+fn test_text_footnotes() {
+    test_both(
+        "Text#footnote[Note *here*.] more.\n\n#footnote[First.] starts.",
+        "Text[1] more.\n\n[2] starts.\n\n[1] Note here.\n\n[2] First.\n",
+        "Text[^1] more.\n\n[^2] starts.\n\n[^1]: Note **here**.\n\n[^2]: First.\n",
+    );
+}
+
+#[test]
+fn test_text_math() {
+    test_both(
+        "Let $x^2 + 1$ be.\n\n$ a/b $",
+        "Let x^2 + 1 be.\n\na/b\n",
+        "Let $x^2 + 1$ be.\n\n$$\na/b\n$$\n",
+    );
+}
+
+#[test]
+fn test_text_links() {
+    test_both(
+        "See #link(\"https://typst.app\")[the *site*] or https://typst.app.",
+        "See the site (https://typst.app) or https://typst.app.\n",
+        "See [the **site**](https://typst.app) or <https://typst.app>.\n",
+    );
+}
+
+#[test]
+fn test_text_warnings() {
+    let world = TestWorld::new("Hello ** world");
+    let document = typst::compile(&world).output.unwrap();
+    let warned = text(&world, &document, TextFormat::Plain);
+    assert_eq!(warned.output.unwrap(), "Hello world\n");
+    assert_eq!(warned.warnings.len(), 1);
+    assert_eq!(warned.warnings[0].message.as_str(), "no text within stars");
+}
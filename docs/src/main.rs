@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 use clap::Parser;
 use typst::model::Document;
 use typst_docs::{provide, Html, Resolver};
-use typst_render::render;
+use typst_render::{render, RenderOptions};
 
 #[derive(Debug)]
 struct CliResolver<'a> {
@@ -35,7 +35,7 @@ impl<'a> Resolver for CliResolver<'a> {
         }
 
         let page = document.pages.first().expect("page 0");
-        let pixmap = render(page, 2.0);
+        let pixmap = render(page, &RenderOptions::new(2.0));
         let filename = format!("{hash:x}.png");
         let path = self.assets_dir.join(&filename);
         fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
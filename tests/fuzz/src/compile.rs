@@ -67,7 +67,10 @@ fuzz_target!(|text: &str| {
     let world = FuzzWorld::new(text);
     if let Ok(document) = typst::compile(&world).output {
         if let Some(page) = document.pages.first() {
-            std::hint::black_box(typst_render::render(page, 1.0));
+            std::hint::black_box(typst_render::render(
+                page,
+                &typst_render::RenderOptions::default(),
+            ));
         }
     }
     comemo::evict(10);
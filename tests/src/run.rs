@@ -10,6 +10,8 @@ use typst::model::Document;
 use typst::visualize::Color;
 use typst::WorldExt;
 use typst_pdf::PdfOptions;
+use typst_render::RenderOptions;
+use typst_svg::SvgOptions;
 
 use crate::collect::{FileSize, NoteKind, Test};
 use crate::logger::TestResult;
@@ -181,7 +183,8 @@ impl<'a> Runner<'a> {
         // Write SVG if requested.
         if crate::ARGS.svg() {
             let svg_path = format!("{}/svg/{}.svg", crate::STORE_PATH, self.test.name);
-            let svg = typst_svg::svg_merged(document, Abs::pt(5.0));
+            let svg =
+                typst_svg::svg_merged(document, Abs::pt(5.0), &SvgOptions::default());
             std::fs::write(svg_path, svg).unwrap();
         }
 
@@ -360,8 +363,9 @@ fn render(document: &Document, pixel_per_pt: f32) -> sk::Pixmap {
     }
 
     let gap = Abs::pt(1.0);
+    let options = RenderOptions::new(pixel_per_pt);
     let mut pixmap =
-        typst_render::render_merged(document, pixel_per_pt, gap, Some(Color::BLACK));
+        typst_render::render_merged(document, &options, gap, Some(Color::BLACK));
 
     let gap = (pixel_per_pt * gap.to_pt() as f32).round();
 
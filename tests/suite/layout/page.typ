@@ -74,6 +74,20 @@
 #set page(fill: none)
 #rect(fill: green)
 
+--- page-bleed-and-crop-marks ---
+// The bleed and crop marks only show up in PDF export.
+#set page(bleed: 3mm, crop-marks: true)
+#context test(page.bleed, 3mm)
+#context test(page.crop-marks, true)
+
+--- page-bleed-bad ---
+// Error: 18-22 expected length, found auto
+#set page(bleed: auto)
+
+--- page-bleed-negative ---
+// Error: 18-22 bleed must not be negative
+#set page(bleed: -3mm)
+
 --- page-margin-uniform ---
 // Set all margins at once.
 #[
@@ -16,6 +16,38 @@ What's up?
 #set document(author: (123,))
 What's up?
 
+--- document-set-metadata ---
+#set document(
+  subject: [A _short_ description],
+  lang: "de",
+  copyright: "© 2024 Typst GmbH",
+  license: "https://creativecommons.org/licenses/by/4.0/",
+  xmp: (
+    prism: (
+      namespace: "http://prismstandard.org/namespaces/basic/3.0/",
+      schema: "PRISM Basic Metadata",
+      properties: (
+        doi: "10.1000/182",
+        volume: 4,
+        pages: ("1", "2"),
+        keyword: (bag: ("metadata", "typesetting")),
+      ),
+    ),
+  ),
+)
+
+--- document-xmp-bad-prefix ---
+// Error: 20-80 `1x` is not a valid XMP namespace prefix
+#set document(xmp: ("1x": (namespace: "https://example.com/", properties: (:))))
+
+--- document-xmp-bad-list ---
+// Error: 20-93 expected a dictionary with either `bag` or `seq`
+#set document(xmp: (ex: (namespace: "https://example.com/", properties: (a: (set: ("x",))))))
+
+--- document-xmp-missing-properties ---
+// Error: 20-61 dictionary does not contain key "properties"
+#set document(xmp: (ex: (namespace: "https://example.com/")))
+
 --- document-set-after-content ---
 // Document set rules can appear anywhere in top-level realization, also after
 // content.
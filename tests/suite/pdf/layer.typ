@@ -0,0 +1,13 @@
+// Test the `pdf.layer` function.
+
+--- pdf-layer-fields ---
+#let layer = pdf.layer("Solutions", visible: false)[The answer is 4.]
+#test(layer.name, "Solutions")
+#test(layer.visible, false)
+#test(layer.body, [The answer is 4.])
+
+--- pdf-layer-no-layout-effect ---
+#context test(
+  measure(pdf.layer("Watermark", visible: false)[Draft]),
+  measure[Draft],
+)